@@ -2,5 +2,8 @@ export class AppConfig {
 	messageMaxLength: number = 1024;
 	fileUploadEnabled: boolean = false;
 	fileMaxSize: number = 0;
+	ttlMin: number = 60;
+	ttlMax: number = 604800;
+	ttlDefault: number = 3600;
 	localeId: string = 'en';
 }
@@ -2,9 +2,30 @@ export class Secret {
 	id: string = '';
 	payload: string = '';
 	contentType: SecretContentType = SecretContentType.Text;
-	ttl: SecretTTL = SecretTTL.OneHour;
-	downloadPolicy: SecretDownloadPolicy = SecretDownloadPolicy.OneTime;
+	ttl: SecretTTL | SecondsTTL | ExpiresAtTTL = SecretTTL.OneHour;
+	downloadPolicy: SecretDownloadPolicy | MaxViewsDownloadPolicy = SecretDownloadPolicy.OneTime;
+	remainingViews?: number;
 	metadata: FileMetadata = new FileMetadata();
+	/** `FileMetadata` JSON encrypted with the payload key, `metadata` is left empty then */
+	encryptedMetadata?: string;
+}
+
+export interface MaxViewsDownloadPolicy {
+	MaxViews: number;
+}
+
+export interface SecondsTTL {
+	Seconds: number;
+}
+
+export interface ExpiresAtTTL {
+	/** Unix timestamp in seconds */
+	ExpiresAt: number;
+}
+
+export interface StoredSecret {
+	id: string;
+	revocationToken: string;
 }
 
 export enum SecretContentType {
@@ -0,0 +1,12 @@
+import { error } from '@sveltejs/kit';
+import type { PageLoad } from './$types';
+
+export const load: PageLoad = ({ params }) => {
+	if (params.id) {
+		return {
+			secretId: params.id
+		};
+	}
+
+	throw error(404, 'Not found');
+};
@@ -0,0 +1,119 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::{Display, Formatter};
+
+use crate::secret::model::{Secret, SecretContentType, SecretDownloadPolicy};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Read,
+    Delete,
+    /// Expired secret was removed by the storage
+    Expire,
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            AuditAction::Create => "create",
+            AuditAction::Read => "read",
+            AuditAction::Delete => "delete",
+            AuditAction::Expire => "expire",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// Secret lifecycle event, secret id is hashed and payload, file metadata
+/// and revocation token are never included.
+///
+/// Secret details and client IP are missing for `expire` events, storages don't return
+/// expired secrets and no client causes the expiration.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    /// RFC 3339 UTC time
+    pub time: String,
+    pub action: AuditAction,
+    /// SHA-256 of secret id, hex
+    pub secret_id_hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<SecretContentType>,
+    /// Encrypted payload length in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_policy: Option<SecretDownloadPolicy>,
+}
+
+impl AuditEvent {
+    pub fn new(action: AuditAction, secret: &Secret, client_ip: &str) -> AuditEvent {
+        AuditEvent {
+            content_type: Some(secret.content_type.clone()),
+            size: Some(secret.payload_size()),
+            ttl_seconds: secret.ttl_seconds(),
+            download_policy: Some(secret.download_policy.clone()),
+            client_ip: Some(client_ip.to_string()),
+            ..AuditEvent::without_details(action, &secret.id)
+        }
+    }
+
+    pub fn expired(secret_id: &str) -> AuditEvent {
+        AuditEvent::without_details(AuditAction::Expire, secret_id)
+    }
+
+    fn without_details(action: AuditAction, secret_id: &str) -> AuditEvent {
+        AuditEvent {
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            action,
+            secret_id_hash: get_secret_id_hash(secret_id),
+            client_ip: None,
+            content_type: None,
+            size: None,
+            ttl_seconds: None,
+            download_policy: None,
+        }
+    }
+}
+
+pub fn get_secret_id_hash(secret_id: &str) -> String {
+    format!("{:x}", Sha256::digest(secret_id.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::secret::get_sample_secret;
+
+    #[test]
+    fn event_should_not_contain_secret_content() {
+        let secret = get_sample_secret();
+
+        let event = AuditEvent::new(AuditAction::Create, &secret, "10.0.0.1");
+        let json = serde_json::to_string(&event).unwrap();
+
+        assert!(!json.contains(&secret.id));
+        assert!(!json.contains(&secret.payload.to_base64()));
+        assert!(!json.contains(&secret.metadata.name));
+        assert_eq!(event.secret_id_hash, get_secret_id_hash(&secret.id));
+        assert_eq!(event.size, Some(secret.payload.len()));
+        assert!(json.contains(r#""action":"create""#));
+        assert!(json.contains(r#""client_ip":"10.0.0.1""#));
+    }
+
+    #[test]
+    fn expire_event_should_skip_details() {
+        let event = AuditEvent::expired("abc");
+        let json = serde_json::to_string(&event).unwrap();
+
+        assert!(json.contains(r#""action":"expire""#));
+        assert!(!json.contains("client_ip"));
+        assert!(!json.contains("size"));
+        assert!(!json.contains("download_policy"));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod event;
+pub mod service;
+pub mod sink;
+
+pub use event::{AuditAction, AuditEvent};
+pub use service::AuditLog;
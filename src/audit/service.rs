@@ -0,0 +1,69 @@
+use log::{error, info};
+use std::sync::Arc;
+
+use crate::audit::event::AuditEvent;
+use crate::audit::sink::{AuditSink, FileAuditSink, RedisAuditSink};
+use crate::config::model::{AppConfig, AuditBackend};
+use crate::secret::redis_pool::RedisConnectionPool;
+
+/// Records secret lifecycle events when audit is enabled.
+///
+/// Events are written before the response is sent, sink failures are logged
+/// and don't fail client requests.
+#[derive(Clone, Default)]
+pub struct AuditLog {
+    sink: Option<Arc<dyn AuditSink>>,
+}
+
+impl AuditLog {
+    /// Redis pool of the secret storage is reused when available
+    pub async fn new(
+        config: &AppConfig,
+        redis_pool: Option<&RedisConnectionPool>,
+    ) -> anyhow::Result<AuditLog> {
+        let audit = &config.audit;
+
+        if !audit.enabled {
+            info!("audit log is disabled");
+            return Ok(AuditLog::disabled());
+        }
+
+        let sink: Arc<dyn AuditSink> = match audit.backend {
+            AuditBackend::File => Arc::new(FileAuditSink::open(&audit.file.path).await?),
+            AuditBackend::Redis => {
+                let redis_pool = match redis_pool {
+                    Some(redis_pool) => redis_pool.clone(),
+                    None => RedisConnectionPool::new(&config.redis_url, &config.redis_pool)?,
+                };
+                info!(
+                    "audit events are added to redis stream '{}'",
+                    audit.redis.stream
+                );
+                Arc::new(RedisAuditSink::new(redis_pool, audit.redis.clone()))
+            }
+        };
+
+        Ok(AuditLog { sink: Some(sink) })
+    }
+
+    pub fn disabled() -> AuditLog {
+        AuditLog::default()
+    }
+
+    pub fn with_sink(sink: Arc<dyn AuditSink>) -> AuditLog {
+        AuditLog { sink: Some(sink) }
+    }
+
+    pub async fn record(&self, event: AuditEvent) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+
+        if let Err(e) = sink.write(&event).await {
+            error!(
+                "unable to record audit event '{}' for secret {}: {:#}",
+                event.action, event.secret_id_hash, e
+            );
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::Context;
+use async_trait::async_trait;
+use log::info;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::audit::event::AuditEvent;
+use crate::config::model::AuditRedisConfig;
+use crate::secret::redis_pool::RedisConnectionPool;
+
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn write(&self, event: &AuditEvent) -> anyhow::Result<()>;
+}
+
+/// Appends JSON line per event, file is flushed after every write
+pub struct FileAuditSink {
+    path: String,
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    pub async fn open(path: &str) -> anyhow::Result<FileAuditSink> {
+        info!("open audit file '{path}'");
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await
+            .with_context(|| format!("unable to open audit file '{path}'"))?;
+
+        Ok(FileAuditSink {
+            path: path.to_string(),
+            file: Mutex::new(file),
+        })
+    }
+}
+
+#[async_trait]
+impl AuditSink for FileAuditSink {
+    async fn write(&self, event: &AuditEvent) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(event).context("audit event serialization error")?;
+        line.push(b'\n');
+
+        let mut file = self.file.lock().await;
+        file.write_all(&line)
+            .await
+            .with_context(|| format!("unable to write audit file '{}'", self.path))?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+/// Adds stream entry per event, event fields become entry fields
+pub struct RedisAuditSink {
+    pool: RedisConnectionPool,
+    config: AuditRedisConfig,
+}
+
+impl RedisAuditSink {
+    pub fn new(pool: RedisConnectionPool, config: AuditRedisConfig) -> RedisAuditSink {
+        RedisAuditSink { pool, config }
+    }
+}
+
+#[async_trait]
+impl AuditSink for RedisAuditSink {
+    async fn write(&self, event: &AuditEvent) -> anyhow::Result<()> {
+        let mut cnn = self.pool.get().await?;
+
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(&self.config.stream);
+
+        if self.config.max_len > 0 {
+            cmd.arg("MAXLEN").arg("~").arg(self.config.max_len);
+        }
+
+        cmd.arg("*");
+
+        for (name, value) in get_fields(event)? {
+            cmd.arg(name).arg(value);
+        }
+
+        let _: String = cmd.query_async(&mut cnn).await?;
+
+        Ok(())
+    }
+}
+
+/// Strings are written as is, other values as JSON
+fn get_fields(event: &AuditEvent) -> anyhow::Result<Vec<(String, String)>> {
+    let serde_json::Value::Object(fields) = serde_json::to_value(event)? else {
+        anyhow::bail!("audit event must be serialized into object");
+    };
+
+    Ok(fields
+        .into_iter()
+        .map(|(name, value)| match value {
+            serde_json::Value::String(value) => (name, value),
+            value => (name, value.to_string()),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::event::AuditAction;
+    use crate::config::model::RedisPoolConfig;
+    use crate::secret::model::SecretDownloadPolicy;
+    use crate::secret::storage::DEFAULT_REDIS_CNN_URL;
+    use crate::tests::secret::get_sample_secret;
+
+    #[tokio::test]
+    async fn events_should_be_appended_to_file() {
+        let path = std::env::temp_dir().join(format!("pw-audit-{}.log", uuid::Uuid::new_v4()));
+        let path = path.to_str().unwrap().to_string();
+
+        let secret = get_sample_secret();
+
+        let sink = FileAuditSink::open(&path).await.unwrap();
+        sink.write(&AuditEvent::new(AuditAction::Create, &secret, "10.0.0.1"))
+            .await
+            .unwrap();
+        sink.write(&AuditEvent::new(AuditAction::Read, &secret, "10.0.0.2"))
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["action"], "create");
+        assert_eq!(lines[1]["action"], "read");
+        assert_eq!(lines[1]["client_ip"], "10.0.0.2");
+        assert!(!content.contains(&secret.payload.to_base64()));
+    }
+
+    #[test]
+    fn stream_fields_should_be_flat_strings() {
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(3);
+
+        let fields = get_fields(&AuditEvent::new(AuditAction::Read, &secret, "10.0.0.1")).unwrap();
+
+        assert!(fields.contains(&("action".to_string(), "read".to_string())));
+        assert!(fields.contains(&("ttl_seconds".to_string(), "3600".to_string())));
+        assert!(fields.contains(&(
+            "download_policy".to_string(),
+            r#"{"MaxViews":3}"#.to_string()
+        )));
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn events_should_be_added_to_redis_stream() {
+        let pool =
+            RedisConnectionPool::new(DEFAULT_REDIS_CNN_URL, &RedisPoolConfig::default()).unwrap();
+        let stream = format!("pw:audit-test:{}", uuid::Uuid::new_v4());
+
+        let sink = RedisAuditSink::new(
+            pool.clone(),
+            AuditRedisConfig {
+                stream: stream.clone(),
+                max_len: 10,
+            },
+        );
+
+        let secret = get_sample_secret();
+        sink.write(&AuditEvent::new(AuditAction::Create, &secret, "10.0.0.1"))
+            .await
+            .unwrap();
+
+        let mut cnn = pool.get().await.unwrap();
+        let length: u64 = redis::cmd("XLEN")
+            .arg(&stream)
+            .query_async(&mut cnn)
+            .await
+            .unwrap();
+        let _: () = redis::cmd("DEL")
+            .arg(&stream)
+            .query_async(&mut cnn)
+            .await
+            .unwrap();
+
+        assert_eq!(length, 1);
+    }
+}
@@ -0,0 +1,88 @@
+use clap::{Parser, Subcommand};
+
+use crate::VERSION;
+
+pub const DEFAULT_CONFIG_FILE: &str = "pw.yml";
+
+#[derive(Parser, Debug)]
+#[command(name = "pw", version = VERSION, about = "Share secrets with one-time links")]
+pub struct Cli {
+    /// Server is started when no command is given
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// Start the server
+    Serve {
+        /// Config file path
+        #[arg(short, long, default_value = DEFAULT_CONFIG_FILE)]
+        config: String,
+    },
+
+    /// Load and validate config, print effective values with secrets redacted
+    CheckConfig {
+        /// Config file path
+        #[arg(short, long, default_value = DEFAULT_CONFIG_FILE)]
+        config: String,
+    },
+
+    /// Print version
+    Version,
+}
+
+impl Cli {
+    pub fn get_command(self) -> Command {
+        self.command.unwrap_or(Command::Serve {
+            config: DEFAULT_CONFIG_FILE.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_should_be_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn serve_should_be_default_command() {
+        let cli = Cli::try_parse_from(["pw"]).unwrap();
+        assert_eq!(
+            cli.get_command(),
+            Command::Serve {
+                config: DEFAULT_CONFIG_FILE.to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn config_path_should_be_parsed() {
+        let cli =
+            Cli::try_parse_from(["pw", "check-config", "--config", "/etc/pw/pw.yml"]).unwrap();
+        assert_eq!(
+            cli.get_command(),
+            Command::CheckConfig {
+                config: "/etc/pw/pw.yml".to_string()
+            }
+        );
+
+        let cli = Cli::try_parse_from(["pw", "serve", "-c", "custom.yml"]).unwrap();
+        assert_eq!(
+            cli.get_command(),
+            Command::Serve {
+                config: "custom.yml".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_command_should_be_rejected() {
+        assert!(Cli::try_parse_from(["pw", "start"]).is_err());
+    }
+}
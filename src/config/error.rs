@@ -0,0 +1,282 @@
+use config::ConfigError;
+use std::fmt::{Display, Formatter};
+
+/// Config loading failure, `line` points to the config file when the failed key is found there
+#[derive(Debug, PartialEq)]
+pub struct ConfigFileError {
+    pub path: String,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigFileError {
+    pub fn new(path: &str, error: &anyhow::Error) -> ConfigFileError {
+        let Some(config_error) = error.downcast_ref::<ConfigError>() else {
+            return ConfigFileError {
+                path: path.to_string(),
+                line: None,
+                message: format!("{error:#}"),
+            };
+        };
+
+        let line = match config_error {
+            ConfigError::FileParse { cause, .. } => get_parse_error_line(&cause.to_string()),
+            _ => get_error_key(config_error, path).and_then(|key| {
+                let content = std::fs::read_to_string(path).ok()?;
+                find_key_line(&content, &key)
+            }),
+        };
+
+        ConfigFileError {
+            path: path.to_string(),
+            line,
+            message: describe_config_error(config_error),
+        }
+    }
+}
+
+impl Display for ConfigFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path, line, self.message),
+            None => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigFileError {}
+
+/// Same as `ConfigError` display but without origin, file path is reported separately
+fn describe_config_error(error: &ConfigError) -> String {
+    match error {
+        ConfigError::Type {
+            unexpected,
+            expected,
+            key,
+            ..
+        } => with_key(
+            format!("invalid type: {unexpected}, expected {expected}"),
+            key,
+        ),
+        ConfigError::At { error, key, .. } => with_key(describe_config_error(error), key),
+        ConfigError::FileParse { cause, .. } => cause.to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn with_key(message: String, key: &Option<String>) -> String {
+    match key {
+        Some(key) => format!("{message} for key `{key}`"),
+        None => message,
+    }
+}
+
+/// Key is ignored when the value came from another source, e.g. environment variables
+fn get_error_key(error: &ConfigError, path: &str) -> Option<String> {
+    let (origin, key) = match error {
+        ConfigError::Type { origin, key, .. } | ConfigError::At { origin, key, .. } => {
+            (origin, key)
+        }
+        _ => return None,
+    };
+
+    match origin {
+        Some(origin) if !is_same_file(origin, path) => None,
+        _ => key.clone(),
+    }
+}
+
+/// Origin of file values is relative to the current directory
+fn is_same_file(origin: &str, path: &str) -> bool {
+    match (std::fs::canonicalize(origin), std::fs::canonicalize(path)) {
+        (Ok(origin), Ok(path)) => origin == path,
+        _ => false,
+    }
+}
+
+/// YAML parser reports position as `... at byte 10 line 2 column 5`
+fn get_parse_error_line(message: &str) -> Option<usize> {
+    let (_, position) = message.rsplit_once(" line ")?;
+    position.split_whitespace().next()?.parse().ok()
+}
+
+/// Finds 1-based line of a dotted key like `rate-limits.create.requests` or
+/// `ip-limits.whitelist[1].ip`, falls back to the deepest parent key found.
+fn find_key_line(content: &str, key: &str) -> Option<usize> {
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut found = None;
+    let mut start = 0;
+    let mut min_indent = 0;
+
+    for segment in key.split('.') {
+        let (name, index) = match segment.split_once('[') {
+            Some((name, index)) => (name, index.trim_end_matches(']').parse::<usize>().ok()),
+            None => (segment, None),
+        };
+
+        let Some((line_index, indent)) = find_child_key(&lines, start, min_indent, name) else {
+            break;
+        };
+        found = Some(line_index);
+        start = line_index + 1;
+        min_indent = indent + 1;
+
+        if let Some(index) = index {
+            let Some((item_index, item_indent)) = find_list_item(&lines, start, index) else {
+                break;
+            };
+            found = Some(item_index);
+            start = item_index;
+            min_indent = item_indent;
+        }
+    }
+
+    found.map(|line_index| line_index + 1)
+}
+
+fn get_indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn is_significant(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn find_child_key(
+    lines: &[&str],
+    start: usize,
+    min_indent: usize,
+    name: &str,
+) -> Option<(usize, usize)> {
+    for (line_index, line) in lines.iter().enumerate().skip(start) {
+        if !is_significant(line) {
+            continue;
+        }
+
+        let indent = get_indent(line);
+        if indent < min_indent {
+            return None;
+        }
+
+        let trimmed = line.trim_start();
+        let (indent, trimmed) = match trimmed.strip_prefix("- ") {
+            // Next item of the same list ends the current one
+            Some(_) if line_index > start && indent <= min_indent => return None,
+            Some(item) => (indent + 2, item.trim_start()),
+            None => (indent, trimmed),
+        };
+
+        if trimmed
+            .strip_prefix(name)
+            .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        {
+            return Some((line_index, indent));
+        }
+    }
+
+    None
+}
+
+/// First significant line after the list key must be an item
+fn find_list_item(lines: &[&str], start: usize, index: usize) -> Option<(usize, usize)> {
+    let mut item_indent = None;
+    let mut count = 0;
+
+    for (line_index, line) in lines.iter().enumerate().skip(start) {
+        if !is_significant(line) {
+            continue;
+        }
+
+        let indent = get_indent(line);
+        let is_item = line.trim_start().starts_with('-');
+
+        match item_indent {
+            None if !is_item => return None,
+            None => item_indent = Some(indent),
+            Some(item_indent) if indent < item_indent || (indent == item_indent && !is_item) => {
+                return None;
+            }
+            Some(item_indent) if indent > item_indent => continue,
+            Some(_) => {}
+        }
+
+        if count == index {
+            return Some((line_index, indent));
+        }
+        count += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "\
+listen: 0.0.0.0:8080
+# comment
+rate-limits:
+  enabled: true
+  create:
+    requests: 10
+    period-seconds: 60
+  read:
+    requests: abc
+ip-limits:
+  enabled: true
+  whitelist:
+    - ip: 10.0.0.1
+    - ip: 10.0.0.2
+      message-max-length: big
+";
+
+    #[test]
+    fn nested_key_line_should_be_found() {
+        assert_eq!(find_key_line(CONTENT, "listen"), Some(1));
+        assert_eq!(find_key_line(CONTENT, "rate-limits.read.requests"), Some(9));
+        assert_eq!(find_key_line(CONTENT, "rate-limits.create"), Some(5));
+    }
+
+    #[test]
+    fn list_item_key_line_should_be_found() {
+        assert_eq!(
+            find_key_line(CONTENT, "ip-limits.whitelist[0].ip"),
+            Some(13)
+        );
+        assert_eq!(
+            find_key_line(CONTENT, "ip-limits.whitelist[1].ip"),
+            Some(14)
+        );
+        assert_eq!(
+            find_key_line(CONTENT, "ip-limits.whitelist[1].message-max-length"),
+            Some(15)
+        );
+    }
+
+    #[test]
+    fn missing_key_should_fall_back_to_parent() {
+        assert_eq!(
+            find_key_line(CONTENT, "rate-limits.delete.requests"),
+            Some(3)
+        );
+        assert_eq!(find_key_line(CONTENT, "storage.backend"), None);
+    }
+
+    #[test]
+    fn parse_error_line_should_be_extracted() {
+        assert_eq!(
+            get_parse_error_line("mapping values are not allowed at byte 12 line 3 column 9"),
+            Some(3)
+        );
+        assert_eq!(get_parse_error_line("unexpected end"), None);
+    }
+
+    #[test]
+    fn error_without_location_should_be_displayed_with_path() {
+        let error = ConfigFileError::new("pw.yml", &anyhow::anyhow!("ttl-min cannot be zero"));
+        assert_eq!(error.to_string(), "pw.yml: ttl-min cannot be zero");
+    }
+}
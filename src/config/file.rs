@@ -4,12 +4,27 @@ use std::env;
 use config::{Config, File};
 use serde_json;
 
-use super::model::{AppConfig, IpLimitEntry, IpLimitsConfig};
-use super::validation::{format_validation_errors, validate_ip_limits_config};
-
-pub fn load_config_from_file(file_path: &str) -> anyhow::Result<AppConfig> {
+use super::error::ConfigFileError;
+use super::model::{
+    AppConfig, AuditConfig, AuditFileConfig, AuditRedisConfig, BasicAuthConfig,
+    DEFAULT_TLS_RELOAD_INTERVAL_SECONDS, EmbeddedStorageConfig, IpLimitEntry, IpLimitsConfig,
+    LogFileConfig, LookupGuardConfig, MemoryStorageConfig, MetricsAccessConfig, RateLimitRule,
+    RateLimitsConfig, RedisPoolConfig, StorageConfig, StorageEncryptionConfig, StorageKeyConfig,
+    TlsConfig, TlsVersion,
+};
+use super::validation::{
+    format_validation_errors, validate_app_config, validate_ip_limits_config,
+    validate_metrics_access_config, validate_rate_limit_rule,
+};
+
+/// Loads config with environment overrides, errors point to the config file line when possible
+pub fn load_config_from_file(file_path: &str) -> Result<AppConfig, ConfigFileError> {
     info!("load config from file '{file_path}'");
 
+    read_config(file_path).map_err(|e| ConfigFileError::new(file_path, &e))
+}
+
+fn read_config(file_path: &str) -> anyhow::Result<AppConfig> {
     let config_builder = Config::builder()
         .add_source(
             config::Environment::with_prefix("PW")
@@ -25,30 +40,75 @@ pub fn load_config_from_file(file_path: &str) -> anyhow::Result<AppConfig> {
     let listen = get_env_var("PW_LISTEN").unwrap_or(config.listen.to_string());
     let log_level = get_env_var("PW_LOG_LEVEL").unwrap_or(config.log_level);
     let log_target = get_env_var("PW_LOG_TARGET").unwrap_or(config.log_target);
+    let log_format = get_env_var("PW_LOG_FORMAT").unwrap_or(config.log_format);
+    let log_file = get_log_file_config(config.log_file)?;
     let message_max_length =
         get_env_var("PW_MESSAGE_MAX_LENGTH").unwrap_or(config.message_max_length.to_string());
     let file_upload_enabled =
         get_env_var("PW_FILE_UPLOAD_ENABLED").unwrap_or(config.file_upload_enabled.to_string());
     let file_max_size = get_env_var("PW_FILE_MAX_SIZE").unwrap_or(config.file_max_size.to_string());
+    let file_chunk_max_size =
+        get_env_var("PW_FILE_CHUNK_MAX_SIZE").unwrap_or(config.file_chunk_max_size.to_string());
     let encrypted_message_max_length = get_env_var("PW_ENCRYPTED_MESSAGE_MAX_LENGTH")
         .and_then(|v| v.parse::<u64>().ok())
         .or(config.encrypted_message_max_length);
     let redis_url = get_env_var("PW_REDIS_URL").unwrap_or(config.redis_url);
+    let redis_pool = get_redis_pool_config(config.redis_pool)?;
+    let storage = get_storage_config(config.storage)?;
+    let ttl_min = get_env_var("PW_TTL_MIN").unwrap_or(config.ttl_min.to_string());
+    let ttl_max = get_env_var("PW_TTL_MAX").unwrap_or(config.ttl_max.to_string());
+    let ttl_default = get_env_var("PW_TTL_DEFAULT").unwrap_or(config.ttl_default.to_string());
+    let rate_limits = get_rate_limits_config(config.rate_limits)?;
+    let lookup_guard = get_lookup_guard_config(config.lookup_guard)?;
+    let audit = get_audit_config(config.audit)?;
 
     let ip_limits = get_ip_limits_config(config.ip_limits)?;
 
+    let metrics_listen = get_env_var("PW_METRICS_LISTEN").or(config.metrics_listen);
+    let metrics_access = get_metrics_access_config(config.metrics_access)?;
+    let tls = get_tls_config(config.tls)?;
+    let shutdown_drain_timeout_seconds = get_env_var("PW_SHUTDOWN_DRAIN_TIMEOUT_SECONDS")
+        .unwrap_or(config.shutdown_drain_timeout_seconds.to_string());
+    let shutdown_pre_stop_delay_seconds = get_env_var("PW_SHUTDOWN_PRE_STOP_DELAY_SECONDS")
+        .unwrap_or(config.shutdown_pre_stop_delay_seconds.to_string());
+    let config_reload_interval_seconds = get_env_var("PW_CONFIG_RELOAD_INTERVAL_SECONDS")
+        .unwrap_or(config.config_reload_interval_seconds.to_string());
+
     let config = AppConfig {
         listen: listen.parse()?,
         log_level,
         log_target,
+        log_format,
+        log_file,
         message_max_length: message_max_length.parse()?,
         encrypted_message_max_length,
         file_upload_enabled: file_upload_enabled.parse()?,
         file_max_size: file_max_size.parse()?,
+        file_chunk_max_size: file_chunk_max_size.parse()?,
         redis_url,
+        redis_pool,
+        storage,
+        ttl_min: ttl_min.parse()?,
+        ttl_max: ttl_max.parse()?,
+        ttl_default: ttl_default.parse()?,
+        rate_limits,
+        lookup_guard,
+        audit,
         ip_limits,
+        metrics_listen,
+        metrics_access,
+        tls,
+        shutdown_drain_timeout_seconds: shutdown_drain_timeout_seconds.parse()?,
+        shutdown_pre_stop_delay_seconds: shutdown_pre_stop_delay_seconds.parse()?,
+        config_reload_interval_seconds: config_reload_interval_seconds.parse()?,
     };
 
+    if let Err(validation_errors) = validate_app_config(&config) {
+        return Err(anyhow::anyhow!(format_validation_errors(
+            &validation_errors
+        )));
+    }
+
     info!("config: {}", config);
 
     Ok(config)
@@ -58,6 +118,265 @@ fn get_env_var(name: &str) -> Option<String> {
     env::var(name).ok()
 }
 
+fn get_log_file_config(yaml_config: LogFileConfig) -> anyhow::Result<LogFileConfig> {
+    let path = get_env_var("PW_LOG_FILE_PATH").unwrap_or(yaml_config.path);
+    let max_size = get_env_var("PW_LOG_FILE_MAX_SIZE").unwrap_or(yaml_config.max_size.to_string());
+    let max_files =
+        get_env_var("PW_LOG_FILE_MAX_FILES").unwrap_or(yaml_config.max_files.to_string());
+
+    Ok(LogFileConfig {
+        path,
+        max_size: max_size.parse()?,
+        max_files: max_files.parse()?,
+    })
+}
+
+fn get_redis_pool_config(yaml_config: RedisPoolConfig) -> anyhow::Result<RedisPoolConfig> {
+    let size = get_env_var("PW_REDIS_POOL_SIZE").unwrap_or(yaml_config.size.to_string());
+    let connect_timeout_ms = get_env_var("PW_REDIS_POOL_CONNECT_TIMEOUT_MS")
+        .unwrap_or(yaml_config.connect_timeout_ms.to_string());
+    let command_timeout_ms = get_env_var("PW_REDIS_POOL_COMMAND_TIMEOUT_MS")
+        .unwrap_or(yaml_config.command_timeout_ms.to_string());
+
+    Ok(RedisPoolConfig {
+        size: size.parse()?,
+        connect_timeout_ms: connect_timeout_ms.parse()?,
+        command_timeout_ms: command_timeout_ms.parse()?,
+    })
+}
+
+fn get_storage_config(yaml_config: StorageConfig) -> anyhow::Result<StorageConfig> {
+    let backend = match get_env_var("PW_STORAGE_BACKEND") {
+        Some(value) => value.parse()?,
+        None => yaml_config.backend,
+    };
+    let compression = match get_env_var("PW_STORAGE_COMPRESSION") {
+        Some(value) => value.parse()?,
+        None => yaml_config.compression,
+    };
+    let embedded_path =
+        get_env_var("PW_STORAGE_EMBEDDED_PATH").unwrap_or(yaml_config.embedded.path);
+    let embedded_sweep_interval_seconds = get_env_var("PW_STORAGE_EMBEDDED_SWEEP_INTERVAL_SECONDS")
+        .unwrap_or(yaml_config.embedded.sweep_interval_seconds.to_string());
+
+    let memory_max_bytes = get_env_var("PW_STORAGE_MEMORY_MAX_BYTES")
+        .unwrap_or(yaml_config.memory.max_bytes.to_string());
+    let memory_max_entries = get_env_var("PW_STORAGE_MEMORY_MAX_ENTRIES")
+        .unwrap_or(yaml_config.memory.max_entries.to_string());
+    let memory_sweep_interval_seconds = get_env_var("PW_STORAGE_MEMORY_SWEEP_INTERVAL_SECONDS")
+        .unwrap_or(yaml_config.memory.sweep_interval_seconds.to_string());
+
+    Ok(StorageConfig {
+        backend,
+        compression,
+        encryption: get_storage_encryption_config(yaml_config.encryption)?,
+        embedded: EmbeddedStorageConfig {
+            path: embedded_path,
+            sweep_interval_seconds: embedded_sweep_interval_seconds.parse()?,
+        },
+        memory: MemoryStorageConfig {
+            max_bytes: memory_max_bytes.parse()?,
+            max_entries: memory_max_entries.parse()?,
+            sweep_interval_seconds: memory_sweep_interval_seconds.parse()?,
+        },
+    })
+}
+
+/// `PW_STORAGE_ENCRYPTION_KEY` or `PW_STORAGE_ENCRYPTION_KEY_FILE` replace the key with `key-id`
+fn get_storage_encryption_config(
+    yaml_config: StorageEncryptionConfig,
+) -> anyhow::Result<StorageEncryptionConfig> {
+    let enabled =
+        get_env_var("PW_STORAGE_ENCRYPTION_ENABLED").unwrap_or(yaml_config.enabled.to_string());
+    let key_id = get_env_var("PW_STORAGE_ENCRYPTION_KEY_ID").unwrap_or(yaml_config.key_id);
+
+    let mut keys = yaml_config.keys;
+
+    let key = get_env_var("PW_STORAGE_ENCRYPTION_KEY");
+    let key_file = get_env_var("PW_STORAGE_ENCRYPTION_KEY_FILE");
+
+    if key.is_some() || key_file.is_some() {
+        keys.retain(|entry| entry.id != key_id);
+        keys.push(StorageKeyConfig {
+            id: key_id.clone(),
+            key,
+            key_file,
+        });
+    }
+
+    Ok(StorageEncryptionConfig {
+        enabled: enabled.parse()?,
+        key_id,
+        keys,
+    })
+}
+
+fn get_rate_limits_config(yaml_config: RateLimitsConfig) -> anyhow::Result<RateLimitsConfig> {
+    let enabled = get_env_var("PW_RATE_LIMITS_ENABLED").unwrap_or(yaml_config.enabled.to_string());
+    let backend = match get_env_var("PW_RATE_LIMITS_BACKEND") {
+        Some(value) => value.parse()?,
+        None => yaml_config.backend,
+    };
+
+    let rate_limits = RateLimitsConfig {
+        enabled: enabled.parse()?,
+        backend,
+        create: get_rate_limit_rule("PW_RATE_LIMITS_CREATE", yaml_config.create)?,
+        read: get_rate_limit_rule("PW_RATE_LIMITS_READ", yaml_config.read)?,
+        delete: get_rate_limit_rule("PW_RATE_LIMITS_DELETE", yaml_config.delete)?,
+    };
+
+    for (name, rule) in [
+        ("create", &rate_limits.create),
+        ("read", &rate_limits.read),
+        ("delete", &rate_limits.delete),
+    ] {
+        validate_rate_limit_rule(name, rule)
+            .map_err(|e| anyhow::anyhow!("Rate limits configuration validation failed: {}", e))?;
+    }
+
+    Ok(rate_limits)
+}
+
+fn get_rate_limit_rule(prefix: &str, yaml_rule: RateLimitRule) -> anyhow::Result<RateLimitRule> {
+    let requests =
+        get_env_var(&format!("{prefix}_REQUESTS")).unwrap_or(yaml_rule.requests.to_string());
+    let period_seconds = get_env_var(&format!("{prefix}_PERIOD_SECONDS"))
+        .unwrap_or(yaml_rule.period_seconds.to_string());
+
+    Ok(RateLimitRule {
+        requests: requests.parse()?,
+        period_seconds: period_seconds.parse()?,
+    })
+}
+
+fn get_lookup_guard_config(yaml_config: LookupGuardConfig) -> anyhow::Result<LookupGuardConfig> {
+    let enabled = get_env_var("PW_LOOKUP_GUARD_ENABLED").unwrap_or(yaml_config.enabled.to_string());
+    let max_failures =
+        get_env_var("PW_LOOKUP_GUARD_MAX_FAILURES").unwrap_or(yaml_config.max_failures.to_string());
+    let failure_window_seconds = get_env_var("PW_LOOKUP_GUARD_FAILURE_WINDOW_SECONDS")
+        .unwrap_or(yaml_config.failure_window_seconds.to_string());
+    let ban_seconds =
+        get_env_var("PW_LOOKUP_GUARD_BAN_SECONDS").unwrap_or(yaml_config.ban_seconds.to_string());
+    let max_ban_seconds = get_env_var("PW_LOOKUP_GUARD_MAX_BAN_SECONDS")
+        .unwrap_or(yaml_config.max_ban_seconds.to_string());
+
+    let lookup_guard = LookupGuardConfig {
+        enabled: enabled.parse()?,
+        max_failures: max_failures.parse()?,
+        failure_window_seconds: failure_window_seconds.parse()?,
+        ban_seconds: ban_seconds.parse()?,
+        max_ban_seconds: max_ban_seconds.parse()?,
+    };
+
+    if lookup_guard.enabled && (lookup_guard.max_failures == 0 || lookup_guard.ban_seconds == 0) {
+        return Err(anyhow::anyhow!(
+            "Lookup guard configuration validation failed: max-failures and ban-seconds must be greater than zero"
+        ));
+    }
+
+    Ok(lookup_guard)
+}
+
+fn get_audit_config(yaml_config: AuditConfig) -> anyhow::Result<AuditConfig> {
+    let enabled = get_env_var("PW_AUDIT_ENABLED").unwrap_or(yaml_config.enabled.to_string());
+    let backend = match get_env_var("PW_AUDIT_BACKEND") {
+        Some(value) => value.parse()?,
+        None => yaml_config.backend,
+    };
+    let file_path = get_env_var("PW_AUDIT_FILE_PATH").unwrap_or(yaml_config.file.path);
+    let redis_stream = get_env_var("PW_AUDIT_REDIS_STREAM").unwrap_or(yaml_config.redis.stream);
+    let redis_max_len =
+        get_env_var("PW_AUDIT_REDIS_MAX_LEN").unwrap_or(yaml_config.redis.max_len.to_string());
+
+    Ok(AuditConfig {
+        enabled: enabled.parse()?,
+        backend,
+        file: AuditFileConfig { path: file_path },
+        redis: AuditRedisConfig {
+            stream: redis_stream,
+            max_len: redis_max_len.parse()?,
+        },
+    })
+}
+
+fn get_metrics_access_config(
+    yaml_config: MetricsAccessConfig,
+) -> anyhow::Result<MetricsAccessConfig> {
+    let mut basic_auth = yaml_config.basic_auth;
+
+    let username = get_env_var("PW_METRICS_ACCESS_BASIC_AUTH_USERNAME");
+    let password = get_env_var("PW_METRICS_ACCESS_BASIC_AUTH_PASSWORD");
+
+    if username.is_some() || password.is_some() {
+        let yaml_auth = basic_auth.unwrap_or(BasicAuthConfig {
+            username: String::new(),
+            password: String::new(),
+        });
+        basic_auth = Some(BasicAuthConfig {
+            username: username.unwrap_or(yaml_auth.username),
+            password: password.unwrap_or(yaml_auth.password),
+        });
+    }
+
+    let allowed_ips = match get_env_var("PW_METRICS_ACCESS_ALLOWED_IPS") {
+        Some(allowed_ips_json) => serde_json::from_str(&allowed_ips_json).map_err(|e| {
+            anyhow::anyhow!("Failed to parse PW_METRICS_ACCESS_ALLOWED_IPS JSON: {}", e)
+        })?,
+        None => yaml_config.allowed_ips,
+    };
+
+    let metrics_access = MetricsAccessConfig {
+        basic_auth,
+        allowed_ips,
+    };
+
+    if let Err(validation_errors) = validate_metrics_access_config(&metrics_access) {
+        let error_message = format_validation_errors(&validation_errors);
+        return Err(anyhow::anyhow!(
+            "Metrics access configuration validation failed:\n{}",
+            error_message
+        ));
+    }
+
+    Ok(metrics_access)
+}
+
+fn get_tls_config(yaml_config: Option<TlsConfig>) -> anyhow::Result<Option<TlsConfig>> {
+    let cert_path = get_env_var("PW_TLS_CERT_PATH");
+    let key_path = get_env_var("PW_TLS_KEY_PATH");
+
+    let mut tls = match (yaml_config, cert_path, key_path) {
+        (Some(yaml_tls), cert_path, key_path) => TlsConfig {
+            cert_path: cert_path.unwrap_or(yaml_tls.cert_path),
+            key_path: key_path.unwrap_or(yaml_tls.key_path),
+            ..yaml_tls
+        },
+        (None, Some(cert_path), Some(key_path)) => TlsConfig {
+            cert_path,
+            key_path,
+            min_version: TlsVersion::default(),
+            reload_interval_seconds: DEFAULT_TLS_RELOAD_INTERVAL_SECONDS,
+        },
+        (None, None, None) => return Ok(None),
+        (None, _, _) => {
+            return Err(anyhow::anyhow!(
+                "TLS configuration validation failed: both PW_TLS_CERT_PATH and PW_TLS_KEY_PATH must be set"
+            ));
+        }
+    };
+
+    if let Some(min_version) = get_env_var("PW_TLS_MIN_VERSION") {
+        tls.min_version = min_version.parse()?;
+    }
+
+    if let Some(reload_interval_seconds) = get_env_var("PW_TLS_RELOAD_INTERVAL_SECONDS") {
+        tls.reload_interval_seconds = reload_interval_seconds.parse()?;
+    }
+
+    Ok(Some(tls))
+}
+
 fn get_ip_limits_config(
     yaml_config: Option<IpLimitsConfig>,
 ) -> anyhow::Result<Option<IpLimitsConfig>> {
@@ -125,6 +444,7 @@ fn get_ip_limits_config(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::model::{RateLimitBackend, StorageBackend, StorageCompression};
     use serial_test::serial;
     use std::env;
 
@@ -142,6 +462,7 @@ mod tests {
                 ip: "192.168.1.1".to_string(),
                 message_max_length: None,
                 file_max_size: None,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         });
@@ -165,13 +486,14 @@ mod tests {
                 ip: "192.168.1.1".to_string(),
                 message_max_length: None,
                 file_max_size: None,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         });
 
         let result = get_ip_limits_config(yaml_config).unwrap();
         let config = result.unwrap();
-        assert_eq!(config.enabled, false);
+        assert!(!config.enabled);
 
         unsafe {
             env::remove_var("PW_IP_LIMITS_ENABLED");
@@ -197,12 +519,13 @@ mod tests {
                 ip: "192.168.1.1".to_string(),
                 message_max_length: None,
                 file_max_size: None,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         });
 
         let result = get_ip_limits_config(yaml_config).unwrap().unwrap();
-        assert_eq!(result.enabled, true);
+        assert!(result.enabled);
         assert_eq!(result.whitelist.len(), 2);
         assert_eq!(result.whitelist[0].ip, "10.0.0.1");
         assert_eq!(result.whitelist[1].ip, "10.0.0.2");
@@ -223,7 +546,7 @@ mod tests {
         }
 
         let result = get_ip_limits_config(None).unwrap().unwrap();
-        assert_eq!(result.enabled, true);
+        assert!(result.enabled);
         assert_eq!(result.whitelist.len(), 1);
         assert_eq!(result.whitelist[0].ip, "127.0.0.1");
 
@@ -296,7 +619,7 @@ mod tests {
         }
 
         let result = get_ip_limits_config(None).unwrap().unwrap();
-        assert_eq!(result.enabled, false); // Default when only whitelist provided
+        assert!(!result.enabled); // Default when only whitelist provided
         assert_eq!(result.whitelist.len(), 3);
 
         // Check first entry (all fields)
@@ -330,7 +653,7 @@ mod tests {
         }
 
         let result = get_ip_limits_config(None).unwrap().unwrap();
-        assert_eq!(result.enabled, true);
+        assert!(result.enabled);
         assert_eq!(result.whitelist.len(), 0);
 
         unsafe {
@@ -364,6 +687,7 @@ mod tests {
                 ip: "192.168.1.1".to_string(),
                 message_max_length: Some(2048),
                 file_max_size: Some(52428800),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         });
@@ -371,7 +695,7 @@ mod tests {
         let result = get_ip_limits_config(yaml_config).unwrap().unwrap();
 
         // Environment variables should override YAML
-        assert_eq!(result.enabled, false); // env overrides yaml (true -> false)
+        assert!(!result.enabled); // env overrides yaml (true -> false)
         assert_eq!(result.whitelist.len(), 1);
         assert_eq!(result.whitelist[0].ip, "203.0.113.1"); // env overrides yaml IP
         assert_eq!(result.whitelist[0].message_max_length, Some(4096)); // env overrides yaml (2048 -> 4096)
@@ -393,7 +717,7 @@ mod tests {
         }
 
         let result = get_ip_limits_config(None).unwrap().unwrap();
-        assert_eq!(result.enabled, true);
+        assert!(result.enabled);
         assert_eq!(result.whitelist.len(), 0);
 
         unsafe {
@@ -411,7 +735,7 @@ mod tests {
         }
 
         let result = get_ip_limits_config(None).unwrap().unwrap();
-        assert_eq!(result.enabled, false); // Default when only whitelist provided
+        assert!(!result.enabled); // Default when only whitelist provided
         assert_eq!(result.whitelist.len(), 1);
         assert_eq!(result.whitelist[0].ip, "127.0.0.1");
 
@@ -481,7 +805,7 @@ mod tests {
             env::remove_var("PW_IP_LIMITS_WHITELIST");
             env::set_var(
                 "PW_IP_LIMITS_WHITELIST",
-                &format!(
+                format!(
                     r#"[
                 {{"ip": "192.168.1.1", "message-max-length": {}, "file-max-size": {}}}
             ]"#,
@@ -595,7 +919,7 @@ mod tests {
             env::remove_var("PW_IP_LIMITS_WHITELIST");
             env::set_var(
                 "PW_IP_LIMITS_WHITELIST",
-                &format!(
+                format!(
                     r#"[
                 {{"ip": "192.168.1.100", "message-max-length": {}, "file-max-size": {}}}
             ]"#,
@@ -614,6 +938,188 @@ mod tests {
         }
     }
 
+    #[test]
+    #[serial]
+    fn test_get_redis_pool_config_with_env_override() {
+        unsafe {
+            env::set_var("PW_REDIS_POOL_SIZE", "8");
+            env::set_var("PW_REDIS_POOL_COMMAND_TIMEOUT_MS", "500");
+        }
+
+        let result = get_redis_pool_config(RedisPoolConfig::default()).unwrap();
+        assert_eq!(result.size, 8);
+        assert_eq!(
+            result.connect_timeout_ms,
+            RedisPoolConfig::default().connect_timeout_ms
+        );
+        assert_eq!(result.command_timeout_ms, 500);
+
+        unsafe {
+            env::set_var("PW_REDIS_POOL_SIZE", "invalid");
+        }
+
+        assert!(get_redis_pool_config(RedisPoolConfig::default()).is_err());
+
+        unsafe {
+            env::remove_var("PW_REDIS_POOL_SIZE");
+            env::remove_var("PW_REDIS_POOL_COMMAND_TIMEOUT_MS");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_storage_config_with_env_override() {
+        unsafe {
+            env::set_var("PW_STORAGE_BACKEND", "embedded");
+            env::set_var("PW_STORAGE_COMPRESSION", "deflate");
+            env::set_var("PW_STORAGE_EMBEDDED_PATH", "/var/lib/pw/pw.redb");
+        }
+
+        let result = get_storage_config(StorageConfig::default()).unwrap();
+        assert_eq!(result.backend, StorageBackend::Embedded);
+        assert_eq!(result.compression, StorageCompression::Deflate);
+        assert_eq!(result.embedded.path, "/var/lib/pw/pw.redb");
+        assert_eq!(
+            result.embedded.sweep_interval_seconds,
+            EmbeddedStorageConfig::default().sweep_interval_seconds
+        );
+
+        unsafe {
+            env::set_var("PW_STORAGE_BACKEND", "memory");
+            env::set_var("PW_STORAGE_MEMORY_MAX_ENTRIES", "100");
+        }
+
+        let result = get_storage_config(StorageConfig::default()).unwrap();
+        assert_eq!(result.backend, StorageBackend::Memory);
+        assert_eq!(result.memory.max_entries, 100);
+        assert_eq!(
+            result.memory.max_bytes,
+            MemoryStorageConfig::default().max_bytes
+        );
+
+        unsafe {
+            env::remove_var("PW_STORAGE_MEMORY_MAX_ENTRIES");
+            env::set_var("PW_STORAGE_BACKEND", "unknown");
+        }
+
+        assert!(get_storage_config(StorageConfig::default()).is_err());
+
+        unsafe {
+            env::set_var("PW_STORAGE_BACKEND", "redis");
+            env::set_var("PW_STORAGE_COMPRESSION", "gzip");
+        }
+
+        assert!(get_storage_config(StorageConfig::default()).is_err());
+
+        unsafe {
+            env::remove_var("PW_STORAGE_BACKEND");
+            env::remove_var("PW_STORAGE_COMPRESSION");
+            env::remove_var("PW_STORAGE_EMBEDDED_PATH");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_storage_encryption_config_with_env_override() {
+        let yaml_config = StorageEncryptionConfig {
+            enabled: false,
+            key_id: "2024".to_string(),
+            keys: vec![StorageKeyConfig {
+                id: "2024".to_string(),
+                key: Some("old".to_string()),
+                key_file: None,
+            }],
+        };
+
+        unsafe {
+            env::set_var("PW_STORAGE_ENCRYPTION_ENABLED", "true");
+            env::set_var("PW_STORAGE_ENCRYPTION_KEY_ID", "2025");
+            env::set_var("PW_STORAGE_ENCRYPTION_KEY_FILE", "/run/secrets/pw-2025");
+        }
+
+        let result = get_storage_encryption_config(yaml_config.clone()).unwrap();
+        assert!(result.enabled);
+        assert_eq!(result.key_id, "2025");
+        assert_eq!(result.keys.len(), 2);
+        assert_eq!(result.keys[0].key.as_deref(), Some("old"));
+        assert_eq!(
+            result.keys[1].key_file.as_deref(),
+            Some("/run/secrets/pw-2025")
+        );
+
+        unsafe {
+            env::remove_var("PW_STORAGE_ENCRYPTION_KEY_ID");
+            env::remove_var("PW_STORAGE_ENCRYPTION_KEY_FILE");
+            env::set_var("PW_STORAGE_ENCRYPTION_KEY", "new");
+        }
+
+        let result = get_storage_encryption_config(yaml_config.clone()).unwrap();
+        assert_eq!(result.keys.len(), 1);
+        assert_eq!(result.keys[0].id, "2024");
+        assert_eq!(result.keys[0].key.as_deref(), Some("new"));
+
+        unsafe {
+            env::set_var("PW_STORAGE_ENCRYPTION_ENABLED", "yes");
+        }
+
+        assert!(get_storage_encryption_config(yaml_config).is_err());
+
+        unsafe {
+            env::remove_var("PW_STORAGE_ENCRYPTION_ENABLED");
+            env::remove_var("PW_STORAGE_ENCRYPTION_KEY");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_rate_limits_config_with_env_override() {
+        unsafe {
+            env::set_var("PW_RATE_LIMITS_ENABLED", "true");
+            env::set_var("PW_RATE_LIMITS_BACKEND", "redis");
+            env::set_var("PW_RATE_LIMITS_READ_REQUESTS", "5");
+            env::set_var("PW_RATE_LIMITS_READ_PERIOD_SECONDS", "10");
+        }
+
+        let result = get_rate_limits_config(RateLimitsConfig::default()).unwrap();
+        assert!(result.enabled);
+        assert_eq!(result.backend, RateLimitBackend::Redis);
+        assert_eq!(result.read, RateLimitRule::new(5, 10));
+        assert_eq!(result.create, RateLimitsConfig::default().create);
+        assert_eq!(result.delete, RateLimitsConfig::default().delete);
+
+        unsafe {
+            env::set_var("PW_RATE_LIMITS_BACKEND", "unknown");
+        }
+
+        assert!(get_rate_limits_config(RateLimitsConfig::default()).is_err());
+
+        unsafe {
+            env::remove_var("PW_RATE_LIMITS_ENABLED");
+            env::remove_var("PW_RATE_LIMITS_BACKEND");
+            env::remove_var("PW_RATE_LIMITS_READ_REQUESTS");
+            env::remove_var("PW_RATE_LIMITS_READ_PERIOD_SECONDS");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_lookup_guard_config_with_env_override() {
+        unsafe {
+            env::set_var("PW_LOOKUP_GUARD_ENABLED", "false");
+            env::set_var("PW_LOOKUP_GUARD_MAX_FAILURES", "5");
+        }
+
+        let result = get_lookup_guard_config(LookupGuardConfig::default()).unwrap();
+        assert!(!result.enabled);
+        assert_eq!(result.max_failures, 5);
+        assert_eq!(result.ban_seconds, LookupGuardConfig::default().ban_seconds);
+
+        unsafe {
+            env::remove_var("PW_LOOKUP_GUARD_ENABLED");
+            env::remove_var("PW_LOOKUP_GUARD_MAX_FAILURES");
+        }
+    }
+
     #[test]
     #[serial]
     fn test_env_var_helpers() {
@@ -727,4 +1233,124 @@ mod tests {
             env::remove_var("PW_IP_LIMITS_WHITELIST");
         }
     }
+
+    #[test]
+    #[serial]
+    fn test_get_metrics_access_config_with_env_override() {
+        unsafe {
+            env::set_var("PW_METRICS_ACCESS_BASIC_AUTH_PASSWORD", "from-env");
+            env::set_var("PW_METRICS_ACCESS_ALLOWED_IPS", r#"["10.0.0.0/8"]"#);
+        }
+
+        let yaml_config = MetricsAccessConfig {
+            basic_auth: Some(BasicAuthConfig {
+                username: "prometheus".to_string(),
+                password: "from-yaml".to_string(),
+            }),
+            allowed_ips: vec!["127.0.0.1".to_string()],
+        };
+
+        let result = get_metrics_access_config(yaml_config).unwrap();
+
+        let basic_auth = result.basic_auth.unwrap();
+        assert_eq!(basic_auth.username, "prometheus");
+        assert_eq!(basic_auth.password, "from-env");
+        assert_eq!(result.allowed_ips, vec!["10.0.0.0/8".to_string()]);
+
+        unsafe {
+            env::remove_var("PW_METRICS_ACCESS_BASIC_AUTH_PASSWORD");
+            env::remove_var("PW_METRICS_ACCESS_ALLOWED_IPS");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_metrics_access_config_rejects_incomplete_basic_auth() {
+        unsafe {
+            env::set_var("PW_METRICS_ACCESS_BASIC_AUTH_USERNAME", "prometheus");
+        }
+
+        let result = get_metrics_access_config(MetricsAccessConfig::default());
+        assert!(result.is_err());
+
+        unsafe {
+            env::remove_var("PW_METRICS_ACCESS_BASIC_AUTH_USERNAME");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_tls_config_from_env_only() {
+        unsafe {
+            env::set_var("PW_TLS_CERT_PATH", "/etc/pw/tls.crt");
+            env::set_var("PW_TLS_KEY_PATH", "/etc/pw/tls.key");
+            env::set_var("PW_TLS_MIN_VERSION", "1.3");
+        }
+
+        let result = get_tls_config(None).unwrap().unwrap();
+        assert_eq!(result.cert_path, "/etc/pw/tls.crt");
+        assert_eq!(result.key_path, "/etc/pw/tls.key");
+        assert_eq!(result.min_version, TlsVersion::Tls13);
+        assert_eq!(
+            result.reload_interval_seconds,
+            DEFAULT_TLS_RELOAD_INTERVAL_SECONDS
+        );
+
+        unsafe {
+            env::remove_var("PW_TLS_CERT_PATH");
+            env::remove_var("PW_TLS_KEY_PATH");
+            env::remove_var("PW_TLS_MIN_VERSION");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_tls_config_requires_cert_and_key() {
+        unsafe {
+            env::set_var("PW_TLS_CERT_PATH", "/etc/pw/tls.crt");
+        }
+
+        assert!(get_tls_config(None).is_err());
+
+        unsafe {
+            env::remove_var("PW_TLS_CERT_PATH");
+        }
+
+        assert_eq!(get_tls_config(None).unwrap(), None);
+    }
+
+    fn write_config_file(content: &str) -> String {
+        let path = env::temp_dir().join(format!("pw-test-{}.yml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_from_file_reports_invalid_value_line() {
+        let path = write_config_file(
+            "listen: 0.0.0.0:8080\nlog-level: info\nlog-target: stdout\n\
+            message-max-length: big\nfile-upload-enabled: true\nfile-max-size: 1024\n\
+            redis-url: redis://localhost\n",
+        );
+
+        let error = load_config_from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(error.line, Some(4));
+        assert!(error.message.contains("message-max-length"));
+        assert!(error.to_string().starts_with(&format!("{path}:4: ")));
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_from_file_reports_syntax_error_line() {
+        let path = write_config_file("listen: 0.0.0.0:8080\nlog-level: [info\n");
+
+        let error = load_config_from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(error.line.is_some());
+        assert_eq!(error.path, path);
+    }
 }
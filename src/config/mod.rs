@@ -1,3 +1,5 @@
+pub mod error;
 pub mod file;
 pub mod model;
+pub mod reload;
 pub mod validation;
@@ -0,0 +1,218 @@
+use log::{debug, error, info, warn};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use super::file::load_config_from_file;
+use super::model::AppConfig;
+use crate::limits::LimitsService;
+use crate::shutdown::{HangupSignal, tick};
+
+/// Result of the last `ip-limits` reload, readiness reports not-ready while it's failed
+#[derive(Clone, Default, Debug)]
+pub struct ConfigReloadStatus {
+    last_error: Arc<RwLock<Option<String>>>,
+}
+
+impl ConfigReloadStatus {
+    pub fn record(&self, result: &anyhow::Result<()>) {
+        *self.last_error.write().unwrap() = result.as_ref().err().map(|e| format!("{e:#}"));
+    }
+
+    /// Error of the last reload, `None` when nothing was reloaded yet or it succeeded
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.read().unwrap().clone()
+    }
+}
+
+/// Reloads `ip-limits` on SIGHUP and when config file is modified.
+///
+/// Whole file is loaded and validated, current limits are kept when it's invalid.
+/// Other settings are applied on restart only.
+pub fn spawn_ip_limits_reloader(
+    config_file: &str,
+    config: AppConfig,
+    limits_service: LimitsService,
+    status: ConfigReloadStatus,
+) -> anyhow::Result<JoinHandle<()>> {
+    let mut hangup = HangupSignal::new()?;
+
+    let mut ticker = match config.config_reload_interval_seconds {
+        0 => None,
+        seconds => Some(tokio::time::interval(Duration::from_secs(seconds))),
+    };
+
+    let config_file = config_file.to_string();
+
+    Ok(tokio::spawn(async move {
+        let mut config = config;
+        let mut last_modified = get_modified_time(&config_file);
+
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => info!("SIGHUP received, reloading ip-limits"),
+                _ = tick(&mut ticker) => {
+                    let modified = get_modified_time(&config_file);
+                    if modified == last_modified {
+                        continue;
+                    }
+                    info!("config file '{}' has changed, reloading ip-limits", config_file);
+                }
+            }
+
+            last_modified = get_modified_time(&config_file);
+
+            let result = reload_ip_limits(&config_file, &mut config, &limits_service);
+            if let Err(e) = &result {
+                error!("unable to reload ip-limits, current ones are kept: {:#}", e);
+            }
+            status.record(&result);
+        }
+    }))
+}
+
+/// Applies `ip-limits` from config file, `config` is updated on success
+pub fn reload_ip_limits(
+    config_file: &str,
+    config: &mut AppConfig,
+    limits_service: &LimitsService,
+) -> anyhow::Result<()> {
+    let new_config = load_config_from_file(config_file)?;
+
+    let restart_required = AppConfig {
+        ip_limits: None,
+        ..new_config.clone()
+    } != AppConfig {
+        ip_limits: None,
+        ..config.clone()
+    };
+
+    if restart_required {
+        warn!("config file has changes besides ip-limits, they will be applied after restart");
+    }
+
+    if new_config.ip_limits == config.ip_limits {
+        debug!("ip-limits haven't changed");
+        return Ok(());
+    }
+
+    let body_limit = limits_service.reload_ip_limits(new_config.ip_limits.clone())?;
+
+    let (whitelist, trusted_proxies) = match &new_config.ip_limits {
+        Some(ip_limits) => (ip_limits.whitelist.len(), ip_limits.trusted_proxies.len()),
+        None => (0, 0),
+    };
+
+    info!(
+        "ip-limits have been reloaded: {} whitelist entries, {} trusted proxies, body limit {} bytes",
+        whitelist, trusted_proxies, body_limit
+    );
+
+    config.ip_limits = new_config.ip_limits;
+
+    Ok(())
+}
+
+fn get_modified_time(config_file: &str) -> Option<SystemTime> {
+    match std::fs::metadata(config_file).and_then(|meta| meta.modified()) {
+        Ok(modified) => Some(modified),
+        Err(e) => {
+            warn!("unable to check config file '{}': {}", config_file, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    const BASE_CONFIG: &str = "\
+listen: 0.0.0.0:8080
+log-level: info
+log-target: console
+message-max-length: 1024
+file-upload-enabled: true
+file-max-size: 1048576
+redis-url: redis://localhost
+";
+
+    struct TempConfigFile(String);
+
+    impl TempConfigFile {
+        fn new() -> TempConfigFile {
+            let path = std::env::temp_dir().join(format!("pw-test-{}.yml", uuid::Uuid::new_v4()));
+            TempConfigFile(path.to_str().unwrap().to_string())
+        }
+
+        fn write(&self, ip_limits: &str) {
+            std::fs::write(&self.0, format!("{BASE_CONFIG}{ip_limits}")).unwrap();
+        }
+    }
+
+    impl Drop for TempConfigFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn ip_limits_should_be_reloaded() {
+        let file = TempConfigFile::new();
+        file.write("");
+
+        let mut config = load_config_from_file(&file.0).unwrap();
+        let limits_service = LimitsService::new(&config);
+        let body_limit = limits_service.body_limit_as_usize().unwrap();
+
+        file.write(
+            "ip-limits:\n  enabled: true\n  trusted-proxies: [\"10.0.0.1\"]\n  whitelist:\n    \
+            - ip: 192.168.1.0/24\n      file-max-size: 5242880\n",
+        );
+
+        reload_ip_limits(&file.0, &mut config, &limits_service).unwrap();
+
+        assert_eq!(
+            limits_service
+                .get_limits_for_ip("192.168.1.10")
+                .file_max_size,
+            5242880
+        );
+        assert!(limits_service.body_limit_as_usize().unwrap() > body_limit);
+        assert_eq!(
+            limits_service
+                .get_ip_limits_config()
+                .unwrap()
+                .trusted_proxies,
+            vec!["10.0.0.1".to_string()]
+        );
+        assert_eq!(config.ip_limits, limits_service.get_ip_limits_config());
+    }
+
+    #[test]
+    #[serial]
+    fn invalid_ip_limits_should_keep_current_ones() {
+        let file = TempConfigFile::new();
+        file.write(
+            "ip-limits:\n  enabled: true\n  whitelist:\n    - ip: 192.168.1.0/24\n      \
+            file-max-size: 5242880\n",
+        );
+
+        let mut config = load_config_from_file(&file.0).unwrap();
+        let limits_service = LimitsService::new(&config);
+
+        file.write("ip-limits:\n  enabled: true\n  whitelist:\n    - ip: 192.168.1.300\n");
+
+        assert!(reload_ip_limits(&file.0, &mut config, &limits_service).is_err());
+
+        assert_eq!(
+            limits_service
+                .get_limits_for_ip("192.168.1.10")
+                .file_max_size,
+            5242880
+        );
+        assert_eq!(config.ip_limits, limits_service.get_ip_limits_config());
+    }
+}
@@ -1,10 +1,15 @@
 use ipnet::IpNet;
 use log::warn;
-use std::net::IpAddr;
+use std::net::{IpAddr, ToSocketAddrs};
 use std::str::FromStr;
 use thiserror::Error;
 
-use super::model::{IpLimitEntry, IpLimitsConfig};
+use super::model::{
+    AppConfig, AuditBackend, AuditConfig, IpLimitEntry, IpLimitsConfig, MetricsAccessConfig,
+    RateLimitRule, StorageEncryptionConfig, redact_url_password,
+};
+use crate::limits::LimitsService;
+use crate::logging::{LOG_FORMATS, LOG_LEVELS, LOG_TARGETS};
 
 /// Validation errors for IP limits configuration
 #[derive(Error, Debug)]
@@ -24,6 +29,75 @@ pub enum ValidationError {
     #[error("File max size cannot be zero")]
     FileSizeZero,
 
+    #[error("File chunk max size cannot be zero")]
+    FileChunkSizeZero,
+
+    #[error("TTL min cannot be zero")]
+    TtlMinZero,
+
+    #[error("TTL min {min} is greater than TTL max {max}")]
+    TtlRangeInvalid { min: u64, max: u64 },
+
+    #[error("TTL default {value} is outside of allowed range {min}..={max}")]
+    TtlDefaultOutOfRange { value: u64, min: u64, max: u64 },
+
+    #[error("Rate limit '{name}' must allow at least one request per non-zero period")]
+    RateLimitZero { name: String },
+
+    #[error("Metrics basic auth username and password cannot be empty")]
+    MetricsBasicAuthEmpty,
+
+    #[error("Metrics basic auth username cannot contain ':'")]
+    MetricsBasicAuthUsernameInvalid,
+
+    #[error("Invalid {name} address '{value}': {reason}")]
+    InvalidSocketAddress {
+        name: String,
+        value: String,
+        reason: String,
+    },
+
+    #[error("Unknown log level '{value}', expected one of: {expected}")]
+    UnknownLogLevel { value: String, expected: String },
+
+    #[error("Unknown log target '{value}', expected one of: {expected}")]
+    UnknownLogTarget { value: String, expected: String },
+
+    #[error("Unknown log format '{value}', expected one of: {expected}")]
+    UnknownLogFormat { value: String, expected: String },
+
+    #[error("Log file path cannot be empty")]
+    LogFilePathEmpty,
+
+    #[error("Log file max size cannot be zero")]
+    LogFileSizeZero,
+
+    #[error("Audit file path cannot be empty")]
+    AuditFilePathEmpty,
+
+    #[error("Audit Redis stream name cannot be empty")]
+    AuditRedisStreamEmpty,
+
+    #[error("Storage key id cannot be empty and must be at most {max} bytes long")]
+    StorageKeyIdInvalid { max: usize },
+
+    #[error("Duplicate storage key id '{id}'")]
+    DuplicateStorageKeyId { id: String },
+
+    #[error("Storage key '{id}' must have either key or key-file set")]
+    StorageKeySourceInvalid { id: String },
+
+    #[error("Storage encryption key id '{id}' isn't listed in keys")]
+    StorageEncryptionKeyMissing { id: String },
+
+    #[error("Redis URL '{url}' must use one of schemes: redis, rediss, valkey, valkeys, unix")]
+    InvalidRedisUrl { url: String },
+
+    #[error(
+        "Encrypted message max length {value} is below {required} bytes required for message-max-length and file-max-size"
+    )]
+    EncryptedMessageLengthTooLow { value: u64, required: u64 },
+
     #[error("Empty IP string is not allowed")]
     EmptyIpString,
 
@@ -46,6 +120,122 @@ pub enum ValidationError {
 /// Configuration validation limits
 const MAX_FILE_SIZE: u64 = 10_737_418_240; // 10GB
 
+/// Key id length is stored in a single byte of a secret record
+const MAX_STORAGE_KEY_ID_LENGTH: usize = 255;
+
+/// Validates top-level settings and relations between them, sections with
+/// own validation (IP limits, rate limits, metrics access) are checked when loaded
+pub fn validate_app_config(config: &AppConfig) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    if let Err(err) = validate_socket_address("listen", &config.listen) {
+        errors.push(err);
+    }
+
+    if let Some(metrics_listen) = &config.metrics_listen
+        && let Err(err) = validate_socket_address("metrics-listen", metrics_listen)
+    {
+        errors.push(err);
+    }
+
+    if !LOG_LEVELS.contains(&config.log_level.as_str()) {
+        errors.push(ValidationError::UnknownLogLevel {
+            value: config.log_level.clone(),
+            expected: LOG_LEVELS.join(", "),
+        });
+    }
+
+    if !LOG_TARGETS.contains(&config.log_target.as_str()) {
+        errors.push(ValidationError::UnknownLogTarget {
+            value: config.log_target.clone(),
+            expected: LOG_TARGETS.join(", "),
+        });
+    }
+
+    if !LOG_FORMATS.contains(&config.log_format.as_str()) {
+        errors.push(ValidationError::UnknownLogFormat {
+            value: config.log_format.clone(),
+            expected: LOG_FORMATS.join(", "),
+        });
+    }
+
+    if config.log_file.path.trim().is_empty() {
+        errors.push(ValidationError::LogFilePathEmpty);
+    }
+
+    if config.log_file.max_size == 0 {
+        errors.push(ValidationError::LogFileSizeZero);
+    }
+
+    if redis::parse_redis_url(&config.redis_url).is_none() {
+        errors.push(ValidationError::InvalidRedisUrl {
+            url: redact_url_password(&config.redis_url),
+        });
+    }
+
+    if let Err(err) = validate_message_length(config.message_max_length) {
+        errors.push(err);
+    }
+
+    if let Err(err) = validate_file_size(config.file_max_size) {
+        errors.push(err);
+    }
+
+    if config.file_chunk_max_size == 0 {
+        errors.push(ValidationError::FileChunkSizeZero);
+    }
+
+    if let Some(value) = config.encrypted_message_max_length {
+        let file_max_size = if config.file_upload_enabled {
+            config.file_max_size
+        } else {
+            0
+        };
+        let required =
+            LimitsService::calculate_encrypted_max_length(config.message_max_length, file_max_size);
+
+        if value < required {
+            errors.push(ValidationError::EncryptedMessageLengthTooLow { value, required });
+        }
+    }
+
+    if let Err(err) = validate_ttl_limits(config.ttl_min, config.ttl_max, config.ttl_default) {
+        errors.push(err);
+    }
+
+    if let Err(err) = validate_audit_config(&config.audit) {
+        errors.push(err);
+    }
+
+    if let Err(err) = validate_storage_encryption_config(&config.storage.encryption) {
+        errors.extend(err);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validates `host:port` address the same way listeners resolve it, `name` is used in error message
+pub fn validate_socket_address(name: &str, value: &str) -> Result<(), ValidationError> {
+    let reason = match value
+        .to_socket_addrs()
+        .map(|mut addresses| addresses.next())
+    {
+        Ok(Some(_)) => return Ok(()),
+        Ok(None) => "host has no addresses".to_string(),
+        Err(e) => e.to_string(),
+    };
+
+    Err(ValidationError::InvalidSocketAddress {
+        name: name.to_string(),
+        value: value.to_string(),
+        reason,
+    })
+}
+
 /// Validates IP limits configuration
 pub fn validate_ip_limits_config(config: &IpLimitsConfig) -> Result<(), Vec<ValidationError>> {
     let mut errors = Vec::new();
@@ -107,6 +297,20 @@ pub fn validate_ip_entry(entry: &IpLimitEntry) -> Result<(), Vec<ValidationError
         errors.push(err);
     }
 
+    if let Some(overrides) = &entry.rate_limits {
+        for (name, rule) in [
+            ("create", &overrides.create),
+            ("read", &overrides.read),
+            ("delete", &overrides.delete),
+        ] {
+            if let Some(rule) = rule
+                && let Err(err) = validate_rate_limit_rule(name, rule)
+            {
+                errors.push(err);
+            }
+        }
+    }
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -236,6 +440,120 @@ pub fn validate_file_size(size: u64) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Validates rate limit rule, `name` is used in error message
+pub fn validate_rate_limit_rule(name: &str, rule: &RateLimitRule) -> Result<(), ValidationError> {
+    if rule.requests == 0 || rule.period_seconds == 0 {
+        return Err(ValidationError::RateLimitZero {
+            name: name.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Validates secret TTL limits, all values are in seconds
+pub fn validate_ttl_limits(min: u64, max: u64, default: u64) -> Result<(), ValidationError> {
+    if min == 0 {
+        return Err(ValidationError::TtlMinZero);
+    }
+
+    if min > max {
+        return Err(ValidationError::TtlRangeInvalid { min, max });
+    }
+
+    if default < min || default > max {
+        return Err(ValidationError::TtlDefaultOutOfRange {
+            value: default,
+            min,
+            max,
+        });
+    }
+
+    Ok(())
+}
+
+/// Only the selected backend settings are checked, nothing when audit is disabled
+pub fn validate_audit_config(config: &AuditConfig) -> Result<(), ValidationError> {
+    if !config.enabled {
+        return Ok(());
+    }
+
+    match config.backend {
+        AuditBackend::File if config.file.path.trim().is_empty() => {
+            Err(ValidationError::AuditFilePathEmpty)
+        }
+        AuditBackend::Redis if config.redis.stream.trim().is_empty() => {
+            Err(ValidationError::AuditRedisStreamEmpty)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Keys are checked even when encryption is disabled, they are still used to read records
+pub fn validate_storage_encryption_config(
+    config: &StorageEncryptionConfig,
+) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    let mut seen_ids = std::collections::HashSet::new();
+
+    for key in &config.keys {
+        if key.id.is_empty() || key.id.len() > MAX_STORAGE_KEY_ID_LENGTH {
+            errors.push(ValidationError::StorageKeyIdInvalid {
+                max: MAX_STORAGE_KEY_ID_LENGTH,
+            });
+        }
+
+        if !seen_ids.insert(&key.id) {
+            errors.push(ValidationError::DuplicateStorageKeyId { id: key.id.clone() });
+        }
+
+        if key.key.is_some() == key.key_file.is_some() {
+            errors.push(ValidationError::StorageKeySourceInvalid { id: key.id.clone() });
+        }
+    }
+
+    if config.enabled && !seen_ids.contains(&config.key_id) {
+        errors.push(ValidationError::StorageEncryptionKeyMissing {
+            id: config.key_id.clone(),
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validates metrics endpoint basic auth credentials and allowed IPs
+pub fn validate_metrics_access_config(
+    config: &MetricsAccessConfig,
+) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+
+    if let Some(basic_auth) = &config.basic_auth {
+        if basic_auth.username.is_empty() || basic_auth.password.is_empty() {
+            errors.push(ValidationError::MetricsBasicAuthEmpty);
+        }
+
+        if basic_auth.username.contains(':') {
+            errors.push(ValidationError::MetricsBasicAuthUsernameInvalid);
+        }
+    }
+
+    for ip in &config.allowed_ips {
+        if let Err(err) = validate_ip_format(ip) {
+            errors.push(err);
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /// Formats validation errors into a user-friendly message
 pub fn format_validation_errors(errors: &[ValidationError]) -> String {
     if errors.is_empty() {
@@ -255,6 +573,10 @@ pub fn format_validation_errors(errors: &[ValidationError]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::model::{
+        AuditFileConfig, BasicAuthConfig, LogFileConfig, RateLimitOverrides, StorageKeyConfig,
+    };
+    use crate::tests::config::get_test_app_config;
 
     const MIN_MESSAGE_LENGTH: u16 = 1;
     const MIN_FILE_SIZE: u64 = 1;
@@ -264,6 +586,7 @@ mod tests {
             ip: ip.to_string(),
             message_max_length: Some(2048),
             file_max_size: Some(52428800),
+            rate_limits: None,
         }
     }
 
@@ -365,6 +688,233 @@ mod tests {
         assert!(validate_file_size(MAX_FILE_SIZE + 1).is_err());
     }
 
+    #[test]
+    fn test_validate_ttl_limits() {
+        assert!(validate_ttl_limits(60, 604800, 3600).is_ok());
+        assert!(validate_ttl_limits(60, 60, 60).is_ok());
+
+        assert!(matches!(
+            validate_ttl_limits(0, 604800, 3600),
+            Err(ValidationError::TtlMinZero)
+        ));
+        assert!(matches!(
+            validate_ttl_limits(3600, 60, 3600),
+            Err(ValidationError::TtlRangeInvalid { .. })
+        ));
+        assert!(matches!(
+            validate_ttl_limits(60, 3600, 7200),
+            Err(ValidationError::TtlDefaultOutOfRange { .. })
+        ));
+        assert!(matches!(
+            validate_ttl_limits(120, 3600, 60),
+            Err(ValidationError::TtlDefaultOutOfRange { .. })
+        ));
+    }
+
+    fn create_test_app_config() -> AppConfig {
+        AppConfig {
+            log_target: "console".to_string(),
+            ..get_test_app_config()
+        }
+    }
+
+    #[test]
+    fn test_validate_app_config_accepts_valid_config() {
+        assert!(validate_app_config(&create_test_app_config()).is_ok());
+
+        let config = AppConfig {
+            listen: "[::1]:8443".to_string(),
+            metrics_listen: Some("localhost:9090".to_string()),
+            log_level: "debug".to_string(),
+            log_target: "file".to_string(),
+            redis_url: "rediss://:secret@redis.local:6380/1".to_string(),
+            encrypted_message_max_length: Some(20_000_000),
+            ..create_test_app_config()
+        };
+        assert!(validate_app_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_app_config_collects_all_errors() {
+        let config = AppConfig {
+            listen: "localhost".to_string(),
+            metrics_listen: Some("0.0.0.0:99999".to_string()),
+            log_level: "verbose".to_string(),
+            log_target: "syslog".to_string(),
+            redis_url: "http://:secret@localhost".to_string(),
+            message_max_length: 0,
+            ttl_min: 0,
+            ..create_test_app_config()
+        };
+
+        let errors = validate_app_config(&config).unwrap_err();
+        assert_eq!(errors.len(), 7);
+        assert!(matches!(
+            &errors[0],
+            ValidationError::InvalidSocketAddress { name, .. } if name == "listen"
+        ));
+        assert!(matches!(
+            &errors[1],
+            ValidationError::InvalidSocketAddress { name, .. } if name == "metrics-listen"
+        ));
+        assert!(matches!(errors[2], ValidationError::UnknownLogLevel { .. }));
+        assert!(matches!(
+            errors[3],
+            ValidationError::UnknownLogTarget { .. }
+        ));
+        assert!(matches!(errors[4], ValidationError::InvalidRedisUrl { .. }));
+        assert!(matches!(errors[5], ValidationError::MessageLengthZero));
+        assert!(matches!(errors[6], ValidationError::TtlMinZero));
+
+        let formatted = format_validation_errors(&errors);
+        assert!(formatted.contains("  7. "));
+        assert!(!formatted.contains("secret"));
+    }
+
+    #[test]
+    fn test_validate_app_config_log_settings() {
+        let config = AppConfig {
+            log_format: "json".to_string(),
+            ..create_test_app_config()
+        };
+        assert!(validate_app_config(&config).is_ok());
+
+        let config = AppConfig {
+            log_format: "logfmt".to_string(),
+            log_file: LogFileConfig {
+                path: " ".to_string(),
+                max_size: 0,
+                max_files: 0,
+            },
+            ..create_test_app_config()
+        };
+        let errors = validate_app_config(&config).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [
+                ValidationError::UnknownLogFormat { .. },
+                ValidationError::LogFilePathEmpty,
+                ValidationError::LogFileSizeZero
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_validate_audit_config() {
+        let mut config = AuditConfig {
+            file: AuditFileConfig {
+                path: "".to_string(),
+            },
+            ..AuditConfig::default()
+        };
+        assert!(validate_audit_config(&config).is_ok());
+
+        config.enabled = true;
+        assert!(matches!(
+            validate_audit_config(&config),
+            Err(ValidationError::AuditFilePathEmpty)
+        ));
+
+        config.backend = AuditBackend::Redis;
+        assert!(validate_audit_config(&config).is_ok());
+
+        config.redis.stream = " ".to_string();
+        assert!(matches!(
+            validate_audit_config(&config),
+            Err(ValidationError::AuditRedisStreamEmpty)
+        ));
+    }
+
+    #[test]
+    fn test_validate_storage_encryption_config() {
+        let key = |id: &str| StorageKeyConfig {
+            id: id.to_string(),
+            key: Some("key".to_string()),
+            key_file: None,
+        };
+
+        let mut config = StorageEncryptionConfig::default();
+        assert!(validate_storage_encryption_config(&config).is_ok());
+
+        config.enabled = true;
+        config.key_id = "2025".to_string();
+        assert!(matches!(
+            validate_storage_encryption_config(&config).unwrap_err().as_slice(),
+            [ValidationError::StorageEncryptionKeyMissing { id }] if id == "2025"
+        ));
+
+        config.keys = vec![key("2024"), key("2025")];
+        assert!(validate_storage_encryption_config(&config).is_ok());
+
+        config.keys[0].key_file = Some("/run/secrets/pw".to_string());
+        config.keys[1].id = "2024".to_string();
+        config.keys.push(key(&"a".repeat(256)));
+
+        assert!(matches!(
+            validate_storage_encryption_config(&config)
+                .unwrap_err()
+                .as_slice(),
+            [
+                ValidationError::StorageKeySourceInvalid { .. },
+                ValidationError::DuplicateStorageKeyId { .. },
+                ValidationError::StorageKeyIdInvalid { max: 255 },
+                ValidationError::StorageEncryptionKeyMissing { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_validate_app_config_encrypted_length_covers_file_size() {
+        let required = LimitsService::calculate_encrypted_max_length(1024, 10485760);
+        // max(1024, 10485760) * 1.35 = 14155776
+        assert_eq!(required, 14155776);
+
+        let config = AppConfig {
+            encrypted_message_max_length: Some(required - 1),
+            ..create_test_app_config()
+        };
+        let errors = validate_app_config(&config).unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [ValidationError::EncryptedMessageLengthTooLow { value, required: r }]
+                if value == required - 1 && r == required
+        ));
+
+        let config = AppConfig {
+            encrypted_message_max_length: Some(required),
+            ..create_test_app_config()
+        };
+        assert!(validate_app_config(&config).is_ok());
+
+        // Only message length matters when file upload is disabled
+        let config = AppConfig {
+            file_upload_enabled: false,
+            encrypted_message_max_length: Some(2048),
+            ..create_test_app_config()
+        };
+        assert!(validate_app_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rate_limit_rule() {
+        assert!(validate_rate_limit_rule("read", &RateLimitRule::new(10, 60)).is_ok());
+        assert!(matches!(
+            validate_rate_limit_rule("read", &RateLimitRule::new(0, 60)),
+            Err(ValidationError::RateLimitZero { .. })
+        ));
+        assert!(matches!(
+            validate_rate_limit_rule("read", &RateLimitRule::new(10, 0)),
+            Err(ValidationError::RateLimitZero { .. })
+        ));
+
+        let mut entry = create_test_entry("192.168.1.1");
+        entry.rate_limits = Some(RateLimitOverrides {
+            read: Some(RateLimitRule::new(0, 60)),
+            ..RateLimitOverrides::default()
+        });
+        assert!(validate_ip_entry(&entry).is_err());
+    }
+
     #[test]
     fn test_validate_ip_entry() {
         // Valid entry
@@ -376,6 +926,7 @@ mod tests {
             ip: "invalid.ip".to_string(),
             message_max_length: Some(2048),
             file_max_size: Some(52428800),
+            rate_limits: None,
         };
         assert!(validate_ip_entry(&entry).is_err());
 
@@ -384,6 +935,7 @@ mod tests {
             ip: "192.168.1.1".to_string(),
             message_max_length: Some(0),
             file_max_size: Some(52428800),
+            rate_limits: None,
         };
         assert!(validate_ip_entry(&entry).is_err());
 
@@ -392,6 +944,7 @@ mod tests {
             ip: "192.168.1.1".to_string(),
             message_max_length: Some(2048),
             file_max_size: Some(0),
+            rate_limits: None,
         };
         assert!(validate_ip_entry(&entry).is_err());
     }
@@ -425,6 +978,7 @@ mod tests {
             ip: "invalid".to_string(),
             message_max_length: Some(0),
             file_max_size: Some(0),
+            rate_limits: None,
         }]);
         let result = validate_ip_limits_config(&config);
         assert!(result.is_err());
@@ -509,6 +1063,7 @@ mod tests {
             ip: "invalid.ip.address".to_string(),
             message_max_length: Some(0),
             file_max_size: Some(u64::MAX),
+            rate_limits: None,
         };
 
         let result = validate_ip_entry(&entry);
@@ -553,4 +1108,32 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_validate_metrics_access_config() {
+        let valid = MetricsAccessConfig {
+            basic_auth: Some(BasicAuthConfig {
+                username: "prometheus".to_string(),
+                password: "secret".to_string(),
+            }),
+            allowed_ips: vec!["10.0.0.0/8".to_string(), "192.168.1.10".to_string()],
+        };
+        assert!(validate_metrics_access_config(&valid).is_ok());
+        assert!(validate_metrics_access_config(&MetricsAccessConfig::default()).is_ok());
+
+        let invalid = MetricsAccessConfig {
+            basic_auth: Some(BasicAuthConfig {
+                username: "prom:etheus".to_string(),
+                password: "".to_string(),
+            }),
+            allowed_ips: vec!["10.0.0.0/33".to_string()],
+        };
+        let errors = validate_metrics_access_config(&invalid).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], ValidationError::MetricsBasicAuthEmpty));
+        assert!(matches!(
+            errors[1],
+            ValidationError::MetricsBasicAuthUsernameInvalid
+        ));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::config::model::StorageBackend;
 use serde::{Deserialize, Serialize};
 
 #[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
@@ -6,4 +7,70 @@ pub struct AppConfigDto {
     pub message_max_length: u16,
     pub file_upload_enabled: bool,
     pub file_max_size: u64,
+    /// Allowed secret lifetime range in seconds
+    pub ttl_min: u64,
+    pub ttl_max: u64,
+    pub ttl_default: u64,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredSecretDto {
+    pub id: String,
+    /// Shown to the creator only, required to remove the secret
+    pub revocation_token: String,
+}
+
+/// Error response body, `code` values are stable and safe to match on
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+pub struct ApiErrorDto {
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthDto {
+    pub status: String,
+    pub version: String,
+    pub uptime_seconds: u64,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessDto {
+    /// `ready` or `not-ready`
+    pub status: String,
+    pub checks: ReadinessChecksDto,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessChecksDto {
+    /// Fails while the last `ip-limits` reload failed
+    pub config: ReadinessCheckDto,
+    pub storage: StorageCheckDto,
+    /// Fails while connections are drained on shutdown
+    pub shutdown: ReadinessCheckDto,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessCheckDto {
+    /// `ok` or `fail`
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageCheckDto {
+    /// `ok` or `fail`
+    pub status: String,
+    pub backend: StorageBackend,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_seconds: Option<f64>,
 }
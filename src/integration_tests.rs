@@ -1,26 +1,38 @@
 #[cfg(test)]
 mod tests {
     use crate::AppState;
+    use crate::audit::AuditLog;
     use crate::config::model::{AppConfig, IpLimitEntry, IpLimitsConfig};
-    use crate::dto::model::AppConfigDto;
+    use crate::config::reload::ConfigReloadStatus;
+    use crate::dto::model::{ApiErrorDto, AppConfigDto, StoredSecretDto};
     use crate::limits::LimitsService;
     use crate::metrics::service::MetricsServer;
+    use crate::middleware::access_log::AccessLogger;
+    use crate::middleware::body_limit::BodyLimit;
     use crate::middleware::client_ip::ClientIpExtractor;
+    use crate::middleware::request_id::{REQUEST_ID_HEADER, RequestIdPropagator};
     use crate::routes::{
+        binary::SECRET_METADATA_HEADER,
         config::get_config_route,
-        secret::{get_secret_route, store_secret_route},
+        secret::{REVOCATION_TOKEN_HEADER, get_secret_route, store_secret_route},
+        upload::{
+            FILE_KEY_HEADER, complete_upload_route, get_file_route, start_upload_route,
+            upload_chunk_route,
+        },
     };
     use crate::secret::model::{
         Secret, SecretContentType, SecretDownloadPolicy, SecretFileMetadata, SecretTTL,
     };
     use crate::secret::storage::MockSecretStorage;
+    use crate::shutdown::ShutdownSignal;
+    use crate::tests::config::get_test_app_config;
     use axum::{
         Router,
         body::Body,
         extract::{ConnectInfo, DefaultBodyLimit},
         http::{HeaderMap, Request, StatusCode},
         middleware,
-        routing::{get, post},
+        routing::{get, post, put},
     };
     use std::net::SocketAddr;
     use std::sync::Arc;
@@ -28,45 +40,51 @@ mod tests {
 
     fn create_test_app_state(ip_limits_config: Option<IpLimitsConfig>) -> Arc<AppState> {
         let config = AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
-            message_max_length: 1024,
-            file_upload_enabled: true,
-            file_max_size: 10485760,
             encrypted_message_max_length: Some(15485760),
-            redis_url: "redis://localhost".to_string(),
             ip_limits: ip_limits_config,
+            ..get_test_app_config()
         };
 
         let limits_service = LimitsService::new(&config);
         let secret_storage = MockSecretStorage::new();
 
-        let body_limit = limits_service
-            .body_limit_as_usize()
-            .expect("Failed to calculate body limit");
-
-        let metrics_server = MetricsServer::new(config.clone(), body_limit);
+        let metrics_server = MetricsServer::new(config.clone(), limits_service.clone(), None);
 
         Arc::new(AppState {
             config,
             limits_service,
             secret_storage: Box::new(secret_storage),
-            body_limit,
             metrics_server,
+            shutdown: ShutdownSignal::new(),
+            config_reload: ConfigReloadStatus::default(),
+            audit_log: AuditLog::disabled(),
         })
     }
 
     fn create_test_router(app_state: Arc<AppState>) -> Router {
-        let body_limit = app_state.body_limit;
+        let body_limit_layer =
+            middleware::from_fn_with_state(app_state.limits_service.clone(), BodyLimit::middleware);
 
         Router::new()
             .route("/api/config", get(get_config_route))
             .route(
                 "/api/secret",
-                post(store_secret_route).layer(DefaultBodyLimit::max(body_limit)),
+                post(store_secret_route)
+                    .layer(DefaultBodyLimit::disable())
+                    .layer(body_limit_layer.clone()),
             )
             .route("/api/secret/{id}", get(get_secret_route))
+            .route("/api/secret/{id}/file", get(get_file_route))
+            .route("/api/upload", post(start_upload_route))
+            .route(
+                "/api/upload/{id}/chunks/{index}",
+                put(upload_chunk_route)
+                    .layer(DefaultBodyLimit::disable())
+                    .layer(body_limit_layer),
+            )
+            .route("/api/upload/{id}/complete", post(complete_upload_route))
+            .layer(middleware::from_fn(AccessLogger::middleware))
+            .layer(middleware::from_fn(RequestIdPropagator::middleware))
             .layer(middleware::from_fn(ClientIpExtractor::middleware))
             .with_state(app_state)
     }
@@ -80,9 +98,13 @@ mod tests {
                 r#type: "text/plain".to_string(),
                 size: payload_size as u64,
             },
-            payload: "A".repeat(payload_size),
-            ttl: SecretTTL::OneHour,
+            encrypted_metadata: None,
+            payload: vec![b'A'; payload_size].into(),
+            ttl: Some(SecretTTL::OneHour),
             download_policy: SecretDownloadPolicy::OneTime,
+            remaining_views: None,
+            revocation_token_hash: None,
+            chunks: None,
         }
     }
 
@@ -118,6 +140,7 @@ mod tests {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -151,6 +174,7 @@ mod tests {
                 ip: "203.0.113.195".to_string(),
                 message_max_length: Some(16384),
                 file_max_size: Some(209715200),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -227,6 +251,31 @@ mod tests {
         assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
 
+    #[tokio::test]
+    async fn test_end_to_end_malformed_json_should_return_error_body() {
+        let app_state = create_test_app_state(None);
+        let app = create_test_router(app_state);
+
+        for uri in ["/api/secret", "/api/upload"] {
+            let request = Request::builder()
+                .uri(uri)
+                .method("POST")
+                .extension(ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080))))
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"id":"#))
+                .unwrap();
+
+            let response = app.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let error: ApiErrorDto = serde_json::from_slice(&body).unwrap();
+            assert_eq!(error.code, "invalid_request");
+        }
+    }
+
     #[tokio::test]
     async fn test_end_to_end_secret_storage_with_increased_ip_limits() {
         let ip_limits = IpLimitsConfig {
@@ -234,7 +283,8 @@ mod tests {
             whitelist: vec![IpLimitEntry {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192), // 8x default
-                file_max_size: Some(4096000),   // 4MB instead of 100MB
+                file_max_size: Some(4096000),   // 4MB instead of 100MB,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -265,6 +315,7 @@ mod tests {
                 ip: "192.168.0.0/16".to_string(),
                 message_max_length: Some(4096),
                 file_max_size: Some(52428800),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -331,11 +382,13 @@ mod tests {
                     ip: "203.0.113.195".to_string(), // From X-Forwarded-For
                     message_max_length: Some(8192),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "198.51.100.178".to_string(), // From X-Real-IP
                     message_max_length: Some(4096),
                     file_max_size: Some(52428800),
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
@@ -379,6 +432,7 @@ mod tests {
                 ip: "192.168.1.1".to_string(), // Connection IP
                 message_max_length: Some(2048),
                 file_max_size: Some(26214400),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -420,7 +474,8 @@ mod tests {
             whitelist: vec![IpLimitEntry {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
-                file_max_size: Some(4096000), // 4MB instead of 100MB
+                file_max_size: Some(4096000), // 4MB instead of 100MB,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -447,7 +502,6 @@ mod tests {
         // Step 2: Store secret
         let app = create_test_router(app_state.clone());
         let secret = create_test_secret(SecretContentType::Text, 3_000_000); // Within increased limit (3MB)
-        let secret_id = secret.id.clone();
 
         let store_request = Request::builder()
             .uri("/api/secret")
@@ -460,10 +514,17 @@ mod tests {
         let store_response = app.oneshot(store_request).await.unwrap();
         assert_eq!(store_response.status(), StatusCode::OK);
 
+        let store_body = axum::body::to_bytes(store_response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stored: StoredSecretDto = serde_json::from_slice(&store_body).unwrap();
+        let secret_id = stored.id;
+        assert_ne!(secret_id, secret.id);
+
         // Step 3: Retrieve secret
         let app = create_test_router(app_state);
         let get_request = Request::builder()
-            .uri(&format!("/api/secret/{}", secret_id))
+            .uri(format!("/api/secret/{}", secret_id))
             .extension(ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080))))
             .body(Body::empty())
             .unwrap();
@@ -477,4 +538,290 @@ mod tests {
         let retrieved_secret: Secret = serde_json::from_slice(&secret_body).unwrap();
         assert_eq!(retrieved_secret.id, secret_id);
     }
+
+    #[tokio::test]
+    async fn test_end_to_end_request_id_is_returned() {
+        let app_state = create_test_app_state(None);
+
+        let request = Request::builder()
+            .uri("/api/config")
+            .header(REQUEST_ID_HEADER, "lb-4f2a9c")
+            .extension(ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080))))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "lb-4f2a9c");
+
+        let request = Request::builder()
+            .uri("/api/secret/unknown")
+            .extension(ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080))))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state)
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_ne!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(REQUEST_ID_HEADER));
+    }
+
+    /// Uploads 250 bytes file in 3 chunks and views its secret, returns secret id and file key
+    async fn upload_test_file(
+        app_state: Arc<AppState>,
+        download_policy: &str,
+        file: &[u8],
+    ) -> (String, String) {
+        let client_ip = ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080)));
+
+        let upload = serde_json::json!({
+            "metadata": {"name": "big.bin", "type": "application/octet-stream", "size": 250},
+            "downloadPolicy": download_policy,
+            "chunkCount": 3,
+            "chunkSize": 100,
+            "size": 250,
+        });
+
+        let request = Request::builder()
+            .uri("/api/upload")
+            .method("POST")
+            .extension(client_ip)
+            .header("content-type", "application/json")
+            .body(Body::from(upload.to_string()))
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stored: StoredSecretDto = serde_json::from_slice(&body).unwrap();
+
+        for (index, chunk) in file.chunks(100).enumerate() {
+            let request = Request::builder()
+                .uri(format!("/api/upload/{}/chunks/{}", stored.id, index))
+                .method("PUT")
+                .extension(client_ip)
+                .header(REVOCATION_TOKEN_HEADER, &stored.revocation_token)
+                .body(Body::from(chunk.to_vec()))
+                .unwrap();
+
+            let response = create_test_router(app_state.clone())
+                .oneshot(request)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let request = Request::builder()
+            .uri(format!("/api/upload/{}/complete", stored.id))
+            .method("POST")
+            .extension(client_ip)
+            .header(REVOCATION_TOKEN_HEADER, &stored.revocation_token)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = Request::builder()
+            .uri(format!("/api/secret/{}", stored.id))
+            .extension(client_ip)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let secret: Secret = serde_json::from_slice(&body).unwrap();
+        let file_key = secret.chunks.unwrap().key;
+
+        (stored.id, file_key)
+    }
+
+    #[tokio::test]
+    async fn test_end_to_end_chunked_upload_and_ranged_download() {
+        let app_state = create_test_app_state(None);
+        let client_ip = ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080)));
+
+        let file: Vec<u8> = (0..250).map(|byte| byte as u8).collect();
+        let (id, file_key) = upload_test_file(app_state.clone(), "Unlimited", &file).await;
+
+        let request = Request::builder()
+            .uri(format!("/api/secret/{id}/file"))
+            .extension(client_ip)
+            .header(FILE_KEY_HEADER, &file_key)
+            .header("range", "bytes=90-209")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()["content-range"], "bytes 90-209/250");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.to_vec(), file[90..210].to_vec());
+
+        let request = Request::builder()
+            .uri(format!("/api/secret/{id}/file"))
+            .extension(client_ip)
+            .header(FILE_KEY_HEADER, &file_key)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-length"], "250");
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.to_vec(), file);
+
+        let request = Request::builder()
+            .uri(format!("/api/secret/{id}/file"))
+            .extension(client_ip)
+            .header(FILE_KEY_HEADER, "wrong-key")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state)
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_end_to_end_one_time_file_should_be_downloaded_once() {
+        let app_state = create_test_app_state(None);
+        let client_ip = ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080)));
+
+        let file: Vec<u8> = (0..250).map(|byte| byte as u8).collect();
+        let (id, file_key) = upload_test_file(app_state.clone(), "OneTime", &file).await;
+
+        let download = |range: Option<&str>| {
+            let mut request = Request::builder()
+                .uri(format!("/api/secret/{id}/file"))
+                .extension(client_ip)
+                .header(FILE_KEY_HEADER, &file_key);
+            if let Some(range) = range {
+                request = request.header("range", range);
+            }
+            create_test_router(app_state.clone()).oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        // Interrupted download may be resumed
+        let response = download(Some("bytes=0-99")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let response = download(None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.to_vec(), file);
+
+        let response = download(None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_end_to_end_binary_payload_transport() {
+        let app_state = create_test_app_state(None);
+        let client_ip = ConnectInfo(SocketAddr::from(([192, 168, 1, 100], 8080)));
+
+        let payload: Vec<u8> = (0..=255).collect();
+        let metadata = r#"{"contentType":"File","metadata":{"name":"\u00e9t\u00e9.bin","type":"application/octet-stream","size":256},"ttl":"OneHour","downloadPolicy":"Unlimited"}"#;
+
+        let request = Request::builder()
+            .uri("/api/secret")
+            .method("POST")
+            .extension(client_ip)
+            .header("content-type", "application/octet-stream")
+            .header(SECRET_METADATA_HEADER, metadata)
+            .body(Body::from(payload.clone()))
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let stored: StoredSecretDto = serde_json::from_slice(&body).unwrap();
+
+        let request = Request::builder()
+            .uri(format!("/api/secret/{}", stored.id))
+            .extension(client_ip)
+            .header("accept", "application/octet-stream")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state.clone())
+            .oneshot(request)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["content-type"],
+            "application/octet-stream"
+        );
+
+        let secret: serde_json::Value =
+            serde_json::from_slice(response.headers()[SECRET_METADATA_HEADER].as_bytes()).unwrap();
+        assert_eq!(secret["metadata"]["name"], "été.bin");
+        assert!(secret.get("payload").is_none());
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body.to_vec(), payload);
+
+        // JSON readers get the same payload as base64
+        let request = Request::builder()
+            .uri(format!("/api/secret/{}", stored.id))
+            .extension(client_ip)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = create_test_router(app_state)
+            .oneshot(request)
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let secret: Secret = serde_json::from_slice(&body).unwrap();
+        assert_eq!(secret.payload.as_bytes(), payload.as_slice());
+    }
 }
@@ -1,3 +1,4 @@
+pub mod rate_limit;
 pub mod service;
 
 pub use service::LimitsService;
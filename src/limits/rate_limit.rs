@@ -0,0 +1,246 @@
+use crate::config::model::RateLimitRule;
+use crate::secret::redis_pool::RedisConnectionPool;
+use async_trait::async_trait;
+use log::debug;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const REDIS_KEY_PREFIX: &str = "pw:rate-limit";
+
+/// Stale windows are dropped once the number of tracked keys reaches this value
+const MEMORY_CLEANUP_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitAction {
+    Create,
+    Read,
+    Delete,
+}
+
+impl Display for RateLimitAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            RateLimitAction::Create => "create",
+            RateLimitAction::Read => "read",
+            RateLimitAction::Delete => "delete",
+        };
+        write!(f, "{value}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RateLimitDecision {
+    Allowed,
+    Limited { retry_after_seconds: u64 },
+}
+
+/// Fixed window request counters
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Registers one request for the key and checks it against the rule
+    async fn hit(&self, key: &str, rule: &RateLimitRule) -> anyhow::Result<RateLimitDecision>;
+}
+
+struct RateLimitWindow {
+    started_at: Instant,
+    period: Duration,
+    count: u32,
+}
+
+impl RateLimitWindow {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.started_at) >= self.period
+    }
+}
+
+/// Counters are kept per instance
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    windows: Mutex<HashMap<String, RateLimitWindow>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> MemoryRateLimitStore {
+        MemoryRateLimitStore::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn hit(&self, key: &str, rule: &RateLimitRule) -> anyhow::Result<RateLimitDecision> {
+        let now = Instant::now();
+        let period = Duration::from_secs(rule.period_seconds.max(1));
+
+        let mut windows = self.windows.lock().unwrap();
+
+        if windows.len() >= MEMORY_CLEANUP_THRESHOLD {
+            windows.retain(|_, window| !window.is_expired(now));
+            debug!("rate limit windows left after cleanup: {}", windows.len());
+        }
+
+        let window = windows
+            .entry(key.to_string())
+            .or_insert_with(|| RateLimitWindow {
+                started_at: now,
+                period,
+                count: 0,
+            });
+
+        if window.is_expired(now) {
+            window.started_at = now;
+            window.period = period;
+            window.count = 0;
+        }
+
+        window.count = window.count.saturating_add(1);
+
+        if window.count > rule.requests {
+            let elapsed = now.duration_since(window.started_at);
+            let retry_after = window.period.saturating_sub(elapsed);
+            return Ok(RateLimitDecision::Limited {
+                retry_after_seconds: retry_after_seconds(retry_after),
+            });
+        }
+
+        Ok(RateLimitDecision::Allowed)
+    }
+}
+
+/// Counters are shared between replicas
+#[derive(Clone)]
+pub struct RedisRateLimitStore {
+    pool: RedisConnectionPool,
+}
+
+impl RedisRateLimitStore {
+    pub fn new(pool: RedisConnectionPool) -> RedisRateLimitStore {
+        RedisRateLimitStore { pool }
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn hit(&self, key: &str, rule: &RateLimitRule) -> anyhow::Result<RateLimitDecision> {
+        let mut cnn = self.pool.get().await?;
+
+        let script = redis::Script::new(
+            r#"
+            local count = redis.call('INCR', KEYS[1])
+            local ttl = redis.call('TTL', KEYS[1])
+            if ttl < 0 then
+                redis.call('EXPIRE', KEYS[1], ARGV[1])
+                ttl = tonumber(ARGV[1])
+            end
+            return {count, ttl}
+            "#,
+        );
+
+        let (count, ttl): (u64, u64) = script
+            .key(format!("{REDIS_KEY_PREFIX}:{key}"))
+            .arg(rule.period_seconds.max(1))
+            .invoke_async(&mut cnn)
+            .await?;
+
+        if count > rule.requests as u64 {
+            return Ok(RateLimitDecision::Limited {
+                retry_after_seconds: ttl.max(1),
+            });
+        }
+
+        Ok(RateLimitDecision::Allowed)
+    }
+}
+
+fn retry_after_seconds(duration: Duration) -> u64 {
+    let seconds = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+    seconds.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::RedisPoolConfig;
+    use crate::secret::storage::DEFAULT_REDIS_CNN_URL;
+    use crate::tests::string::get_random_string;
+
+    #[tokio::test]
+    async fn requests_above_limit_should_be_rejected() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule::new(2, 60);
+
+        assert_eq!(
+            store.hit("read:10.0.0.1", &rule).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+        assert_eq!(
+            store.hit("read:10.0.0.1", &rule).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+
+        match store.hit("read:10.0.0.1", &rule).await.unwrap() {
+            RateLimitDecision::Limited {
+                retry_after_seconds,
+            } => assert!((1..=60).contains(&retry_after_seconds)),
+            RateLimitDecision::Allowed => panic!("request should be limited"),
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_should_have_separate_counters() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule::new(1, 60);
+
+        assert_eq!(
+            store.hit("read:10.0.0.1", &rule).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+        assert_eq!(
+            store.hit("create:10.0.0.1", &rule).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+        assert_eq!(
+            store.hit("read:10.0.0.2", &rule).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+    }
+
+    #[tokio::test]
+    async fn counter_should_be_reset_after_window() {
+        let store = MemoryRateLimitStore::new();
+        let rule = RateLimitRule::new(1, 60);
+
+        store.hit("read:10.0.0.1", &rule).await.unwrap();
+
+        {
+            let mut windows = store.windows.lock().unwrap();
+            let window = windows.get_mut("read:10.0.0.1").unwrap();
+            window.started_at = Instant::now() - Duration::from_secs(61);
+        }
+
+        assert_eq!(
+            store.hit("read:10.0.0.1", &rule).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn redis_requests_above_limit_should_be_rejected() {
+        let pool =
+            RedisConnectionPool::new(DEFAULT_REDIS_CNN_URL, &RedisPoolConfig::default()).unwrap();
+        let store = RedisRateLimitStore::new(pool);
+        let rule = RateLimitRule::new(1, 60);
+        let key = get_random_string();
+
+        assert_eq!(
+            store.hit(&key, &rule).await.unwrap(),
+            RateLimitDecision::Allowed
+        );
+        assert!(matches!(
+            store.hit(&key, &rule).await.unwrap(),
+            RateLimitDecision::Limited { .. }
+        ));
+    }
+}
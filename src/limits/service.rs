@@ -1,23 +1,70 @@
 use std::net::IpAddr;
 use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 
 use ipnet::IpNet;
 use log::{debug, info};
 
-use crate::config::model::{AppConfig, IpLimitEntry};
+use crate::config::model::{AppConfig, IpLimitEntry, IpLimitsConfig, RateLimitRule};
+use crate::secret::model::ENCRYPTED_METADATA_MAX_LENGTH;
+
+/// Matches client address against IP or CIDR rule from `ip-limits` config
+pub fn matches_ip_rule(client_ip: &IpAddr, rule_ip: &str) -> bool {
+    if let Ok(exact_ip) = IpAddr::from_str(rule_ip) {
+        return *client_ip == exact_ip;
+    }
+
+    if let Ok(network) = IpNet::from_str(rule_ip) {
+        return network.contains(client_ip);
+    }
+
+    false
+}
 
 #[derive(Debug, Clone)]
 pub struct ClientLimits {
     pub message_max_length: u16,
     pub file_max_size: u64,
     pub encrypted_message_max_length: u64,
+    /// Max encrypted chunk size for chunked file uploads
+    pub chunk_max_size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientRateLimits {
+    pub create: RateLimitRule,
+    pub read: RateLimitRule,
+    pub delete: RateLimitRule,
+}
+
+/// Limits calculated from a single config version
+#[derive(Debug)]
+struct LimitsRules {
+    default_limits: ClientLimits,
+    default_rate_limits: ClientRateLimits,
+    file_chunk_max_size: u64,
+    ip_limits: Option<IpLimitsConfig>,
+}
+
+impl LimitsRules {
+    fn ip_limits_enabled(&self) -> bool {
+        self.ip_limits
+            .as_ref()
+            .is_some_and(|ip_limits| ip_limits.enabled)
+    }
+
+    fn ip_whitelist(&self) -> &[IpLimitEntry] {
+        match &self.ip_limits {
+            Some(ip_limits) => &ip_limits.whitelist,
+            None => &[],
+        }
+    }
 }
 
+/// Clones share rules, `reload_ip_limits` replaces them for all clones at once
 #[derive(Debug, Clone)]
 pub struct LimitsService {
-    default_limits: ClientLimits,
-    ip_limits_enabled: bool,
-    ip_whitelist: Vec<IpLimitEntry>,
+    rules: Arc<RwLock<Arc<LimitsRules>>>,
 }
 
 impl LimitsService {
@@ -34,27 +81,73 @@ impl LimitsService {
             message_max_length: config.message_max_length,
             file_max_size: config.file_max_size,
             encrypted_message_max_length,
+            chunk_max_size: config.file_chunk_max_size.min(encrypted_message_max_length),
         };
 
-        let (ip_limits_enabled, ip_whitelist) = match &config.ip_limits {
-            Some(ip_limits) => (ip_limits.enabled, ip_limits.whitelist.clone()),
-            None => (false, Vec::new()),
+        let default_rate_limits = ClientRateLimits {
+            create: config.rate_limits.create.clone(),
+            read: config.rate_limits.read.clone(),
+            delete: config.rate_limits.delete.clone(),
         };
 
-        Self {
+        let rules = LimitsRules {
             default_limits,
-            ip_limits_enabled,
-            ip_whitelist,
+            default_rate_limits,
+            file_chunk_max_size: config.file_chunk_max_size,
+            ip_limits: config.ip_limits.clone(),
+        };
+
+        Self {
+            rules: Arc::new(RwLock::new(Arc::new(rules))),
         }
     }
 
+    fn rules(&self) -> Arc<LimitsRules> {
+        self.rules.read().unwrap().clone()
+    }
+
+    /// Replaces whitelist and trusted proxies, default limits stay the same.
+    ///
+    /// Returns new body limit, current rules are kept when it can't be calculated.
+    pub fn reload_ip_limits(&self, ip_limits: Option<IpLimitsConfig>) -> anyhow::Result<usize> {
+        let current = self.rules();
+
+        let candidate = LimitsService {
+            rules: Arc::new(RwLock::new(Arc::new(LimitsRules {
+                default_limits: current.default_limits.clone(),
+                default_rate_limits: current.default_rate_limits.clone(),
+                file_chunk_max_size: current.file_chunk_max_size,
+                ip_limits,
+            }))),
+        };
+
+        let body_limit = candidate
+            .body_limit_as_usize()
+            .ok_or_else(|| anyhow::anyhow!("unable to calculate body limit"))?;
+
+        *self.rules.write().unwrap() = candidate.rules();
+
+        Ok(body_limit)
+    }
+
+    /// Current `ip-limits` config, used to decide which proxies are trusted
+    pub fn get_ip_limits_config(&self) -> Option<IpLimitsConfig> {
+        self.rules().ip_limits.clone()
+    }
+
+    pub fn is_ip_limits_enabled(&self) -> bool {
+        self.rules().ip_limits_enabled()
+    }
+
     pub fn get_limits_for_ip(&self, client_ip: &str) -> ClientLimits {
-        if !self.ip_limits_enabled {
+        let rules = self.rules();
+
+        if !rules.ip_limits_enabled() {
             debug!(
                 "IP limits disabled, using default limits for client IP: {}",
                 client_ip
             );
-            return self.default_limits.clone();
+            return rules.default_limits.clone();
         }
 
         let client_ip_addr = match IpAddr::from_str(client_ip) {
@@ -64,13 +157,13 @@ impl LimitsService {
                     "Failed to parse client IP '{}': {}. Using default limits",
                     client_ip, e
                 );
-                return self.default_limits.clone();
+                return rules.default_limits.clone();
             }
         };
 
-        for entry in &self.ip_whitelist {
+        for entry in rules.ip_whitelist() {
             if self.matches_ip(&client_ip_addr, &entry.ip) {
-                let limits = self.calculate_limits_for_entry(entry);
+                let limits = Self::calculate_limits_for_entry(&rules, entry);
                 info!(
                     "Applied custom IP limits for {}: matched rule '{}' -> message_max_length: {}, file_max_size: {}, encrypted_message_max_length: {}",
                     client_ip,
@@ -87,28 +180,48 @@ impl LimitsService {
             "No IP limit rules matched for {}, using default limits",
             client_ip
         );
-        self.default_limits.clone()
+        rules.default_limits.clone()
     }
 
-    fn matches_ip(&self, client_ip: &IpAddr, rule_ip: &str) -> bool {
-        if let Ok(exact_ip) = IpAddr::from_str(rule_ip) {
-            return *client_ip == exact_ip;
+    /// Returns rate limits for client, whitelist entry overrides take precedence over global rules
+    pub fn get_rate_limits_for_ip(&self, client_ip: &IpAddr) -> ClientRateLimits {
+        let rules = self.rules();
+
+        if !rules.ip_limits_enabled() {
+            return rules.default_rate_limits.clone();
         }
 
-        if let Ok(network) = IpNet::from_str(rule_ip) {
-            return network.contains(client_ip);
+        let entry = rules
+            .ip_whitelist()
+            .iter()
+            .find(|entry| self.matches_ip(client_ip, &entry.ip));
+
+        match entry.and_then(|entry| entry.rate_limits.as_ref()) {
+            Some(overrides) => {
+                let defaults = &rules.default_rate_limits;
+                let limits = ClientRateLimits {
+                    create: overrides.create.clone().unwrap_or(defaults.create.clone()),
+                    read: overrides.read.clone().unwrap_or(defaults.read.clone()),
+                    delete: overrides.delete.clone().unwrap_or(defaults.delete.clone()),
+                };
+                debug!("applied custom rate limits for {}: {:?}", client_ip, limits);
+                limits
+            }
+            None => rules.default_rate_limits.clone(),
         }
+    }
 
-        false
+    fn matches_ip(&self, client_ip: &IpAddr, rule_ip: &str) -> bool {
+        matches_ip_rule(client_ip, rule_ip)
     }
 
-    fn calculate_limits_for_entry(&self, entry: &IpLimitEntry) -> ClientLimits {
+    fn calculate_limits_for_entry(rules: &LimitsRules, entry: &IpLimitEntry) -> ClientLimits {
         let message_max_length = entry
             .message_max_length
-            .unwrap_or(self.default_limits.message_max_length);
+            .unwrap_or(rules.default_limits.message_max_length);
         let file_max_size = entry
             .file_max_size
-            .unwrap_or(self.default_limits.file_max_size);
+            .unwrap_or(rules.default_limits.file_max_size);
 
         let encrypted_message_max_length =
             Self::calculate_encrypted_max_length(message_max_length, file_max_size);
@@ -117,10 +230,11 @@ impl LimitsService {
             message_max_length,
             file_max_size,
             encrypted_message_max_length,
+            chunk_max_size: rules.file_chunk_max_size.min(encrypted_message_max_length),
         }
     }
 
-    fn calculate_encrypted_max_length(message_limit: u16, file_limit: u64) -> u64 {
+    pub fn calculate_encrypted_max_length(message_limit: u16, file_limit: u64) -> u64 {
         let overhead_factor = 1.35; // 35% overhead for encryption
         let max_content_size = std::cmp::max(message_limit as u64, file_limit);
         (max_content_size as f64 * overhead_factor) as u64
@@ -135,11 +249,12 @@ impl LimitsService {
     /// Returns the calculated limit with encryption overhead applied, suitable
     /// for use with Axum's DefaultBodyLimit::max()
     pub fn calculate_max_body_limit(&self) -> u64 {
-        let mut max_limit = self.default_limits.encrypted_message_max_length;
+        let rules = self.rules();
+        let mut max_limit = rules.default_limits.encrypted_message_max_length;
 
-        if self.ip_limits_enabled {
-            for entry in &self.ip_whitelist {
-                let entry_limits = self.calculate_limits_for_entry(entry);
+        if rules.ip_limits_enabled() {
+            for entry in rules.ip_whitelist() {
+                let entry_limits = Self::calculate_limits_for_entry(&rules, entry);
                 max_limit = std::cmp::max(max_limit, entry_limits.encrypted_message_max_length);
             }
         }
@@ -149,16 +264,25 @@ impl LimitsService {
 
     /// Converts body limit to usize with safety margin and overflow protection
     ///
-    /// Adds a small safety margin (5%) to account for HTTP headers,
-    /// multipart boundaries, and JSON formatting overhead beyond encryption.
+    /// Applies to raw `application/octet-stream` bodies, adds a small safety
+    /// margin (5%) to account for HTTP overhead beyond encryption.
     ///
     /// Returns None if the limit would overflow usize on 32-bit systems.
     pub fn body_limit_as_usize(&self) -> Option<usize> {
-        let base_limit = self.calculate_max_body_limit();
+        Self::limit_with_margin_as_usize(self.calculate_max_body_limit() as f64)
+    }
 
+    /// Body limit for JSON requests, payload and encrypted metadata are base64 encoded
+    /// there and take 4/3 of raw size
+    pub fn json_body_limit_as_usize(&self) -> Option<usize> {
+        let raw_limit = self.calculate_max_body_limit() + ENCRYPTED_METADATA_MAX_LENGTH as u64;
+        Self::limit_with_margin_as_usize(raw_limit as f64 * 4.0 / 3.0)
+    }
+
+    fn limit_with_margin_as_usize(base_limit: f64) -> Option<usize> {
         // Add 5% safety margin for HTTP overhead
         let safety_factor = 1.05;
-        let limit_with_margin = (base_limit as f64 * safety_factor) as u64;
+        let limit_with_margin = (base_limit * safety_factor) as u64;
 
         // Check for usize overflow
         if limit_with_margin > usize::MAX as u64 {
@@ -178,19 +302,10 @@ impl LimitsService {
 mod tests {
     use super::*;
     use crate::config::model::IpLimitsConfig;
+    use crate::tests::config::get_test_app_config;
 
     fn create_test_config() -> AppConfig {
-        AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
-            message_max_length: 1024,
-            file_upload_enabled: true,
-            file_max_size: 10485760,
-            encrypted_message_max_length: None, // Will be calculated dynamically
-            redis_url: "redis://localhost".to_string(),
-            ip_limits: None,
-        }
+        get_test_app_config()
     }
 
     fn create_test_config_with_limits() -> AppConfig {
@@ -202,16 +317,19 @@ mod tests {
                     ip: "192.168.1.100".to_string(),
                     message_max_length: Some(8192),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "10.0.0.0/8".to_string(),
                     message_max_length: Some(4096),
                     file_max_size: None,
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "172.16.1.5".to_string(),
                     message_max_length: None,
                     file_max_size: Some(209715200),
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
@@ -351,6 +469,7 @@ mod tests {
                 ip: "2001:db8::/32".to_string(),
                 message_max_length: Some(16384),
                 file_max_size: Some(209715200),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         });
@@ -376,11 +495,13 @@ mod tests {
                     ip: "192.168.0.0/16".to_string(),
                     message_max_length: Some(2048),
                     file_max_size: Some(52428800),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "192.168.1.100".to_string(),
                     message_max_length: Some(8192),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
@@ -430,6 +551,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_chunk_max_size_is_capped_by_encrypted_max_length() {
+        let mut config = create_test_config_with_limits();
+        config.file_max_size = 1024;
+        config.file_chunk_max_size = 4096;
+        let service = LimitsService::new(&config);
+
+        // max(1024, 1024) * 1.35 = 1382
+        assert_eq!(service.get_limits_for_ip("8.8.8.8").chunk_max_size, 1382);
+        assert_eq!(
+            service.get_limits_for_ip("192.168.1.100").chunk_max_size,
+            4096
+        );
+    }
+
     #[test]
     fn test_calculate_max_body_limit_defaults_only() {
         let config = create_test_config(); // No IP limits
@@ -449,12 +585,14 @@ mod tests {
                 IpLimitEntry {
                     ip: "192.168.1.100".to_string(),
                     message_max_length: Some(8192),
-                    file_max_size: Some(104857600), // 100 MB
+                    file_max_size: Some(104857600), // 100 MB,
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "10.0.0.0/8".to_string(),
                     message_max_length: Some(4096),
-                    file_max_size: Some(52428800), // 50 MB
+                    file_max_size: Some(52428800), // 50 MB,
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
@@ -477,6 +615,7 @@ mod tests {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         });
@@ -557,12 +696,14 @@ mod tests {
                 IpLimitEntry {
                     ip: "192.168.1.100".to_string(),
                     message_max_length: Some(16384),
-                    file_max_size: None, // Uses default
+                    file_max_size: None, // Uses default,
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "10.0.0.1".to_string(),
                     message_max_length: None,       // Uses default
-                    file_max_size: Some(209715200), // 200 MB
+                    file_max_size: Some(209715200), // 200 MB,
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
@@ -598,6 +739,7 @@ mod tests {
                 ip: "192.168.1.0/24".to_string(),
                 message_max_length: Some(4096),
                 file_max_size: Some(52428800),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         });
@@ -629,16 +771,19 @@ mod tests {
                     ip: "10.0.0.0/8".to_string(),
                     message_max_length: Some(2048),
                     file_max_size: None,
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "172.16.0.0/12".to_string(),
                     message_max_length: Some(4096),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "192.168.0.0/16".to_string(),
                     message_max_length: None,
                     file_max_size: Some(209715200),
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
@@ -669,16 +814,19 @@ mod tests {
                     ip: "192.168.1.0/33".to_string(), // Invalid CIDR - too high mask
                     message_max_length: Some(8192),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "not.a.cidr/24".to_string(), // Invalid CIDR - malformed IP
                     message_max_length: Some(4096),
                     file_max_size: Some(52428800),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "192.168.1.100".to_string(), // Valid exact IP
                     message_max_length: Some(2048),
                     file_max_size: Some(26214400),
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
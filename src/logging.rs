@@ -2,28 +2,42 @@ use log::LevelFilter;
 use log4rs::append::console::ConsoleAppender;
 use log4rs::append::rolling_file::RollingFileAppender;
 use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
+use log4rs::append::rolling_file::policy::compound::roll::Roll;
+use log4rs::append::rolling_file::policy::compound::roll::delete::DeleteRoller;
 use log4rs::append::rolling_file::policy::compound::roll::fixed_window::FixedWindowRoller;
 use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
 use log4rs::config::{Appender, Config, Logger, Root};
+use log4rs::encode::Encode;
+use log4rs::encode::json::JsonEncoder;
 use log4rs::encode::pattern::PatternEncoder;
 use log4rs::filter::threshold::ThresholdFilter;
 
+use crate::config::model::{AppConfig, LogFileConfig};
+
 const FILE_APPENDER_NAME: &str = "file";
 const CONSOLE_APPENDER_NAME: &str = "console";
 
-const LOG_FILE_PATH: &str = "pw.log";
+/// Values accepted by `log-level`
+pub const LOG_LEVELS: [&str; 6] = ["trace", "debug", "info", "warn", "error", "off"];
+
+/// Values accepted by `log-target`, everything except `file` logs to console
+pub const LOG_TARGETS: [&str; 3] = ["console", "stdout", "file"];
+
+/// Values accepted by `log-format`
+pub const LOG_FORMATS: [&str; 2] = ["text", "json"];
 
-pub fn get_logging_config(logging_level: &str, log_target: &str) -> Config {
-    let level = get_logging_level_from_string(logging_level);
+pub fn get_logging_config(config: &AppConfig) -> Config {
+    let level = get_logging_level_from_string(&config.log_level);
+    let json = config.log_format == "json";
 
-    match log_target {
+    match config.log_target.as_str() {
         "file" => Config::builder()
-            .appender(get_rolling_appender(level))
+            .appender(get_rolling_appender(level, json, &config.log_file))
             .logger(get_default_logger(level))
             .build(Root::builder().appender(FILE_APPENDER_NAME).build(level))
-            .unwrap_or_else(|_| panic!("unable to create log file '{}'", LOG_FILE_PATH)),
+            .unwrap_or_else(|_| panic!("unable to create log file '{}'", config.log_file.path)),
         _ => Config::builder()
-            .appender(get_console_appender(level))
+            .appender(get_console_appender(level, json))
             .logger(get_default_logger(level))
             .build(Root::builder().appender(CONSOLE_APPENDER_NAME).build(level))
             .expect("unable to create console logging configuration"),
@@ -41,33 +55,43 @@ fn get_logging_level_from_string(level: &str) -> LevelFilter {
     }
 }
 
-fn get_rolling_appender(level: LevelFilter) -> Appender {
-    let log_file_format = format!("{}.{{}}", LOG_FILE_PATH);
+fn get_rolling_appender(level: LevelFilter, json: bool, log_file: &LogFileConfig) -> Appender {
+    let roller: Box<dyn Roll> = match log_file.max_files {
+        0 => Box::new(DeleteRoller::new()),
+        max_files => Box::new(
+            FixedWindowRoller::builder()
+                .build(&format!("{}.{{}}", log_file.path), max_files)
+                .expect("couldn't build fixed window roller"),
+        ),
+    };
 
-    let fixed_window_roller = FixedWindowRoller::builder()
-        .build(&log_file_format, 5)
-        .expect("couldn't build fixed window roller");
-
-    let size_trigger = SizeTrigger::new(100_000_000);
-    let policy = CompoundPolicy::new(Box::new(size_trigger), Box::new(fixed_window_roller));
+    let size_trigger = SizeTrigger::new(log_file.max_size);
+    let policy = CompoundPolicy::new(Box::new(size_trigger), roller);
     let rolling_appender = RollingFileAppender::builder()
-        .encoder(get_encoder())
-        .build(LOG_FILE_PATH, Box::new(policy))
-        .expect("couldn't build rolling appender");
+        .encoder(get_encoder(json))
+        .build(&log_file.path, Box::new(policy))
+        .unwrap_or_else(|e| panic!("couldn't open log file '{}': {}", log_file.path, e));
 
     Appender::builder()
         .filter(Box::new(ThresholdFilter::new(level)))
         .build(FILE_APPENDER_NAME, Box::new(rolling_appender))
 }
 
-fn get_encoder() -> Box<PatternEncoder> {
+/// JSON lines carry structured fields, e.g. request id, under `attributes`
+fn get_encoder(json: bool) -> Box<dyn Encode> {
+    if json {
+        return Box::new(JsonEncoder::new());
+    }
+
     Box::new(PatternEncoder::new(
         "{d(%Y-%m-%d %H:%M:%S)} - {l} - [{M}] - {m}{n}",
     ))
 }
 
-fn get_console_appender(level: LevelFilter) -> Appender {
-    let console_appender = ConsoleAppender::builder().encoder(get_encoder()).build();
+fn get_console_appender(level: LevelFilter, json: bool) -> Appender {
+    let console_appender = ConsoleAppender::builder()
+        .encoder(get_encoder(json))
+        .build();
 
     Appender::builder()
         .filter(Box::new(ThresholdFilter::new(level)))
@@ -1,21 +1,51 @@
-use crate::config::model::AppConfig;
-use crate::metrics::service::MetricsServer;
-use crate::routes::secret::{get_secret_route, remove_secret_route, store_secret_route};
+use crate::audit::AuditLog;
+use crate::config::model::{AppConfig, RateLimitBackend, StorageBackend};
+use crate::config::reload::ConfigReloadStatus;
+use crate::limits::rate_limit::{MemoryRateLimitStore, RateLimitStore, RedisRateLimitStore};
+use crate::metrics::service::{MetricsServer, REDIS_PROBE_INTERVAL};
+use crate::middleware::access_log::AccessLogger;
+use crate::middleware::body_limit::BodyLimit;
+use crate::middleware::metrics::MetricsCollector;
+use crate::middleware::metrics_access::MetricsAccessGuard;
+use crate::middleware::rate_limit::RateLimiter;
+use crate::middleware::request_id::RequestIdPropagator;
+use crate::routes::secret::{
+    get_secret_route, remove_secret_route, store_secret_route, store_secret_with_id_route,
+};
+use crate::routes::upload::{
+    complete_upload_route, get_file_route, start_upload_route, upload_chunk_route,
+};
+use crate::secret::embedded_storage::EmbeddedSecretStorage;
+use crate::secret::encryption::StorageKeys;
+use crate::secret::memory_storage::MemorySecretStorage;
+use crate::secret::record::RecordCodec;
+use crate::secret::redis_pool::RedisConnectionPool;
 use crate::secret::storage::{RedisSecretStorage, SecretStorage};
 use axum::Router;
 use axum::extract::DefaultBodyLimit;
+use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
 use axum::http::{StatusCode, Uri, header};
 use axum::response::{Html, IntoResponse, Response};
-use axum::routing::{get, post};
+use axum::routing::{get, post, put};
+use axum_server::tls_rustls::RustlsConfig;
+use clap::Parser;
+use cli::{Cli, Command};
 use config::file::load_config_from_file;
 use logging::get_logging_config;
 use routes::config::get_config_route;
+use routes::health::{get_health_route, get_readiness_route};
 use routes::metrics::get_metrics_route;
 use routes::version::get_version_route;
 use rust_embed::Embed;
-use std::path::Path;
+use shutdown::{ShutdownSignal, wait_for_os_signal};
+use std::net::SocketAddr;
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
 
+pub mod audit;
+pub mod cli;
 pub mod config;
 pub mod dto;
 pub mod limits;
@@ -24,6 +54,8 @@ pub mod metrics;
 pub mod middleware;
 pub mod routes;
 pub mod secret;
+pub mod shutdown;
+pub mod tls;
 
 #[cfg(test)]
 pub mod tests;
@@ -42,78 +74,435 @@ pub struct AppState {
     pub config: AppConfig,
     pub secret_storage: Box<dyn SecretStorage + Send + Sync>,
     pub limits_service: limits::LimitsService,
-    pub body_limit: usize,
     pub metrics_server: MetricsServer,
+    pub shutdown: ShutdownSignal,
+    pub config_reload: ConfigReloadStatus,
+    pub audit_log: AuditLog,
 }
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    let config_file = Path::new("pw.yml").to_str().expect("unexpected error");
+async fn main() -> ExitCode {
+    match Cli::parse().get_command() {
+        Command::Serve { config } => match serve(&config).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e:#}");
+                ExitCode::FAILURE
+            }
+        },
+        Command::CheckConfig { config } => check_config(&config),
+        Command::Version => {
+            println!("PW v{VERSION}");
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Effective config is printed as JSON which is also valid YAML
+fn check_config(config_file: &str) -> ExitCode {
+    let app_config = match load_config_from_file(config_file) {
+        Ok(app_config) => app_config,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if let Some(tls_config) = &app_config.tls
+        && let Err(e) = tls::load_server_config(tls_config)
+    {
+        eprintln!("error: {config_file}: {e:#}");
+        return ExitCode::FAILURE;
+    }
+
+    match serde_json::to_string_pretty(&app_config.redacted()) {
+        Ok(effective_config) => {
+            println!("{effective_config}");
+            eprintln!("config '{config_file}' is valid");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: unable to print config: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
+async fn serve(config_file: &str) -> anyhow::Result<()> {
     let app_config = load_config_from_file(config_file)?;
 
-    let logging_config = get_logging_config(&app_config.log_level, &app_config.log_target);
+    let logging_config = get_logging_config(&app_config);
     log4rs::init_config(logging_config).expect("unable to init logging configuration");
 
-    let secret_storage = RedisSecretStorage::new(&app_config.redis_url);
+    let shutdown = ShutdownSignal::new().with_pre_stop_delay(Duration::from_secs(
+        app_config.shutdown_pre_stop_delay_seconds,
+    ));
+    let mut background_tasks = Vec::new();
+
+    let redis_pool = match app_config.storage.backend {
+        StorageBackend::Redis => Some(RedisConnectionPool::new(
+            &app_config.redis_url,
+            &app_config.redis_pool,
+        )?),
+        _ => None,
+    };
+
+    let audit_log = AuditLog::new(&app_config, redis_pool.as_ref()).await?;
+
+    let secret_storage = create_secret_storage(
+        &app_config,
+        redis_pool.as_ref(),
+        &audit_log,
+        &mut background_tasks,
+    )?;
     let limits_service = limits::LimitsService::new(&app_config);
 
     let body_limit = limits_service
         .body_limit_as_usize()
         .expect("failed to calculate body limit");
 
+    let json_body_limit = limits_service
+        .json_body_limit_as_usize()
+        .expect("failed to calculate body limit");
+
     log::info!(
-        "configured HTTP body limit: {} bytes ({:.2} MB)",
+        "configured HTTP body limit: {} bytes ({:.2} MB), JSON body limit: {} bytes ({:.2} MB)",
         body_limit,
-        body_limit as f64 / 1_048_576.0
+        body_limit as f64 / 1_048_576.0,
+        json_body_limit,
+        json_body_limit as f64 / 1_048_576.0
     );
 
-    let app_state = AppState {
+    let rate_limiter = create_rate_limiter(&app_config, &limits_service, redis_pool.as_ref())?;
+
+    let metrics_server = MetricsServer::new(app_config.clone(), limits_service.clone(), redis_pool);
+    background_tasks.extend(metrics_server.spawn_redis_probe(REDIS_PROBE_INTERVAL));
+
+    let config_reload = ConfigReloadStatus::default();
+
+    let app_state = Arc::new(AppState {
         config: app_config.clone(),
-        secret_storage: Box::new(secret_storage),
-        limits_service,
-        body_limit,
-        metrics_server: MetricsServer::new(app_config.clone(), body_limit),
-    };
+        secret_storage,
+        limits_service: limits_service.clone(),
+        metrics_server,
+        shutdown: shutdown.clone(),
+        config_reload: config_reload.clone(),
+        audit_log,
+    });
+
+    background_tasks.push(config::reload::spawn_ip_limits_reloader(
+        config_file,
+        app_config.clone(),
+        limits_service.clone(),
+        config_reload,
+    )?);
 
-    let body_limit_for_route = app_state.body_limit;
+    let body_limit_layer =
+        axum::middleware::from_fn_with_state(limits_service.clone(), BodyLimit::middleware);
 
-    let app = Router::new()
+    let mut app = Router::new()
+        .route("/healthz", get(get_health_route))
+        .route("/readyz", get(get_readiness_route))
         .route("/api/config", get(get_config_route))
-        .route("/api/metrics", get(get_metrics_route))
         .route(
             "/api/secret",
-            post(store_secret_route).layer(DefaultBodyLimit::max(body_limit_for_route)),
+            post(store_secret_route)
+                .layer(DefaultBodyLimit::disable())
+                .layer(body_limit_layer.clone()),
+        )
+        .route(
+            "/api/v1/secret",
+            post(store_secret_with_id_route)
+                .layer(DefaultBodyLimit::disable())
+                .layer(body_limit_layer.clone()),
         )
         .route(
             "/api/secret/{id}",
             get(get_secret_route).delete(remove_secret_route),
         )
+        .route("/api/secret/{id}/file", get(get_file_route))
+        .route("/api/upload", post(start_upload_route))
+        .route(
+            "/api/upload/{id}/chunks/{index}",
+            put(upload_chunk_route)
+                .layer(DefaultBodyLimit::disable())
+                .layer(body_limit_layer),
+        )
+        .route("/api/upload/{id}/complete", post(complete_upload_route))
         .route("/api/version", get(get_version_route))
-        .fallback(static_handler)
+        .fallback(static_handler);
+
+    let metrics_access_guard = Arc::new(MetricsAccessGuard::new(app_config.metrics_access.clone()));
+
+    let metrics_routes = Router::new()
+        .route("/api/metrics", get(get_metrics_route))
+        .route_layer(axum::middleware::from_fn_with_state(
+            metrics_access_guard,
+            MetricsAccessGuard::middleware,
+        ));
+
+    match &app_config.metrics_listen {
+        Some(metrics_bind) => {
+            let metrics_app = metrics_routes.with_state(app_state.clone());
+            background_tasks
+                .push(serve_metrics(metrics_bind, metrics_app, shutdown.clone()).await?);
+        }
+        None => app = app.merge(metrics_routes),
+    }
+
+    if let Some(rate_limiter) = rate_limiter {
+        app = app.layer(axum::middleware::from_fn_with_state(
+            Arc::new(rate_limiter),
+            RateLimiter::middleware,
+        ));
+    }
+
+    let app = app
+        .layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            MetricsCollector::middleware,
+        ))
+        .layer(axum::middleware::from_fn(AccessLogger::middleware))
+        .layer(axum::middleware::from_fn(RequestIdPropagator::middleware))
         .layer(axum::middleware::from_fn(
             middleware::client_ip::ClientIpExtractor::middleware,
         ))
-        .layer(axum::Extension(app_config.ip_limits.clone()))
-        .with_state(Arc::new(app_state));
+        .layer(axum::Extension(limits_service))
+        .with_state(app_state);
 
     let bind = app_config.listen.to_string();
 
     let listener = tokio::net::TcpListener::bind(&bind).await.unwrap();
 
+    let rustls_config = match &app_config.tls {
+        Some(tls_config) => {
+            let rustls_config = RustlsConfig::from_config(tls::load_server_config(tls_config)?);
+            background_tasks.push(tls::spawn_reloader(
+                tls_config.clone(),
+                rustls_config.clone(),
+            )?);
+            Some(rustls_config)
+        }
+        None => None,
+    };
+
+    let scheme = if rustls_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+
     println!("PW v{VERSION}");
-    println!("URL: http://{bind}");
+    println!("URL: {scheme}://{bind}");
+
+    tokio::spawn({
+        let shutdown = shutdown.clone();
+        let pre_stop_delay_seconds = app_config.shutdown_pre_stop_delay_seconds;
+        async move {
+            wait_for_os_signal().await;
+            log::info!(
+                "shutdown started, readiness reports not-ready, new connections are accepted for {} seconds",
+                pre_stop_delay_seconds
+            );
+            shutdown.trigger();
+
+            shutdown.wait().await;
+            log::info!("new connections are not accepted");
+        }
+    });
+
+    let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    let server = async {
+        match rustls_config {
+            Some(rustls_config) => {
+                serve_https(listener, make_service, rustls_config, shutdown.clone()).await
+            }
+            None => {
+                let shutdown = shutdown.clone();
+                axum::serve(listener, make_service)
+                    .with_graceful_shutdown(async move { shutdown.wait().await })
+                    .await?;
+                Ok(())
+            }
+        }
+    };
+
+    let drain_timeout = Duration::from_secs(app_config.shutdown_drain_timeout_seconds);
+
+    tokio::select! {
+        result = server => result?,
+        _ = async {
+            shutdown.wait().await;
+            tokio::time::sleep(drain_timeout).await;
+        } => {
+            log::warn!(
+                "connections were not drained within {} seconds, closing them",
+                drain_timeout.as_secs()
+            );
+        }
+    }
+
+    stop_background_tasks(background_tasks).await;
+
+    log::info!("shutdown completed");
+
+    Ok(())
+}
 
-    axum::serve(
-        listener,
-        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
-    )
-    .await
-    .unwrap();
+/// Background loops have no state to flush, they are cancelled at the next await point
+async fn stop_background_tasks(tasks: Vec<JoinHandle<()>>) {
+    for task in &tasks {
+        task.abort();
+    }
+
+    for task in tasks {
+        let _ = task.await;
+    }
+}
+
+/// Peer address is passed to `ConnectInfo` the same way as for plain HTTP.
+/// Drain timeout is enforced by the caller.
+async fn serve_https(
+    listener: tokio::net::TcpListener,
+    make_service: IntoMakeServiceWithConnectInfo<Router, SocketAddr>,
+    rustls_config: RustlsConfig,
+    shutdown: ShutdownSignal,
+) -> anyhow::Result<()> {
+    let handle = axum_server::Handle::new();
+
+    tokio::spawn({
+        let handle = handle.clone();
+        async move {
+            shutdown.wait().await;
+            handle.graceful_shutdown(None);
+        }
+    });
+
+    axum_server::from_tcp_rustls(listener.into_std()?, rustls_config)?
+        .handle(handle)
+        .serve(make_service)
+        .await?;
 
     Ok(())
 }
 
+/// Serves metrics on a dedicated listener in background until shutdown
+async fn serve_metrics(
+    bind: &str,
+    metrics_app: Router,
+    shutdown: ShutdownSignal,
+) -> anyhow::Result<JoinHandle<()>> {
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+
+    println!("Metrics URL: http://{bind}/api/metrics");
+
+    Ok(tokio::spawn(async move {
+        if let Err(e) = axum::serve(
+            listener,
+            metrics_app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move { shutdown.wait().await })
+        .await
+        {
+            log::error!("metrics listener has stopped: {}", e);
+        }
+    }))
+}
+
+/// Codec of redis and embedded backends, memory storage keeps secrets unencoded
+fn create_record_codec(app_config: &AppConfig) -> anyhow::Result<RecordCodec> {
+    let encryption = &app_config.storage.encryption;
+    let keys = StorageKeys::from_config(encryption)?;
+
+    match keys.as_ref().and_then(|keys| keys.write_key_id()) {
+        Some(key_id) => log::info!("storage encryption is enabled, key id '{key_id}'"),
+        None if keys.is_some() => {
+            log::info!("storage encryption is disabled, keys are used for reading")
+        }
+        None => log::info!("storage encryption is disabled"),
+    }
+
+    Ok(RecordCodec::new(app_config.storage.compression, keys))
+}
+
+type SecretStorageBox = Box<dyn SecretStorage + Send + Sync>;
+
+/// Redis connection pool is required only for Redis backend, sweeper tasks are added to
+/// `background_tasks` and record expirations to `audit_log`
+fn create_secret_storage(
+    app_config: &AppConfig,
+    redis_pool: Option<&RedisConnectionPool>,
+    audit_log: &AuditLog,
+    background_tasks: &mut Vec<JoinHandle<()>>,
+) -> anyhow::Result<SecretStorageBox> {
+    match app_config.storage.backend {
+        StorageBackend::Redis => {
+            let codec = create_record_codec(app_config)?;
+            let redis_pool = match redis_pool {
+                Some(redis_pool) => redis_pool.clone(),
+                None => RedisConnectionPool::new(&app_config.redis_url, &app_config.redis_pool)?,
+            };
+            let storage = RedisSecretStorage::new(redis_pool).with_codec(codec);
+            Ok(Box::new(storage))
+        }
+        StorageBackend::Embedded => {
+            let embedded_config = &app_config.storage.embedded;
+            let storage = EmbeddedSecretStorage::open(&embedded_config.path)?
+                .with_codec(create_record_codec(app_config)?);
+            background_tasks.push(storage.spawn_sweeper(
+                Duration::from_secs(embedded_config.sweep_interval_seconds.max(1)),
+                audit_log.clone(),
+            ));
+            Ok(Box::new(storage))
+        }
+        StorageBackend::Memory => {
+            let memory_config = &app_config.storage.memory;
+            let storage = MemorySecretStorage::new(memory_config);
+            background_tasks.push(storage.spawn_sweeper(
+                Duration::from_secs(memory_config.sweep_interval_seconds.max(1)),
+                audit_log.clone(),
+            ));
+            Ok(Box::new(storage))
+        }
+    }
+}
+
+/// Returns `None` when rate limits are disabled
+fn create_rate_limiter(
+    app_config: &AppConfig,
+    limits_service: &limits::LimitsService,
+    redis_pool: Option<&RedisConnectionPool>,
+) -> anyhow::Result<Option<RateLimiter>> {
+    let rate_limits = &app_config.rate_limits;
+
+    if !rate_limits.enabled {
+        log::info!("rate limits are disabled");
+        return Ok(None);
+    }
+
+    let store: Box<dyn RateLimitStore> = match rate_limits.backend {
+        RateLimitBackend::Memory => Box::new(MemoryRateLimitStore::new()),
+        RateLimitBackend::Redis => {
+            let redis_pool = match redis_pool {
+                Some(redis_pool) => redis_pool.clone(),
+                None => RedisConnectionPool::new(&app_config.redis_url, &app_config.redis_pool)?,
+            };
+            Box::new(RedisRateLimitStore::new(redis_pool))
+        }
+    };
+
+    log::info!(
+        "rate limits: backend {:?}, create {:?}, read {:?}, delete {:?}",
+        rate_limits.backend,
+        rate_limits.create,
+        rate_limits.read,
+        rate_limits.delete
+    );
+
+    Ok(Some(RateLimiter::new(limits_service.clone(), store)))
+}
+
 async fn index_html() -> Response {
     match Assets::get(INDEX_HTML) {
         Some(content) => Html(content.data).into_response(),
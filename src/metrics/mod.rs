@@ -1,3 +1,4 @@
 pub mod model;
 pub mod ports;
 pub mod service;
+pub mod traffic;
@@ -1,5 +1,24 @@
+use std::collections::BTreeMap;
 use std::fmt::Write;
 
+/// Request latency buckets in seconds
+pub const REQUEST_DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Secret payload size buckets in bytes, from 1 KB to 100 MB
+pub const PAYLOAD_SIZE_BUCKETS: [f64; 6] = [
+    1024.0,
+    10_240.0,
+    102_400.0,
+    1_048_576.0,
+    10_485_760.0,
+    104_857_600.0,
+];
+
+/// Metric labels as name-value pairs, rendered in the given order
+pub type Labels = Vec<(&'static str, String)>;
+
 #[derive(Debug, Clone)]
 pub struct Metrics {
     pub up: bool,
@@ -7,6 +26,8 @@ pub struct Metrics {
     pub uptime_seconds: f64,
     pub redis: RedisMetrics,
     pub config: ConfigMetrics,
+    pub secrets: SecretMetrics,
+    pub traffic: TrafficMetrics,
 }
 
 #[derive(Debug, Clone)]
@@ -29,19 +50,132 @@ pub struct ConfigMetrics {
     pub body_limit_bytes: usize,
 }
 
+#[derive(Debug, Clone)]
+pub struct SecretMetrics {
+    pub lookup_failures_total: u64,
+}
+
+/// Counters and histograms collected by the metrics middleware
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrafficMetrics {
+    /// Labels: content_type, ttl, download_policy
+    pub secrets_created_total: BTreeMap<Labels, u64>,
+    pub secrets_read_total: BTreeMap<Labels, u64>,
+    pub secrets_deleted_total: BTreeMap<Labels, u64>,
+    /// Labels: action
+    pub secrets_not_found_total: BTreeMap<Labels, u64>,
+    /// Labels: reason
+    pub limit_rejections_total: BTreeMap<Labels, u64>,
+    /// Labels: route, method
+    pub request_duration_seconds: BTreeMap<Labels, Histogram>,
+    pub payload_size_bytes: Histogram,
+}
+
+impl Default for TrafficMetrics {
+    fn default() -> Self {
+        Self {
+            secrets_created_total: BTreeMap::new(),
+            secrets_read_total: BTreeMap::new(),
+            secrets_deleted_total: BTreeMap::new(),
+            secrets_not_found_total: BTreeMap::new(),
+            limit_rejections_total: BTreeMap::new(),
+            request_duration_seconds: BTreeMap::new(),
+            payload_size_bytes: Histogram::new(&PAYLOAD_SIZE_BUCKETS),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Upper bounds of buckets, `+Inf` bucket is implicit
+    pub bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative
+    pub bucket_counts: Vec<u64>,
+    pub sum: f64,
+    pub count: u64,
+}
+
+impl Histogram {
+    pub fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            bucket_counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    pub fn observe(&mut self, value: f64) {
+        if let Some(index) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.bucket_counts[index] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Text formats negotiated by `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExpositionFormat {
+    /// Prometheus text format 0.0.4
+    Prometheus,
+    /// OpenMetrics 1.0, counter families are named without `_total` and body ends with `# EOF`
+    OpenMetrics,
+}
+
+impl ExpositionFormat {
+    pub fn from_accept(accept: &str) -> Self {
+        if accept.contains("application/openmetrics-text") {
+            ExpositionFormat::OpenMetrics
+        } else {
+            ExpositionFormat::Prometheus
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExpositionFormat::Prometheus => "text/plain; version=0.0.4",
+            ExpositionFormat::OpenMetrics => {
+                "application/openmetrics-text; version=1.0.0; charset=utf-8"
+            }
+        }
+    }
+}
+
 impl Metrics {
     pub fn to_prometheus_text(&self) -> String {
+        self.render(ExpositionFormat::Prometheus)
+    }
+
+    pub fn to_openmetrics_text(&self) -> String {
+        self.render(ExpositionFormat::OpenMetrics)
+    }
+
+    pub fn render(&self, format: ExpositionFormat) -> String {
         let mut body = String::new();
 
-        push_help_and_type(&mut body, "pw_up", "Application availability", "gauge");
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_up",
+            "Application availability",
+            "gauge",
+        );
         push_metric_line(&mut body, "pw_up", bool_to_u8(self.up));
 
-        push_help_and_type(&mut body, "pw_build_info", "Build information", "gauge");
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_build_info",
+            "Build information",
+            "gauge",
+        );
         let version_label = escape_label_value(&self.build.version);
         let _ = writeln!(body, "pw_build_info{{version=\"{}\"}} 1", version_label);
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_uptime_seconds",
             "Process uptime in seconds",
             "gauge",
@@ -50,6 +184,7 @@ impl Metrics {
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_redis_up",
             "Redis availability check result",
             "gauge",
@@ -58,6 +193,7 @@ impl Metrics {
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_redis_latency_seconds",
             "Redis availability check latency in seconds",
             "gauge",
@@ -70,6 +206,7 @@ impl Metrics {
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_config_message_max_length",
             "Configured message max length",
             "gauge",
@@ -82,6 +219,7 @@ impl Metrics {
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_config_file_max_size_bytes",
             "Configured file max size in bytes",
             "gauge",
@@ -94,6 +232,7 @@ impl Metrics {
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_config_file_upload_enabled",
             "Configured file upload enabled flag",
             "gauge",
@@ -106,6 +245,7 @@ impl Metrics {
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_ip_limits_enabled",
             "IP limits feature enabled",
             "gauge",
@@ -118,6 +258,7 @@ impl Metrics {
 
         push_help_and_type(
             &mut body,
+            format,
             "pw_body_limit_bytes",
             "Configured HTTP body limit in bytes",
             "gauge",
@@ -128,11 +269,137 @@ impl Metrics {
             self.config.body_limit_bytes,
         );
 
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_secret_lookup_failures_total",
+            "Secret lookups by unknown or expired id",
+            "counter",
+        );
+        push_metric_line(
+            &mut body,
+            "pw_secret_lookup_failures_total",
+            self.secrets.lookup_failures_total,
+        );
+
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_secrets_created_total",
+            "Stored secrets",
+            "counter",
+        );
+        push_labeled_counter(
+            &mut body,
+            "pw_secrets_created_total",
+            &self.traffic.secrets_created_total,
+        );
+
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_secrets_read_total",
+            "Secret downloads",
+            "counter",
+        );
+        push_labeled_counter(
+            &mut body,
+            "pw_secrets_read_total",
+            &self.traffic.secrets_read_total,
+        );
+
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_secrets_deleted_total",
+            "Secrets removed by revocation token",
+            "counter",
+        );
+        push_labeled_counter(
+            &mut body,
+            "pw_secrets_deleted_total",
+            &self.traffic.secrets_deleted_total,
+        );
+
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_secrets_not_found_total",
+            "Secret reads and removals by unknown or expired id",
+            "counter",
+        );
+        push_labeled_counter(
+            &mut body,
+            "pw_secrets_not_found_total",
+            &self.traffic.secrets_not_found_total,
+        );
+
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_limit_rejections_total",
+            "Requests rejected by size, TTL, rate or storage limits",
+            "counter",
+        );
+        push_labeled_counter(
+            &mut body,
+            "pw_limit_rejections_total",
+            &self.traffic.limit_rejections_total,
+        );
+
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_http_request_duration_seconds",
+            "HTTP request latency in seconds",
+            "histogram",
+        );
+        for (labels, histogram) in &self.traffic.request_duration_seconds {
+            push_histogram(
+                &mut body,
+                "pw_http_request_duration_seconds",
+                labels,
+                histogram,
+            );
+        }
+
+        push_help_and_type(
+            &mut body,
+            format,
+            "pw_secret_payload_size_bytes",
+            "Encrypted payload size of stored secrets in bytes",
+            "histogram",
+        );
+        push_histogram(
+            &mut body,
+            "pw_secret_payload_size_bytes",
+            &vec![],
+            &self.traffic.payload_size_bytes,
+        );
+
+        if format == ExpositionFormat::OpenMetrics {
+            body.push_str("# EOF\n");
+        }
+
         body
     }
 }
 
-fn push_help_and_type(buffer: &mut String, name: &str, help: &str, metric_type: &str) {
+fn push_help_and_type(
+    buffer: &mut String,
+    format: ExpositionFormat,
+    name: &str,
+    help: &str,
+    metric_type: &str,
+) {
+    // OpenMetrics counter family name has no suffix, samples keep `_total`
+    let name = match format {
+        ExpositionFormat::OpenMetrics if metric_type == "counter" => {
+            name.strip_suffix("_total").unwrap_or(name)
+        }
+        _ => name,
+    };
+
     let _ = writeln!(buffer, "# HELP {} {}", name, help);
     let _ = writeln!(buffer, "# TYPE {} {}", name, metric_type);
 }
@@ -141,6 +408,69 @@ fn push_metric_line<T: std::fmt::Display>(buffer: &mut String, name: &str, value
     let _ = writeln!(buffer, "{} {}", name, value);
 }
 
+fn push_labeled_counter(buffer: &mut String, name: &str, counters: &BTreeMap<Labels, u64>) {
+    for (labels, value) in counters {
+        let _ = writeln!(buffer, "{}{} {}", name, format_labels(labels), value);
+    }
+}
+
+fn push_histogram(buffer: &mut String, name: &str, labels: &Labels, histogram: &Histogram) {
+    let mut cumulative = 0;
+
+    for (bound, count) in histogram.bounds.iter().zip(&histogram.bucket_counts) {
+        cumulative += count;
+
+        let mut bucket_labels = labels.clone();
+        bucket_labels.push(("le", bound.to_string()));
+
+        let _ = writeln!(
+            buffer,
+            "{}_bucket{} {}",
+            name,
+            format_labels(&bucket_labels),
+            cumulative
+        );
+    }
+
+    let mut bucket_labels = labels.clone();
+    bucket_labels.push(("le", "+Inf".to_string()));
+    let _ = writeln!(
+        buffer,
+        "{}_bucket{} {}",
+        name,
+        format_labels(&bucket_labels),
+        histogram.count
+    );
+
+    let _ = writeln!(
+        buffer,
+        "{}_sum{} {}",
+        name,
+        format_labels(labels),
+        histogram.sum
+    );
+    let _ = writeln!(
+        buffer,
+        "{}_count{} {}",
+        name,
+        format_labels(labels),
+        histogram.count
+    );
+}
+
+fn format_labels(labels: &Labels) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect();
+
+    format!("{{{}}}", pairs.join(","))
+}
+
 fn bool_to_u8(value: bool) -> u8 {
     if value { 1 } else { 0 }
 }
@@ -176,8 +506,65 @@ mod tests {
     }
 
     #[test]
-    fn to_prometheus_text_renders_expected_metrics_and_order() {
-        let metrics = Metrics {
+    fn histogram_observations_are_counted_in_first_matching_bucket() {
+        let mut histogram = Histogram::new(&[1.0, 5.0]);
+
+        histogram.observe(0.5);
+        histogram.observe(1.0);
+        histogram.observe(3.0);
+        histogram.observe(10.0);
+
+        assert_eq!(histogram.bucket_counts, vec![2, 1]);
+        assert_eq!(histogram.count, 4);
+        assert_eq!(histogram.sum, 14.5);
+    }
+
+    #[test]
+    fn histogram_is_rendered_with_cumulative_buckets() {
+        let mut histogram = Histogram::new(&[0.1, 1.0]);
+        histogram.observe(0.0625);
+        histogram.observe(0.5);
+        histogram.observe(2.0);
+
+        let mut body = String::new();
+        push_histogram(
+            &mut body,
+            "pw_test_seconds",
+            &vec![("route", "/api/secret".to_string())],
+            &histogram,
+        );
+
+        let expected = concat!(
+            "pw_test_seconds_bucket{route=\"/api/secret\",le=\"0.1\"} 1\n",
+            "pw_test_seconds_bucket{route=\"/api/secret\",le=\"1\"} 2\n",
+            "pw_test_seconds_bucket{route=\"/api/secret\",le=\"+Inf\"} 3\n",
+            "pw_test_seconds_sum{route=\"/api/secret\"} 2.5625\n",
+            "pw_test_seconds_count{route=\"/api/secret\"} 3\n",
+        );
+
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn labeled_counters_are_rendered_in_label_order() {
+        let mut counters = BTreeMap::new();
+        counters.insert(vec![("reason", "too_many_requests".to_string())], 2);
+        counters.insert(vec![("reason", "payload_too_large".to_string())], 1);
+
+        let mut body = String::new();
+        push_labeled_counter(&mut body, "pw_limit_rejections_total", &counters);
+
+        assert_eq!(
+            body,
+            concat!(
+                "pw_limit_rejections_total{reason=\"payload_too_large\"} 1\n",
+                "pw_limit_rejections_total{reason=\"too_many_requests\"} 2\n",
+            )
+        );
+    }
+
+    fn get_sample_metrics() -> Metrics {
+        Metrics {
             up: true,
             build: BuildInfo {
                 version: "1.2.3\\\"rc\n1".to_string(),
@@ -194,7 +581,16 @@ mod tests {
                 ip_limits_enabled: false,
                 body_limit_bytes: 1024,
             },
-        };
+            secrets: SecretMetrics {
+                lookup_failures_total: 7,
+            },
+            traffic: TrafficMetrics::default(),
+        }
+    }
+
+    #[test]
+    fn to_prometheus_text_renders_expected_metrics_and_order() {
+        let metrics = get_sample_metrics();
 
         let rendered = metrics.to_prometheus_text();
 
@@ -229,8 +625,77 @@ mod tests {
             "# HELP pw_body_limit_bytes Configured HTTP body limit in bytes\n",
             "# TYPE pw_body_limit_bytes gauge\n",
             "pw_body_limit_bytes 1024\n",
+            "# HELP pw_secret_lookup_failures_total Secret lookups by unknown or expired id\n",
+            "# TYPE pw_secret_lookup_failures_total counter\n",
+            "pw_secret_lookup_failures_total 7\n",
+            "# HELP pw_secrets_created_total Stored secrets\n",
+            "# TYPE pw_secrets_created_total counter\n",
+            "# HELP pw_secrets_read_total Secret downloads\n",
+            "# TYPE pw_secrets_read_total counter\n",
+            "# HELP pw_secrets_deleted_total Secrets removed by revocation token\n",
+            "# TYPE pw_secrets_deleted_total counter\n",
+            "# HELP pw_secrets_not_found_total Secret reads and removals by unknown or expired id\n",
+            "# TYPE pw_secrets_not_found_total counter\n",
+            "# HELP pw_limit_rejections_total Requests rejected by size, TTL, rate or storage limits\n",
+            "# TYPE pw_limit_rejections_total counter\n",
+            "# HELP pw_http_request_duration_seconds HTTP request latency in seconds\n",
+            "# TYPE pw_http_request_duration_seconds histogram\n",
+            "# HELP pw_secret_payload_size_bytes Encrypted payload size of stored secrets in bytes\n",
+            "# TYPE pw_secret_payload_size_bytes histogram\n",
+            "pw_secret_payload_size_bytes_bucket{le=\"1024\"} 0\n",
+            "pw_secret_payload_size_bytes_bucket{le=\"10240\"} 0\n",
+            "pw_secret_payload_size_bytes_bucket{le=\"102400\"} 0\n",
+            "pw_secret_payload_size_bytes_bucket{le=\"1048576\"} 0\n",
+            "pw_secret_payload_size_bytes_bucket{le=\"10485760\"} 0\n",
+            "pw_secret_payload_size_bytes_bucket{le=\"104857600\"} 0\n",
+            "pw_secret_payload_size_bytes_bucket{le=\"+Inf\"} 0\n",
+            "pw_secret_payload_size_bytes_sum 0\n",
+            "pw_secret_payload_size_bytes_count 0\n",
         );
 
         assert_eq!(rendered, expected);
     }
+
+    #[test]
+    fn to_openmetrics_text_uses_counter_family_names_and_eof() {
+        let mut metrics = get_sample_metrics();
+        metrics
+            .traffic
+            .limit_rejections_total
+            .insert(vec![("reason", "invalid_ttl".to_string())], 3);
+
+        let rendered = metrics.to_openmetrics_text();
+
+        assert!(rendered.contains(concat!(
+            "# HELP pw_secret_lookup_failures Secret lookups by unknown or expired id\n",
+            "# TYPE pw_secret_lookup_failures counter\n",
+            "pw_secret_lookup_failures_total 7\n",
+        )));
+        assert!(rendered.contains(concat!(
+            "# TYPE pw_limit_rejections counter\n",
+            "pw_limit_rejections_total{reason=\"invalid_ttl\"} 3\n",
+        )));
+        assert!(rendered.contains("# TYPE pw_up gauge\npw_up 1\n"));
+        assert!(rendered.contains("# TYPE pw_http_request_duration_seconds histogram\n"));
+        assert!(rendered.ends_with("pw_secret_payload_size_bytes_count 0\n# EOF\n"));
+        assert!(!metrics.to_prometheus_text().contains("# EOF"));
+    }
+
+    #[test]
+    fn exposition_format_is_negotiated_by_accept_header() {
+        assert_eq!(
+            ExpositionFormat::from_accept(
+                "application/openmetrics-text;version=1.0.0,text/plain;version=0.0.4;q=0.5"
+            ),
+            ExpositionFormat::OpenMetrics
+        );
+        assert_eq!(
+            ExpositionFormat::from_accept("text/plain;version=0.0.4"),
+            ExpositionFormat::Prometheus
+        );
+        assert_eq!(
+            ExpositionFormat::from_accept("*/*"),
+            ExpositionFormat::Prometheus
+        );
+    }
 }
@@ -1,44 +1,181 @@
 use crate::VERSION;
 use crate::config::model::AppConfig;
-use crate::metrics::model::{BuildInfo, ConfigMetrics, Metrics, RedisMetrics};
+use crate::limits::LimitsService;
+use crate::metrics::model::{
+    BuildInfo, ConfigMetrics, Histogram, Metrics, REQUEST_DURATION_BUCKETS, RedisMetrics,
+    SecretMetrics, TrafficMetrics,
+};
 use crate::metrics::ports::MetricsService;
-use redis::Commands;
-use std::time::Instant;
+use crate::metrics::traffic::{SecretAction, SecretEvent};
+use crate::secret::redis_pool::RedisConnectionPool;
+use log::{debug, warn};
+use redis::AsyncCommands;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 const REDIS_CHECK_TTL_SECONDS: u64 = 2;
 
-#[derive(Debug, Clone)]
+pub const REDIS_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+const REDIS_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Older probe results are treated as unknown, e.g. when the probe task is stuck
+const REDIS_PROBE_MAX_AGE: Duration = Duration::from_secs(15);
+
+/// Result of the last Redis availability check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RedisProbe {
+    /// `None` when Redis is unavailable or didn't respond within timeout
+    pub latency_seconds: Option<f64>,
+    pub checked_at: Instant,
+}
+
+impl RedisProbe {
+    pub fn is_up(&self) -> bool {
+        self.latency_seconds.is_some() && self.checked_at.elapsed() <= REDIS_PROBE_MAX_AGE
+    }
+}
+
+#[derive(Clone)]
 pub struct MetricsServer {
     config: AppConfig,
-    body_limit: usize,
+    limits_service: LimitsService,
     start_time: Instant,
+    redis_pool: Option<RedisConnectionPool>,
+    redis_probe: Arc<RwLock<Option<RedisProbe>>>,
+    secret_lookup_failures: Arc<AtomicU64>,
+    traffic: Arc<Mutex<TrafficMetrics>>,
 }
 
 impl MetricsServer {
-    pub fn new(config: AppConfig, body_limit: usize) -> Self {
+    /// Redis availability is reported as down when `redis_pool` is not provided.
+    /// IP limits state and body limit are read from `limits_service`, they change on config reload.
+    pub fn new(
+        config: AppConfig,
+        limits_service: LimitsService,
+        redis_pool: Option<RedisConnectionPool>,
+    ) -> Self {
         Self {
             config,
-            body_limit,
+            limits_service,
             start_time: Instant::now(),
+            redis_pool,
+            redis_probe: Arc::new(RwLock::new(None)),
+            secret_lookup_failures: Arc::new(AtomicU64::new(0)),
+            traffic: Arc::new(Mutex::new(TrafficMetrics::default())),
         }
     }
 
-    pub async fn get_metrics(&self) -> Metrics {
-        let uptime_seconds = self.start_time.elapsed().as_secs_f64();
+    pub fn get_uptime(&self) -> Duration {
+        self.start_time.elapsed()
+    }
 
-        let (redis_up, redis_latency_seconds) =
-            match check_redis_availability(&self.config.redis_url) {
-                Some(latency) => (true, latency),
-                None => (false, f64::NAN),
+    /// Checks Redis periodically, scrapes and readiness checks use the cached result.
+    ///
+    /// Returns `None` when `redis_pool` is not provided.
+    pub fn spawn_redis_probe(&self, interval: Duration) -> Option<JoinHandle<()>> {
+        self.redis_pool.as_ref()?;
+
+        let metrics_server = self.clone();
+
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+                metrics_server.probe_redis().await;
+            }
+        }))
+    }
+
+    pub async fn probe_redis(&self) {
+        let Some(pool) = &self.redis_pool else {
+            return;
+        };
+
+        let latency_seconds =
+            match tokio::time::timeout(REDIS_PROBE_TIMEOUT, check_redis_availability(pool)).await {
+                Ok(latency) => latency,
+                Err(_) => {
+                    warn!(
+                        "redis availability check timed out after {} seconds",
+                        REDIS_PROBE_TIMEOUT.as_secs()
+                    );
+                    None
+                }
             };
 
-        let ip_limits_enabled = self
-            .config
-            .ip_limits
-            .as_ref()
-            .map(|limits| limits.enabled)
-            .unwrap_or(false);
+        debug!("redis probe latency: {:?}", latency_seconds);
+
+        *self.redis_probe.write().unwrap() = Some(RedisProbe {
+            latency_seconds,
+            checked_at: Instant::now(),
+        });
+    }
+
+    /// Returns `None` until the first probe is completed
+    pub fn get_redis_probe(&self) -> Option<RedisProbe> {
+        *self.redis_probe.read().unwrap()
+    }
+
+    pub fn record_secret_lookup_failure(&self) {
+        self.secret_lookup_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_secret_event(&self, event: &SecretEvent) {
+        let mut traffic = self.traffic.lock().unwrap();
+
+        let counters = match event.action {
+            SecretAction::Create => {
+                traffic
+                    .payload_size_bytes
+                    .observe(event.payload_size as f64);
+                &mut traffic.secrets_created_total
+            }
+            SecretAction::Read => &mut traffic.secrets_read_total,
+            SecretAction::Delete => &mut traffic.secrets_deleted_total,
+        };
+
+        *counters.entry(event.get_labels()).or_default() += 1;
+    }
+
+    pub fn record_secret_not_found(&self, action: SecretAction) {
+        let mut traffic = self.traffic.lock().unwrap();
+        *traffic
+            .secrets_not_found_total
+            .entry(vec![("action", action.to_string())])
+            .or_default() += 1;
+    }
+
+    pub fn record_limit_rejection(&self, reason: &str) {
+        let mut traffic = self.traffic.lock().unwrap();
+        *traffic
+            .limit_rejections_total
+            .entry(vec![("reason", reason.to_string())])
+            .or_default() += 1;
+    }
+
+    pub fn observe_request_duration(&self, route: &str, method: &str, duration: Duration) {
+        let mut traffic = self.traffic.lock().unwrap();
+        traffic
+            .request_duration_seconds
+            .entry(vec![
+                ("route", route.to_string()),
+                ("method", method.to_string()),
+            ])
+            .or_insert_with(|| Histogram::new(&REQUEST_DURATION_BUCKETS))
+            .observe(duration.as_secs_f64());
+    }
+
+    pub async fn get_metrics(&self) -> Metrics {
+        let uptime_seconds = self.start_time.elapsed().as_secs_f64();
+
+        let (redis_up, redis_latency_seconds) = match self.get_redis_probe() {
+            Some(probe) if probe.is_up() => (true, probe.latency_seconds.unwrap_or(f64::NAN)),
+            _ => (false, f64::NAN),
+        };
 
         Metrics {
             up: true,
@@ -54,9 +191,16 @@ impl MetricsServer {
                 message_max_length: self.config.message_max_length,
                 file_max_size_bytes: self.config.file_max_size,
                 file_upload_enabled: self.config.file_upload_enabled,
-                ip_limits_enabled,
-                body_limit_bytes: self.body_limit,
+                ip_limits_enabled: self.limits_service.is_ip_limits_enabled(),
+                body_limit_bytes: self
+                    .limits_service
+                    .body_limit_as_usize()
+                    .unwrap_or(usize::MAX),
+            },
+            secrets: SecretMetrics {
+                lookup_failures_total: self.secret_lookup_failures.load(Ordering::Relaxed),
             },
+            traffic: self.traffic.lock().unwrap().clone(),
         }
     }
 }
@@ -67,9 +211,8 @@ impl MetricsService for MetricsServer {
     }
 }
 
-fn check_redis_availability(redis_url: &str) -> Option<f64> {
-    let client = redis::Client::open(redis_url).ok()?;
-    let mut connection = client.get_connection().ok()?;
+async fn check_redis_availability(pool: &RedisConnectionPool) -> Option<f64> {
+    let mut connection = pool.get().await.ok()?;
 
     let key = format!("pw:metrics:{}", Uuid::new_v4());
     let value = "1";
@@ -77,8 +220,9 @@ fn check_redis_availability(redis_url: &str) -> Option<f64> {
 
     let _: () = connection
         .set_ex(&key, value, REDIS_CHECK_TTL_SECONDS)
+        .await
         .ok()?;
-    let fetched: Option<String> = connection.get(&key).ok()?;
+    let fetched: Option<String> = connection.get(&key).await.ok()?;
 
     if fetched.as_deref() == Some(value) {
         Some(start.elapsed().as_secs_f64())
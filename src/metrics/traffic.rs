@@ -0,0 +1,150 @@
+use crate::metrics::model::Labels;
+use crate::secret::model::{Secret, SecretContentType, SecretDownloadPolicy, SecretTTL};
+use std::fmt::{Display, Formatter};
+
+/// TTL label buckets, upper bound in seconds. Keeps label cardinality fixed for custom TTLs
+const TTL_LABELS: [(u64, &str); 5] = [
+    (60 * 5, "5m"),
+    (60 * 60, "1h"),
+    (60 * 60 * 2, "2h"),
+    (60 * 60 * 24, "1d"),
+    (60 * 60 * 24 * 7, "1w"),
+];
+
+/// TTL label for reads and deletes of secrets with absolute expiration, their initial TTL isn't stored
+const EXPIRES_AT_TTL_LABEL: &str = "expires_at";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretAction {
+    Create,
+    Read,
+    Delete,
+}
+
+impl Display for SecretAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value = match self {
+            SecretAction::Create => "create",
+            SecretAction::Read => "read",
+            SecretAction::Delete => "delete",
+        };
+        write!(f, "{value}")
+    }
+}
+
+/// Attached by secret routes to successful responses, recorded by the metrics middleware
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretEvent {
+    pub action: SecretAction,
+    pub content_type: SecretContentType,
+    pub ttl_label: &'static str,
+    pub download_policy: SecretDownloadPolicy,
+    pub payload_size: usize,
+}
+
+impl SecretEvent {
+    pub fn new(action: SecretAction, secret: &Secret) -> SecretEvent {
+        SecretEvent {
+            action,
+            content_type: secret.content_type.clone(),
+            ttl_label: get_secret_ttl_label(action, secret.ttl.as_ref()),
+            download_policy: secret.download_policy.clone(),
+            payload_size: secret.payload_size(),
+        }
+    }
+
+    pub fn get_labels(&self) -> Labels {
+        let content_type = match self.content_type {
+            SecretContentType::Text => "text",
+            SecretContentType::File => "file",
+        };
+
+        let download_policy = match self.download_policy {
+            SecretDownloadPolicy::OneTime => "one_time",
+            SecretDownloadPolicy::Unlimited => "unlimited",
+            SecretDownloadPolicy::MaxViews(_) => "max_views",
+        };
+
+        vec![
+            ("content_type", content_type.to_string()),
+            ("ttl", self.ttl_label.to_string()),
+            ("download_policy", download_policy.to_string()),
+        ]
+    }
+}
+
+/// Absolute expiration gives only the time left, it's close to the initial TTL on create only
+fn get_secret_ttl_label(action: SecretAction, ttl: Option<&SecretTTL>) -> &'static str {
+    match ttl {
+        Some(SecretTTL::ExpiresAt(_)) if action != SecretAction::Create => EXPIRES_AT_TTL_LABEL,
+        Some(ttl) => get_ttl_label(ttl.as_seconds()),
+        None => get_ttl_label(0),
+    }
+}
+
+fn get_ttl_label(ttl_seconds: u64) -> &'static str {
+    TTL_LABELS
+        .iter()
+        .find(|(max_seconds, _)| ttl_seconds <= *max_seconds)
+        .map(|(_, label)| *label)
+        .unwrap_or("longer")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::model::SecretTTL;
+    use crate::tests::secret::get_sample_secret;
+
+    #[test]
+    fn ttl_should_be_labeled_by_nearest_bucket() {
+        assert_eq!(get_ttl_label(60), "5m");
+        assert_eq!(get_ttl_label(3600), "1h");
+        assert_eq!(get_ttl_label(3601), "2h");
+        assert_eq!(get_ttl_label(86_400), "1d");
+        assert_eq!(get_ttl_label(604_800), "1w");
+        assert_eq!(get_ttl_label(604_801), "longer");
+    }
+
+    #[test]
+    fn expires_at_ttl_should_be_bucketed_on_create_only() {
+        let mut secret = get_sample_secret();
+        secret.ttl = Some(SecretTTL::ExpiresAt(
+            chrono::Utc::now().timestamp() + 86_000,
+        ));
+
+        assert_eq!(
+            SecretEvent::new(SecretAction::Create, &secret).ttl_label,
+            "1d"
+        );
+        assert_eq!(
+            SecretEvent::new(SecretAction::Read, &secret).ttl_label,
+            "expires_at"
+        );
+
+        secret.ttl = Some(SecretTTL::OneDay);
+        assert_eq!(
+            SecretEvent::new(SecretAction::Read, &secret).ttl_label,
+            "1d"
+        );
+    }
+
+    #[test]
+    fn secret_event_labels() {
+        let mut secret = get_sample_secret();
+        secret.content_type = SecretContentType::File;
+        secret.ttl = Some(SecretTTL::OneDay);
+        secret.download_policy = SecretDownloadPolicy::MaxViews(3);
+
+        let event = SecretEvent::new(SecretAction::Create, &secret);
+
+        assert_eq!(
+            event.get_labels(),
+            vec![
+                ("content_type", "file".to_string()),
+                ("ttl", "1d".to_string()),
+                ("download_policy", "max_views".to_string()),
+            ]
+        );
+    }
+}
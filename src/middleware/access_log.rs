@@ -0,0 +1,73 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use log::Level;
+use std::time::Instant;
+
+use super::client_ip::ClientIp;
+use super::request_id::RequestId;
+
+/// Probes are logged on debug level to keep access log readable
+const PROBE_ROUTES: [&str; 2] = ["/healthz", "/readyz"];
+
+/// Logs one line per request with request id, client ip, route, status and latency.
+///
+/// Fields are attached as structured values, `log-format: json` puts them under `attributes`.
+/// Route is the matched pattern, so secret ids don't get into logs.
+/// Must be applied inside [`RequestIdPropagator`](super::request_id::RequestIdPropagator)
+/// and [`ClientIpExtractor`](super::ClientIpExtractor).
+pub struct AccessLogger;
+
+impl AccessLogger {
+    pub async fn middleware(request: Request, next: Next) -> Response {
+        let start = Instant::now();
+
+        let method = request.method().to_string();
+        let route = match request.extensions().get::<MatchedPath>() {
+            Some(path) => path.as_str().to_string(),
+            None => request.uri().path().to_string(),
+        };
+        let request_id = request
+            .extensions()
+            .get::<RequestId>()
+            .map(|request_id| request_id.0.clone())
+            .unwrap_or_default();
+        let client_ip = request
+            .extensions()
+            .get::<ClientIp>()
+            .map(|client_ip| client_ip.0.to_string())
+            .unwrap_or_default();
+
+        let response = next.run(request).await;
+
+        let status = response.status().as_u16();
+        let latency_ms = start.elapsed().as_millis() as u64;
+
+        let level = if PROBE_ROUTES.contains(&route.as_str()) {
+            Level::Debug
+        } else {
+            Level::Info
+        };
+
+        log::log!(
+            level,
+            request_id = request_id.as_str(),
+            client_ip = client_ip.as_str(),
+            method = method.as_str(),
+            route = route.as_str(),
+            status = status,
+            latency_ms = latency_ms;
+            "{} {} {} {}ms request-id={} client-ip={}",
+            method,
+            route,
+            status,
+            latency_ms,
+            request_id,
+            client_ip
+        );
+
+        response
+    }
+}
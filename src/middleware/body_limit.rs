@@ -0,0 +1,183 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::Limited;
+use log::info;
+
+use crate::limits::LimitsService;
+use crate::routes::binary::is_octet_stream;
+use crate::routes::error::ApiError;
+
+/// Applies body limit calculated from current limits, it changes when `ip-limits` are reloaded.
+///
+/// Raw `application/octet-stream` bodies get the tighter limit, other bodies are
+/// expected to carry base64 payload in JSON.
+///
+/// Routes must use `DefaultBodyLimit::disable()`, otherwise axum default limit is applied as well.
+pub struct BodyLimit;
+
+impl BodyLimit {
+    pub async fn middleware(
+        State(limits_service): State<LimitsService>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let body_limit = if is_octet_stream(request.headers()) {
+            limits_service.body_limit_as_usize()
+        } else {
+            limits_service.json_body_limit_as_usize()
+        }
+        .unwrap_or(usize::MAX);
+
+        let content_length = request
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok());
+
+        if content_length.is_some_and(|length| length > body_limit as u64) {
+            info!(
+                "request body of {} bytes exceeds limit of {} bytes",
+                content_length.unwrap_or_default(),
+                body_limit
+            );
+            return ApiError::PayloadTooLarge.into_response();
+        }
+
+        // Bodies without content length are cut off while being read
+        let request = request.map(|body| Body::new(Limited::new(body, body_limit)));
+
+        next.run(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::{AppConfig, IpLimitEntry, IpLimitsConfig};
+    use crate::routes::binary::OCTET_STREAM;
+    use crate::tests::config::get_test_app_config;
+    use axum::Router;
+    use axum::body::Bytes;
+    use axum::extract::DefaultBodyLimit;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use tower::util::ServiceExt;
+
+    fn create_test_config() -> AppConfig {
+        AppConfig {
+            message_max_length: 100,
+            file_upload_enabled: false,
+            file_max_size: 100,
+            encrypted_message_max_length: Some(1000),
+            ..get_test_app_config()
+        }
+    }
+
+    fn create_test_router(limits_service: LimitsService) -> Router {
+        Router::new().route(
+            "/api/secret",
+            post(|body: Bytes| async move { body.len().to_string() })
+                .layer(DefaultBodyLimit::disable())
+                .layer(axum::middleware::from_fn_with_state(
+                    limits_service,
+                    BodyLimit::middleware,
+                )),
+        )
+    }
+
+    async fn send(app: &Router, size: usize, with_content_length: bool) -> StatusCode {
+        send_with_content_type(app, size, with_content_length, "application/json").await
+    }
+
+    async fn send_with_content_type(
+        app: &Router,
+        size: usize,
+        with_content_length: bool,
+        content_type: &str,
+    ) -> StatusCode {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/api/secret")
+            .header(header::CONTENT_TYPE, content_type);
+
+        if with_content_length {
+            request = request.header(header::CONTENT_LENGTH, size);
+        }
+
+        let request = request.body(Body::from(vec![b'a'; size])).unwrap();
+
+        app.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn body_above_limit_should_be_rejected() {
+        let limits_service = LimitsService::new(&create_test_config());
+        let body_limit = limits_service.json_body_limit_as_usize().unwrap();
+        let app = create_test_router(limits_service);
+
+        assert_eq!(send(&app, body_limit, true).await, StatusCode::OK);
+        assert_eq!(
+            send(&app, body_limit + 1, true).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(send(&app, body_limit, false).await, StatusCode::OK);
+        assert_eq!(
+            send(&app, body_limit + 1, false).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+    }
+
+    #[tokio::test]
+    async fn binary_body_should_get_tighter_limit() {
+        let limits_service = LimitsService::new(&create_test_config());
+        let body_limit = limits_service.body_limit_as_usize().unwrap();
+        let app = create_test_router(limits_service.clone());
+
+        assert!(body_limit < limits_service.json_body_limit_as_usize().unwrap());
+        for with_content_length in [true, false] {
+            assert_eq!(
+                send_with_content_type(&app, body_limit, with_content_length, OCTET_STREAM).await,
+                StatusCode::OK
+            );
+            assert_eq!(
+                send_with_content_type(&app, body_limit + 1, with_content_length, OCTET_STREAM)
+                    .await,
+                StatusCode::PAYLOAD_TOO_LARGE
+            );
+        }
+        assert_eq!(send(&app, body_limit + 1, true).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn reloaded_limit_should_be_applied() {
+        let limits_service = LimitsService::new(&create_test_config());
+        let body_limit = limits_service.json_body_limit_as_usize().unwrap();
+        let app = create_test_router(limits_service.clone());
+
+        assert_eq!(
+            send(&app, body_limit * 2, true).await,
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+
+        let new_body_limit = limits_service
+            .reload_ip_limits(Some(IpLimitsConfig {
+                enabled: true,
+                whitelist: vec![IpLimitEntry {
+                    ip: "10.0.0.1".to_string(),
+                    message_max_length: None,
+                    file_max_size: Some(100_000),
+                    rate_limits: None,
+                }],
+                trusted_proxies: vec![],
+            }))
+            .unwrap();
+
+        assert!(new_body_limit > body_limit * 2);
+        assert_eq!(send(&app, body_limit * 2, true).await, StatusCode::OK);
+    }
+}
@@ -9,23 +9,26 @@ use log::debug;
 use std::net::IpAddr;
 
 use crate::config::model::IpLimitsConfig;
+use crate::limits::LimitsService;
 
 pub const CLIENT_IP_EXTENSION_KEY: &str = "client_ip";
 
 #[derive(Debug, Clone)]
 pub struct ClientIp(pub IpAddr);
 
+/// Trusted proxies are taken from `LimitsService` extension, so they follow config reloads.
+/// Proxy headers are trusted when the extension is missing.
 pub struct ClientIpExtractor;
 
 impl ClientIpExtractor {
     pub async fn middleware(
         ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
-        ip_limits_config: Option<Extension<Option<IpLimitsConfig>>>,
+        limits_service: Option<Extension<LimitsService>>,
         mut request: Request,
         next: Next,
     ) -> Response {
-        let config = ip_limits_config.as_ref().and_then(|ext| ext.0.as_ref());
-        let client_ip = Self::extract_client_ip(request.headers(), addr.ip(), config);
+        let config = limits_service.and_then(|ext| ext.0.get_ip_limits_config());
+        let client_ip = Self::extract_client_ip(request.headers(), addr.ip(), config.as_ref());
         debug!(
             "extracted client IP: {} (connection IP: {})",
             client_ip,
@@ -469,7 +472,7 @@ mod tests {
         }
 
         // Valid IPv6 addresses
-        let valid_ipv6 = vec![
+        let valid_ipv6 = [
             "::1",
             "2001:db8::1",
             "2001:db8:85a3::8a2e:370:7334",
@@ -789,6 +792,7 @@ mod tests {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec!["10.0.0.1".to_string()], // Only trust this proxy
         };
@@ -0,0 +1,231 @@
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::AppState;
+use crate::metrics::traffic::{SecretAction, SecretEvent};
+use crate::routes::error::ApiErrorCode;
+
+/// Route label for requests served by the static fallback
+const FALLBACK_ROUTE: &str = "fallback";
+
+/// Records request latency and secret traffic counters.
+///
+/// Secret routes attach [`SecretEvent`] and [`ApiErrorCode`] extensions to responses,
+/// the middleware must be applied outside rate limiter to see its rejections.
+pub struct MetricsCollector;
+
+impl MetricsCollector {
+    pub async fn middleware(
+        State(state): State<Arc<AppState>>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let start = Instant::now();
+
+        let method = request.method().clone();
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| FALLBACK_ROUTE.to_string());
+
+        let response = next.run(request).await;
+
+        let metrics_server = &state.metrics_server;
+
+        metrics_server.observe_request_duration(&route, method.as_str(), start.elapsed());
+
+        if let Some(event) = response.extensions().get::<SecretEvent>() {
+            metrics_server.record_secret_event(event);
+        }
+
+        let code = response.extensions().get::<ApiErrorCode>().copied();
+
+        if let Some(ApiErrorCode("secret_not_found" | "secret_expired")) = code {
+            match method {
+                Method::GET => metrics_server.record_secret_not_found(SecretAction::Read),
+                Method::DELETE => metrics_server.record_secret_not_found(SecretAction::Delete),
+                _ => {}
+            }
+        }
+
+        if let Some(reason) = Self::get_rejection_reason(response.status(), code) {
+            metrics_server.record_limit_rejection(reason);
+        }
+
+        response
+    }
+
+    /// Body limit rejections come from axum without error code
+    fn get_rejection_reason(
+        status: StatusCode,
+        code: Option<ApiErrorCode>,
+    ) -> Option<&'static str> {
+        if status == StatusCode::PAYLOAD_TOO_LARGE {
+            return Some("payload_too_large");
+        }
+
+        match code?.0 {
+            reason @ ("too_many_requests" | "invalid_ttl" | "storage_full") => Some(reason),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditLog;
+    use crate::config::reload::ConfigReloadStatus;
+    use crate::limits::LimitsService;
+    use crate::metrics::service::MetricsServer;
+    use crate::middleware::client_ip::ClientIp;
+    use crate::routes::secret::{get_secret_route, store_secret_route};
+    use crate::secret::storage::MockSecretStorage;
+    use crate::shutdown::ShutdownSignal;
+    use crate::tests::config::get_test_app_config;
+    use crate::tests::secret::get_sample_secret;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::extract::DefaultBodyLimit;
+    use axum::routing::{get, post};
+    use std::net::IpAddr;
+    use tower::util::ServiceExt;
+
+    fn create_test_app_state() -> Arc<AppState> {
+        let config = get_test_app_config();
+
+        let limits_service = LimitsService::new(&config);
+        let metrics_server = MetricsServer::new(config.clone(), limits_service.clone(), None);
+
+        Arc::new(AppState {
+            config,
+            limits_service,
+            secret_storage: Box::new(MockSecretStorage::new()),
+            metrics_server,
+            shutdown: ShutdownSignal::new(),
+            config_reload: ConfigReloadStatus::default(),
+            audit_log: AuditLog::disabled(),
+        })
+    }
+
+    fn create_test_router(state: Arc<AppState>, body_limit: usize) -> Router {
+        Router::new()
+            .route(
+                "/api/secret",
+                post(store_secret_route).layer(DefaultBodyLimit::max(body_limit)),
+            )
+            .route("/api/secret/{id}", get(get_secret_route))
+            .layer(axum::middleware::from_fn_with_state(
+                state.clone(),
+                MetricsCollector::middleware,
+            ))
+            .with_state(state)
+    }
+
+    fn create_request(method: Method, uri: &str, body: Body) -> Request {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body)
+            .unwrap();
+
+        request
+            .extensions_mut()
+            .insert(ClientIp("192.168.1.100".parse::<IpAddr>().unwrap()));
+        request
+    }
+
+    #[tokio::test]
+    async fn secret_traffic_should_be_recorded() {
+        let state = create_test_app_state();
+        let app = create_test_router(state.clone(), 1_000_000);
+
+        let secret = get_sample_secret();
+        let request = create_request(
+            Method::POST,
+            "/api/secret",
+            Body::from(serde_json::to_string(&secret).unwrap()),
+        );
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let request = create_request(Method::GET, "/api/secret/unknown", Body::empty());
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let traffic = state.metrics_server.get_metrics().await.traffic;
+
+        let created_labels = vec![
+            ("content_type", "text".to_string()),
+            ("ttl", "1h".to_string()),
+            ("download_policy", "unlimited".to_string()),
+        ];
+        assert_eq!(traffic.secrets_created_total.get(&created_labels), Some(&1));
+        assert_eq!(traffic.payload_size_bytes.count, 1);
+        assert_eq!(
+            traffic
+                .secrets_not_found_total
+                .get(&vec![("action", "read".to_string())]),
+            Some(&1)
+        );
+
+        let route_labels = vec![
+            ("route", "/api/secret/{id}".to_string()),
+            ("method", "GET".to_string()),
+        ];
+        assert_eq!(
+            traffic
+                .request_duration_seconds
+                .get(&route_labels)
+                .unwrap()
+                .count,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn body_limit_rejection_should_be_recorded() {
+        let state = create_test_app_state();
+        let app = create_test_router(state.clone(), 10);
+
+        let request = create_request(Method::POST, "/api/secret", Body::from("A".repeat(100)));
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let traffic = state.metrics_server.get_metrics().await.traffic;
+        assert_eq!(
+            traffic
+                .limit_rejections_total
+                .get(&vec![("reason", "payload_too_large".to_string())]),
+            Some(&1)
+        );
+    }
+
+    #[test]
+    fn test_get_rejection_reason() {
+        assert_eq!(
+            MetricsCollector::get_rejection_reason(StatusCode::PAYLOAD_TOO_LARGE, None),
+            Some("payload_too_large")
+        );
+        assert_eq!(
+            MetricsCollector::get_rejection_reason(
+                StatusCode::TOO_MANY_REQUESTS,
+                Some(ApiErrorCode("too_many_requests"))
+            ),
+            Some("too_many_requests")
+        );
+        assert_eq!(
+            MetricsCollector::get_rejection_reason(
+                StatusCode::NOT_FOUND,
+                Some(ApiErrorCode("secret_not_found"))
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use crate::config::model::MetricsAccessConfig;
+use crate::limits::service::matches_ip_rule;
+
+const BASIC_AUTH_REALM: &str = "Basic realm=\"pw-metrics\"";
+
+/// Restricts metrics endpoint by connection address and basic auth credentials.
+///
+/// Forwarded headers are ignored, allowed IPs are matched against the peer address.
+pub struct MetricsAccessGuard {
+    config: MetricsAccessConfig,
+}
+
+impl MetricsAccessGuard {
+    pub fn new(config: MetricsAccessConfig) -> MetricsAccessGuard {
+        MetricsAccessGuard { config }
+    }
+
+    pub async fn middleware(
+        State(guard): State<Arc<MetricsAccessGuard>>,
+        ConnectInfo(addr): ConnectInfo<SocketAddr>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        if !guard.is_ip_allowed(&addr.ip()) {
+            info!("metrics access denied for client {}", addr.ip());
+            return StatusCode::FORBIDDEN.into_response();
+        }
+
+        if !guard.is_authorized(request.headers()) {
+            warn!("metrics basic auth failed for client {}", addr.ip());
+            return (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, BASIC_AUTH_REALM)],
+            )
+                .into_response();
+        }
+
+        next.run(request).await
+    }
+
+    fn is_ip_allowed(&self, client_ip: &IpAddr) -> bool {
+        self.config.allowed_ips.is_empty()
+            || self
+                .config
+                .allowed_ips
+                .iter()
+                .any(|rule| matches_ip_rule(client_ip, rule))
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(basic_auth) = &self.config.basic_auth else {
+            return true;
+        };
+
+        let Some(credentials) = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Basic "))
+            .and_then(|encoded| STANDARD.decode(encoded.trim()).ok())
+        else {
+            return false;
+        };
+
+        let expected = format!("{}:{}", basic_auth.username, basic_auth.password);
+
+        // Digests are compared to avoid leaking credentials length and prefix by timing
+        Sha256::digest(&credentials) == Sha256::digest(expected.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::BasicAuthConfig;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::routing::get;
+    use tower::util::ServiceExt;
+
+    fn create_test_router(config: MetricsAccessConfig) -> Router {
+        let guard = Arc::new(MetricsAccessGuard::new(config));
+
+        Router::new()
+            .route("/api/metrics", get(|| async { "metrics" }))
+            .layer(axum::middleware::from_fn_with_state(
+                guard,
+                MetricsAccessGuard::middleware,
+            ))
+    }
+
+    fn create_request(ip: &str, authorization: Option<&str>) -> Request {
+        let mut builder = Request::builder().uri("/api/metrics");
+
+        if let Some(authorization) = authorization {
+            builder = builder.header(header::AUTHORIZATION, authorization);
+        }
+
+        let mut request = builder.body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::new(ip.parse().unwrap(), 40000)));
+        request
+    }
+
+    fn get_basic_auth_config() -> MetricsAccessConfig {
+        MetricsAccessConfig {
+            basic_auth: Some(BasicAuthConfig {
+                username: "prometheus".to_string(),
+                password: "secret".to_string(),
+            }),
+            allowed_ips: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_access_without_restrictions() {
+        let app = create_test_router(MetricsAccessConfig::default());
+
+        let response = app
+            .oneshot(create_request("203.0.113.10", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_ips_are_matched_by_connection_address() {
+        let app = create_test_router(MetricsAccessConfig {
+            basic_auth: None,
+            allowed_ips: vec!["10.0.0.0/8".to_string()],
+        });
+
+        let response = app
+            .clone()
+            .oneshot(create_request("10.1.2.3", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut request = create_request("203.0.113.10", None);
+        request
+            .headers_mut()
+            .insert("x-forwarded-for", "10.1.2.3".parse().unwrap());
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_is_required() {
+        let app = create_test_router(get_basic_auth_config());
+
+        let response = app
+            .clone()
+            .oneshot(create_request("10.1.2.3", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.headers().get(header::WWW_AUTHENTICATE).unwrap(),
+            BASIC_AUTH_REALM
+        );
+
+        let wrong = format!("Basic {}", STANDARD.encode("prometheus:wrong"));
+        let response = app
+            .clone()
+            .oneshot(create_request("10.1.2.3", Some(&wrong)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let valid = format!("Basic {}", STANDARD.encode("prometheus:secret"));
+        let response = app
+            .oneshot(create_request("10.1.2.3", Some(&valid)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
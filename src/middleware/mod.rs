@@ -1,3 +1,9 @@
+pub mod access_log;
+pub mod body_limit;
 pub mod client_ip;
+pub mod metrics;
+pub mod metrics_access;
+pub mod rate_limit;
+pub mod request_id;
 
 pub use client_ip::ClientIpExtractor;
@@ -0,0 +1,285 @@
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::{error, info, warn};
+use std::sync::Arc;
+
+use crate::limits::LimitsService;
+use crate::limits::rate_limit::{RateLimitAction, RateLimitDecision, RateLimitStore};
+use crate::middleware::client_ip::ClientIp;
+use crate::routes::error::ApiError;
+
+/// Limits secret create/read/delete requests per client IP.
+///
+/// Must be applied inside `ClientIpExtractor` layer, requests without `ClientIp` extension pass through.
+pub struct RateLimiter {
+    limits_service: LimitsService,
+    store: Box<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    pub fn new(limits_service: LimitsService, store: Box<dyn RateLimitStore>) -> RateLimiter {
+        RateLimiter {
+            limits_service,
+            store,
+        }
+    }
+
+    pub async fn middleware(
+        State(limiter): State<Arc<RateLimiter>>,
+        request: Request,
+        next: Next,
+    ) -> Response {
+        let Some(action) = Self::get_action(request.method(), request.uri().path()) else {
+            return next.run(request).await;
+        };
+
+        let Some(ClientIp(client_ip)) = request.extensions().get::<ClientIp>().cloned() else {
+            warn!("client ip is unknown, rate limit is not applied");
+            return next.run(request).await;
+        };
+
+        let limits = limiter.limits_service.get_rate_limits_for_ip(&client_ip);
+        let rule = match action {
+            RateLimitAction::Create => &limits.create,
+            RateLimitAction::Read => &limits.read,
+            RateLimitAction::Delete => &limits.delete,
+        };
+
+        let key = format!("{action}:{client_ip}");
+
+        match limiter.store.hit(&key, rule).await {
+            Ok(RateLimitDecision::Allowed) => next.run(request).await,
+            Ok(RateLimitDecision::Limited {
+                retry_after_seconds,
+            }) => {
+                info!(
+                    "{} rate limit exceeded for client {}, retry after {} seconds",
+                    action, client_ip, retry_after_seconds
+                );
+                ApiError::TooManyRequests {
+                    retry_after_seconds,
+                }
+                .into_response()
+            }
+            Err(e) => {
+                error!("unable to check rate limit, request is allowed: {}", e);
+                next.run(request).await
+            }
+        }
+    }
+
+    /// Chunk uploads and file downloads are limited by upload start and secret view
+    fn get_action(method: &Method, path: &str) -> Option<RateLimitAction> {
+        let is_secret_path = path.starts_with("/api/secret/");
+        let is_file_path = is_secret_path && path.ends_with("/file");
+
+        match *method {
+            Method::POST if path == "/api/secret" || path == "/api/v1/secret" => {
+                Some(RateLimitAction::Create)
+            }
+            Method::POST if path == "/api/upload" => Some(RateLimitAction::Create),
+            Method::GET if is_secret_path && !is_file_path => Some(RateLimitAction::Read),
+            Method::DELETE if is_secret_path => Some(RateLimitAction::Delete),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::{
+        AppConfig, IpLimitEntry, IpLimitsConfig, RateLimitOverrides, RateLimitRule,
+        RateLimitsConfig,
+    };
+    use crate::limits::rate_limit::MemoryRateLimitStore;
+    use crate::tests::config::get_test_app_config;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::{StatusCode, header};
+    use axum::routing::{get, post};
+    use std::net::IpAddr;
+    use tower::util::ServiceExt;
+
+    fn create_test_config(ip_limits: Option<IpLimitsConfig>) -> AppConfig {
+        AppConfig {
+            rate_limits: RateLimitsConfig {
+                enabled: true,
+                create: RateLimitRule::new(1, 60),
+                read: RateLimitRule::new(2, 60),
+                delete: RateLimitRule::new(1, 60),
+                ..RateLimitsConfig::default()
+            },
+            ip_limits,
+            ..get_test_app_config()
+        }
+    }
+
+    fn create_test_router(config: &AppConfig) -> Router {
+        let limiter = Arc::new(RateLimiter::new(
+            LimitsService::new(config),
+            Box::new(MemoryRateLimitStore::new()),
+        ));
+
+        Router::new()
+            .route("/api/config", get(|| async { "config" }))
+            .route("/api/secret", post(|| async { "stored" }))
+            .route(
+                "/api/secret/{id}",
+                get(|| async { "loaded" }).delete(|| async { "removed" }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                limiter,
+                RateLimiter::middleware,
+            ))
+    }
+
+    fn create_request(method: Method, uri: &str, ip: &str) -> Request {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+
+        request
+            .extensions_mut()
+            .insert(ClientIp(ip.parse::<IpAddr>().unwrap()));
+        request
+    }
+
+    async fn send(app: &Router, method: Method, uri: &str, ip: &str) -> Response {
+        app.clone()
+            .oneshot(create_request(method, uri, ip))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_requests_above_limit_are_rejected() {
+        let app = create_test_router(&create_test_config(None));
+
+        for _ in 0..2 {
+            let response = send(&app, Method::GET, "/api/secret/abc", "192.168.1.100").await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = send(&app, Method::GET, "/api/secret/other", "192.168.1.100").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let retry_after: u64 = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!((1..=60).contains(&retry_after));
+    }
+
+    #[tokio::test]
+    async fn test_actions_have_separate_buckets() {
+        let app = create_test_router(&create_test_config(None));
+
+        let response = send(&app, Method::POST, "/api/secret", "192.168.1.100").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&app, Method::POST, "/api/secret", "192.168.1.100").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let response = send(&app, Method::GET, "/api/secret/abc", "192.168.1.100").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&app, Method::DELETE, "/api/secret/abc", "192.168.1.100").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&app, Method::DELETE, "/api/secret/abc", "192.168.1.100").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_clients_have_separate_buckets() {
+        let app = create_test_router(&create_test_config(None));
+
+        let response = send(&app, Method::POST, "/api/secret", "192.168.1.100").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&app, Method::POST, "/api/secret", "192.168.1.101").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_other_routes_are_not_limited() {
+        let app = create_test_router(&create_test_config(None));
+
+        for _ in 0..5 {
+            let response = send(&app, Method::GET, "/api/config", "192.168.1.100").await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_whitelist_entry_overrides_rate_limits() {
+        let ip_limits = IpLimitsConfig {
+            enabled: true,
+            whitelist: vec![IpLimitEntry {
+                ip: "10.0.0.0/8".to_string(),
+                message_max_length: None,
+                file_max_size: None,
+                rate_limits: Some(RateLimitOverrides {
+                    create: Some(RateLimitRule::new(3, 60)),
+                    ..RateLimitOverrides::default()
+                }),
+            }],
+            trusted_proxies: vec![],
+        };
+        let app = create_test_router(&create_test_config(Some(ip_limits)));
+
+        for _ in 0..3 {
+            let response = send(&app, Method::POST, "/api/secret", "10.1.2.3").await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        let response = send(&app, Method::POST, "/api/secret", "10.1.2.3").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Rules without override use global values
+        let response = send(&app, Method::DELETE, "/api/secret/abc", "10.1.2.3").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&app, Method::DELETE, "/api/secret/abc", "10.1.2.3").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_get_action() {
+        assert_eq!(
+            RateLimiter::get_action(&Method::POST, "/api/secret"),
+            Some(RateLimitAction::Create)
+        );
+        assert_eq!(
+            RateLimiter::get_action(&Method::POST, "/api/v1/secret"),
+            Some(RateLimitAction::Create)
+        );
+        assert_eq!(
+            RateLimiter::get_action(&Method::GET, "/api/secret/abc"),
+            Some(RateLimitAction::Read)
+        );
+        assert_eq!(
+            RateLimiter::get_action(&Method::DELETE, "/api/secret/abc"),
+            Some(RateLimitAction::Delete)
+        );
+        assert_eq!(
+            RateLimiter::get_action(&Method::POST, "/api/upload"),
+            Some(RateLimitAction::Create)
+        );
+        assert_eq!(
+            RateLimiter::get_action(&Method::PUT, "/api/upload/abc/chunks/0"),
+            None
+        );
+        assert_eq!(
+            RateLimiter::get_action(&Method::GET, "/api/secret/abc/file"),
+            None
+        );
+        assert_eq!(RateLimiter::get_action(&Method::GET, "/api/config"), None);
+        assert_eq!(RateLimiter::get_action(&Method::GET, "/s/abc"), None);
+    }
+}
@@ -0,0 +1,120 @@
+use axum::{
+    extract::Request,
+    http::{HeaderValue, header::HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longer incoming ids are replaced with generated ones
+const REQUEST_ID_MAX_LENGTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestId(pub String);
+
+/// Takes request id from `X-Request-Id` header or generates one, the id is returned in response header.
+///
+/// Incoming ids are reused only when they are short and printable, so they are safe to log.
+pub struct RequestIdPropagator;
+
+impl RequestIdPropagator {
+    pub async fn middleware(mut request: Request, next: Next) -> Response {
+        let request_id = request
+            .headers()
+            .get(&REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| Self::is_valid(value))
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        request
+            .extensions_mut()
+            .insert(RequestId(request_id.clone()));
+
+        let mut response = next.run(request).await;
+
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            response.headers_mut().insert(REQUEST_ID_HEADER, value);
+        }
+
+        response
+    }
+
+    fn is_valid(value: &str) -> bool {
+        !value.is_empty()
+            && value.len() <= REQUEST_ID_MAX_LENGTH
+            && value.bytes().all(|byte| byte.is_ascii_graphic())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::extract::Extension;
+    use axum::routing::get;
+    use tower::util::ServiceExt;
+
+    fn create_test_router() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|Extension(request_id): Extension<RequestId>| async move { request_id.0 }),
+            )
+            .layer(axum::middleware::from_fn(RequestIdPropagator::middleware))
+    }
+
+    async fn send(request_id: Option<&str>) -> (String, String) {
+        let mut request = Request::builder().uri("/");
+
+        if let Some(request_id) = request_id {
+            request = request.header(REQUEST_ID_HEADER, request_id);
+        }
+
+        let response = create_test_router()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let header = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn incoming_request_id_should_be_propagated() {
+        let (header, extension) = send(Some("abc-123")).await;
+
+        assert_eq!(header, "abc-123");
+        assert_eq!(extension, "abc-123");
+    }
+
+    #[tokio::test]
+    async fn missing_request_id_should_be_generated() {
+        let (header, extension) = send(None).await;
+
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(header, extension);
+    }
+
+    #[tokio::test]
+    async fn invalid_request_id_should_be_replaced() {
+        let too_long = "a".repeat(REQUEST_ID_MAX_LENGTH + 1);
+
+        for request_id in ["with space", too_long.as_str()] {
+            let (header, extension) = send(Some(request_id)).await;
+
+            assert!(Uuid::parse_str(&header).is_ok());
+            assert_eq!(header, extension);
+        }
+    }
+}
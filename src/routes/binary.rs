@@ -0,0 +1,262 @@
+use crate::routes::error::ApiError;
+use crate::routes::json::ApiJson;
+use crate::secret::model::{Secret, SecretPayload};
+use axum::body::Bytes;
+use axum::extract::{FromRequest, Request};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use log::{error, info};
+
+/// Secret fields except `payload` as JSON, used with `application/octet-stream` bodies
+pub const SECRET_METADATA_HEADER: &str = "x-secret-metadata";
+
+pub const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Secret sent either as JSON with base64 payload or as raw encrypted payload,
+/// other fields are taken from `X-Secret-Metadata` header then
+pub struct SecretBody(pub Secret);
+
+impl<S: Send + Sync> FromRequest<S> for SecretBody {
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_octet_stream(request.headers()) {
+            let ApiJson(secret) = ApiJson::<Secret>::from_request(request, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            return Ok(SecretBody(secret));
+        }
+
+        let mut secret = request
+            .headers()
+            .get(SECRET_METADATA_HEADER)
+            .ok_or(ApiError::InvalidSecretMetadata)
+            .and_then(parse_secret_metadata)
+            .map_err(IntoResponse::into_response)?;
+
+        let payload = Bytes::from_request(request, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        secret.payload = SecretPayload::from(payload.to_vec());
+
+        Ok(SecretBody(secret))
+    }
+}
+
+pub fn is_octet_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case(OCTET_STREAM))
+}
+
+/// Client prefers raw payload when `Accept` lists `application/octet-stream`
+pub fn accepts_octet_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|value| value.split(';').next())
+        .any(|mime| mime.trim().eq_ignore_ascii_case(OCTET_STREAM))
+}
+
+fn parse_secret_metadata(value: &HeaderValue) -> Result<Secret, ApiError> {
+    let value = value
+        .to_str()
+        .map_err(|_| ApiError::InvalidSecretMetadata)?;
+
+    let serde_json::Value::Object(mut fields) =
+        serde_json::from_str(value).map_err(|_| ApiError::InvalidSecretMetadata)?
+    else {
+        info!("secret metadata must be JSON object");
+        return Err(ApiError::InvalidSecretMetadata);
+    };
+
+    fields.insert("payload".to_string(), serde_json::Value::from(""));
+
+    serde_json::from_value(serde_json::Value::Object(fields)).map_err(|e| {
+        info!("invalid secret metadata: {e}");
+        ApiError::InvalidSecretMetadata
+    })
+}
+
+/// Raw payload with other secret fields in `X-Secret-Metadata` header
+pub fn get_binary_secret_response(secret: &Secret) -> Response {
+    let metadata = match get_secret_metadata(secret) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            error!("secret metadata serialization error: {e}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(OCTET_STREAM)),
+            (
+                header::HeaderName::from_static(SECRET_METADATA_HEADER),
+                metadata,
+            ),
+        ],
+        secret.payload.as_bytes().to_vec(),
+    )
+        .into_response()
+}
+
+fn get_secret_metadata(secret: &Secret) -> anyhow::Result<HeaderValue> {
+    let serde_json::Value::Object(mut fields) = serde_json::to_value(secret)? else {
+        anyhow::bail!("secret must be serialized into object");
+    };
+    fields.remove("payload");
+
+    let json = serde_json::Value::Object(fields).to_string();
+
+    Ok(HeaderValue::from_str(&escape_non_ascii(&json))?)
+}
+
+/// Header values are limited to ASCII, non-ASCII chars are only possible inside
+/// JSON strings, so `\uXXXX` escapes keep JSON valid
+fn escape_non_ascii(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                escaped.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::secret::get_sample_secret;
+    use axum::body::Body;
+
+    fn create_binary_request(metadata: Option<&str>, payload: &[u8]) -> Request {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("/api/secret")
+            .header(header::CONTENT_TYPE, OCTET_STREAM);
+
+        if let Some(metadata) = metadata {
+            request = request.header(SECRET_METADATA_HEADER, metadata);
+        }
+
+        request.body(Body::from(payload.to_vec())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn binary_body_should_be_parsed_with_metadata_header() {
+        let metadata = r#"{"contentType":"File","metadata":{"name":"a.bin","type":"application/octet-stream","size":3},"ttl":"OneHour","downloadPolicy":"OneTime"}"#;
+        let request = create_binary_request(Some(metadata), &[0, 159, 255]);
+
+        let SecretBody(secret) = SecretBody::from_request(request, &()).await.unwrap();
+
+        assert_eq!(secret.payload, SecretPayload::from(vec![0, 159, 255]));
+        assert_eq!(secret.metadata.name, "a.bin");
+    }
+
+    #[tokio::test]
+    async fn binary_body_should_accept_encrypted_metadata() {
+        let metadata = r#"{"contentType":"File","encryptedMetadata":"AAEC/w==","ttl":"OneHour","downloadPolicy":"OneTime"}"#;
+        let request = create_binary_request(Some(metadata), &[1, 2]);
+
+        let SecretBody(secret) = SecretBody::from_request(request, &()).await.unwrap();
+
+        assert_eq!(
+            secret.encrypted_metadata,
+            Some(SecretPayload::from(vec![0, 1, 2, 255]))
+        );
+        assert_eq!(secret.metadata.name, "");
+
+        let response = get_binary_secret_response(&secret);
+        let header = response.headers().get(SECRET_METADATA_HEADER).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(header.as_bytes()).unwrap();
+
+        assert_eq!(json["encryptedMetadata"], "AAEC/w==");
+    }
+
+    #[tokio::test]
+    async fn binary_body_without_metadata_should_be_rejected() {
+        for metadata in [None, Some("[]"), Some(r#"{"contentType":"Text"}"#)] {
+            let request = create_binary_request(metadata, b"data");
+
+            let response = SecretBody::from_request(request, &()).await.err().unwrap();
+
+            assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+
+    #[tokio::test]
+    async fn json_body_should_be_parsed_as_before() {
+        let secret = get_sample_secret();
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/secret")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_vec(&secret).unwrap()))
+            .unwrap();
+
+        let SecretBody(parsed) = SecretBody::from_request(request, &()).await.unwrap();
+
+        assert_eq!(parsed, secret);
+    }
+
+    #[tokio::test]
+    async fn legacy_json_body_should_keep_payload_string() {
+        let body = r#"{"contentType":"Text","payload":"legacy:ciphertext","ttl":"OneHour","downloadPolicy":"OneTime"}"#;
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/secret")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap();
+
+        let SecretBody(secret) = SecretBody::from_request(request, &()).await.unwrap();
+
+        assert_eq!(secret.payload.as_bytes(), b"legacy:ciphertext");
+        assert_eq!(
+            serde_json::to_value(&secret).unwrap()["payload"],
+            "legacy:ciphertext"
+        );
+    }
+
+    #[test]
+    fn accept_header_should_be_matched_by_mime_type() {
+        let mut headers = HeaderMap::new();
+        assert!(!accepts_octet_stream(&headers));
+
+        headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+        assert!(!accepts_octet_stream(&headers));
+
+        headers.insert(
+            header::ACCEPT,
+            "application/json, application/octet-stream;q=0.9"
+                .parse()
+                .unwrap(),
+        );
+        assert!(accepts_octet_stream(&headers));
+    }
+
+    #[test]
+    fn metadata_header_should_be_ascii_json_without_payload() {
+        let mut secret = get_sample_secret();
+        secret.metadata.name = "отчёт 😀.pdf".to_string();
+
+        let metadata = get_secret_metadata(&secret).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(metadata.as_bytes()).unwrap();
+
+        assert!(metadata.to_str().is_ok());
+        assert_eq!(json["metadata"]["name"], "отчёт 😀.pdf");
+        assert!(json.get("payload").is_none());
+    }
+}
@@ -29,6 +29,9 @@ pub async fn get_config_route(
         message_max_length: limits.message_max_length,
         file_upload_enabled: state.config.file_upload_enabled,
         file_max_size: limits.file_max_size,
+        ttl_min: state.config.ttl_min,
+        ttl_max: state.config.ttl_max,
+        ttl_default: state.config.ttl_default,
     };
 
     (StatusCode::OK, Json(config)).into_response()
@@ -37,43 +40,42 @@ pub async fn get_config_route(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::model::{AppConfig, IpLimitEntry, IpLimitsConfig};
+    use crate::audit::AuditLog;
+    use crate::config::model::{
+        AppConfig, DEFAULT_TTL_DEFAULT, DEFAULT_TTL_MAX, DEFAULT_TTL_MIN, IpLimitEntry,
+        IpLimitsConfig,
+    };
+    use crate::config::reload::ConfigReloadStatus;
     use crate::limits::LimitsService;
     use crate::metrics::service::MetricsServer;
     use crate::middleware::client_ip::ClientIp;
     use crate::secret::storage::MockSecretStorage;
+    use crate::shutdown::ShutdownSignal;
+    use crate::tests::config::get_test_app_config;
     use axum::http::Request as HttpRequest;
     use std::net::IpAddr;
     use std::sync::Arc;
 
     fn create_test_app_state(ip_limits_config: Option<IpLimitsConfig>) -> Arc<AppState> {
         let config = AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
-            message_max_length: 1024,
-            file_upload_enabled: true,
-            file_max_size: 10485760,
             encrypted_message_max_length: Some(15485760),
-            redis_url: "redis://localhost".to_string(),
             ip_limits: ip_limits_config,
+            ..get_test_app_config()
         };
 
         let limits_service = LimitsService::new(&config);
         let secret_storage = MockSecretStorage::new();
 
-        let body_limit = limits_service
-            .body_limit_as_usize()
-            .expect("failed to calculate body limit");
-
-        let metrics_server = MetricsServer::new(config.clone(), body_limit);
+        let metrics_server = MetricsServer::new(config.clone(), limits_service.clone(), None);
 
         Arc::new(AppState {
             config,
             limits_service,
             secret_storage: Box::new(secret_storage),
-            body_limit,
             metrics_server,
+            shutdown: ShutdownSignal::new(),
+            config_reload: ConfigReloadStatus::default(),
+            audit_log: AuditLog::disabled(),
         })
     }
 
@@ -105,6 +107,9 @@ mod tests {
         assert_eq!(config.message_max_length, 1024);
         assert_eq!(config.file_max_size, 10485760);
         assert!(config.file_upload_enabled);
+        assert_eq!(config.ttl_min, DEFAULT_TTL_MIN);
+        assert_eq!(config.ttl_max, DEFAULT_TTL_MAX);
+        assert_eq!(config.ttl_default, DEFAULT_TTL_DEFAULT);
     }
 
     #[tokio::test]
@@ -115,6 +120,7 @@ mod tests {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -145,6 +151,7 @@ mod tests {
                 ip: "192.168.0.0/16".to_string(),
                 message_max_length: Some(4096),
                 file_max_size: Some(52428800),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -174,6 +181,7 @@ mod tests {
                 ip: "10.0.0.0/8".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -204,6 +212,7 @@ mod tests {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -257,6 +266,7 @@ mod tests {
                 ip: "2001:db8::/32".to_string(),
                 message_max_length: Some(16384),
                 file_max_size: Some(209715200),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -281,32 +291,24 @@ mod tests {
     #[tokio::test]
     async fn test_config_route_with_file_upload_disabled() {
         let base_config = AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
-            message_max_length: 1024,
             file_upload_enabled: false, // Disabled
-            file_max_size: 10485760,
             encrypted_message_max_length: Some(15485760),
-            redis_url: "redis://localhost".to_string(),
-            ip_limits: None,
+            ..get_test_app_config()
         };
 
         let limits_service = LimitsService::new(&base_config);
         let secret_storage = MockSecretStorage::new();
 
-        let body_limit = limits_service
-            .body_limit_as_usize()
-            .expect("Failed to calculate body limit");
-
-        let metrics_server = MetricsServer::new(base_config.clone(), body_limit);
+        let metrics_server = MetricsServer::new(base_config.clone(), limits_service.clone(), None);
 
         let state = Arc::new(AppState {
             config: base_config,
             limits_service,
             secret_storage: Box::new(secret_storage),
-            body_limit,
             metrics_server,
+            shutdown: ShutdownSignal::new(),
+            config_reload: ConfigReloadStatus::default(),
+            audit_log: AuditLog::disabled(),
         });
 
         let request = create_request_with_ip("192.168.1.100".parse().unwrap());
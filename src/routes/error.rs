@@ -0,0 +1,303 @@
+use crate::dto::model::ApiErrorDto;
+use crate::secret::upload::{LoadFileError, UploadSecretError};
+use crate::secret::usecase::{LoadSecretError, RemoveSecretError, StoreSecretError};
+use axum::extract::rejection::JsonRejection;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::{Extension, Json};
+use log::info;
+use thiserror::Error;
+
+/// Attached to error responses, lets middlewares tell errors apart without parsing the body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApiErrorCode(pub &'static str);
+
+/// Errors returned by secret routes as `{code, message}` JSON body
+#[derive(Error, Debug, PartialEq)]
+pub enum ApiError {
+    #[error("secret wasn't found")]
+    SecretNotFound,
+
+    #[error("secret has expired")]
+    SecretExpired,
+
+    #[error("secret id is required")]
+    SecretIdRequired,
+
+    #[error("secret with the same id already exists")]
+    SecretAlreadyExists,
+
+    #[error("payload is bigger than allowed")]
+    PayloadTooLarge,
+
+    #[error("file upload is disabled")]
+    FileUploadDisabled,
+
+    #[error("ttl is outside of allowed range")]
+    InvalidTtl,
+
+    #[error("invalid download policy")]
+    InvalidDownloadPolicy,
+
+    #[error("revocation token is required")]
+    RevocationTokenRequired,
+
+    #[error("secret metadata is missing or invalid")]
+    InvalidSecretMetadata,
+
+    #[error("upload wasn't found")]
+    UploadNotFound,
+
+    #[error("chunk doesn't match upload")]
+    InvalidChunk,
+
+    #[error("not all chunks have been uploaded")]
+    UploadIncomplete,
+
+    #[error("file key is required")]
+    FileKeyRequired,
+
+    #[error("range isn't satisfiable")]
+    RangeNotSatisfiable { size: u64 },
+
+    #[error("too many requests, retry after {retry_after_seconds} seconds")]
+    TooManyRequests { retry_after_seconds: u64 },
+
+    #[error("storage capacity exceeded")]
+    StorageFull,
+
+    #[error("storage is unavailable")]
+    StorageUnavailable,
+
+    #[error("request body is malformed")]
+    InvalidRequest,
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::SecretNotFound | ApiError::UploadNotFound => StatusCode::NOT_FOUND,
+            ApiError::SecretExpired => StatusCode::GONE,
+            ApiError::SecretAlreadyExists | ApiError::UploadIncomplete => StatusCode::CONFLICT,
+            ApiError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::InvalidRequest => StatusCode::BAD_REQUEST,
+            ApiError::SecretIdRequired
+            | ApiError::FileUploadDisabled
+            | ApiError::InvalidTtl
+            | ApiError::InvalidDownloadPolicy
+            | ApiError::InvalidChunk
+            | ApiError::InvalidSecretMetadata => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::RevocationTokenRequired | ApiError::FileKeyRequired => {
+                StatusCode::UNAUTHORIZED
+            }
+            ApiError::RangeNotSatisfiable { .. } => StatusCode::RANGE_NOT_SATISFIABLE,
+            ApiError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::StorageFull | ApiError::StorageUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::SecretNotFound => "secret_not_found",
+            ApiError::SecretExpired => "secret_expired",
+            ApiError::SecretIdRequired => "secret_id_required",
+            ApiError::SecretAlreadyExists => "secret_already_exists",
+            ApiError::PayloadTooLarge => "payload_too_large",
+            ApiError::FileUploadDisabled => "file_upload_disabled",
+            ApiError::InvalidTtl => "invalid_ttl",
+            ApiError::InvalidDownloadPolicy => "invalid_download_policy",
+            ApiError::RevocationTokenRequired => "revocation_token_required",
+            ApiError::InvalidSecretMetadata => "invalid_secret_metadata",
+            ApiError::UploadNotFound => "upload_not_found",
+            ApiError::InvalidChunk => "invalid_chunk",
+            ApiError::UploadIncomplete => "upload_incomplete",
+            ApiError::FileKeyRequired => "file_key_required",
+            ApiError::RangeNotSatisfiable { .. } => "range_not_satisfiable",
+            ApiError::TooManyRequests { .. } => "too_many_requests",
+            ApiError::StorageFull => "storage_full",
+            ApiError::StorageUnavailable => "storage_unavailable",
+            ApiError::InvalidRequest => "invalid_request",
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let dto = ApiErrorDto {
+            code: self.code().to_string(),
+            message: self.to_string(),
+        };
+        let code = Extension(ApiErrorCode(self.code()));
+
+        match self {
+            ApiError::TooManyRequests {
+                retry_after_seconds,
+            } => (
+                self.status_code(),
+                [(header::RETRY_AFTER, retry_after_seconds.to_string())],
+                code,
+                Json(dto),
+            )
+                .into_response(),
+            ApiError::RangeNotSatisfiable { size } => (
+                self.status_code(),
+                [(header::CONTENT_RANGE, format!("bytes */{size}"))],
+                code,
+                Json(dto),
+            )
+                .into_response(),
+            _ => (self.status_code(), code, Json(dto)).into_response(),
+        }
+    }
+}
+
+impl From<StoreSecretError> for ApiError {
+    fn from(error: StoreSecretError) -> Self {
+        match error {
+            StoreSecretError::PayloadTooLarge => ApiError::PayloadTooLarge,
+            StoreSecretError::InvalidTtl => ApiError::InvalidTtl,
+            StoreSecretError::InvalidDownloadPolicy => ApiError::InvalidDownloadPolicy,
+            StoreSecretError::InvalidMetadata => ApiError::InvalidSecretMetadata,
+            StoreSecretError::AlreadyExists => ApiError::SecretAlreadyExists,
+            StoreSecretError::StorageFull => ApiError::StorageFull,
+            StoreSecretError::Storage => ApiError::StorageUnavailable,
+        }
+    }
+}
+
+impl From<LoadSecretError> for ApiError {
+    fn from(error: LoadSecretError) -> Self {
+        match error {
+            LoadSecretError::Banned {
+                retry_after_seconds,
+            } => ApiError::TooManyRequests {
+                retry_after_seconds,
+            },
+            LoadSecretError::NotFound => ApiError::SecretNotFound,
+            LoadSecretError::Expired => ApiError::SecretExpired,
+            LoadSecretError::Storage => ApiError::StorageUnavailable,
+        }
+    }
+}
+
+impl From<RemoveSecretError> for ApiError {
+    fn from(error: RemoveSecretError) -> Self {
+        match error {
+            RemoveSecretError::NotFound => ApiError::SecretNotFound,
+            RemoveSecretError::Storage => ApiError::StorageUnavailable,
+        }
+    }
+}
+
+impl From<UploadSecretError> for ApiError {
+    fn from(error: UploadSecretError) -> Self {
+        match error {
+            UploadSecretError::Store(error) => ApiError::from(error),
+            UploadSecretError::InvalidChunk => ApiError::InvalidChunk,
+            UploadSecretError::NotFound => ApiError::UploadNotFound,
+            UploadSecretError::Incomplete => ApiError::UploadIncomplete,
+        }
+    }
+}
+
+impl From<LoadFileError> for ApiError {
+    fn from(error: LoadFileError) -> Self {
+        match error {
+            LoadFileError::NotFound => ApiError::SecretNotFound,
+            LoadFileError::RangeNotSatisfiable { size } => ApiError::RangeNotSatisfiable { size },
+            LoadFileError::Storage => ApiError::StorageUnavailable,
+        }
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            return ApiError::PayloadTooLarge;
+        }
+
+        info!("invalid request body: {}", rejection.body_text());
+        ApiError::InvalidRequest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn get_error_dto(response: Response) -> ApiErrorDto {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn error_should_be_rendered_as_json() {
+        let response = ApiError::PayloadTooLarge.into_response();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            get_error_dto(response).await,
+            ApiErrorDto {
+                code: "payload_too_large".to_string(),
+                message: "payload is bigger than allowed".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn too_many_requests_should_have_retry_after_header() {
+        let response = ApiError::TooManyRequests {
+            retry_after_seconds: 30,
+        }
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "30");
+        assert_eq!(get_error_dto(response).await.code, "too_many_requests");
+    }
+
+    #[tokio::test]
+    async fn range_not_satisfiable_should_have_content_range_header() {
+        let response = ApiError::RangeNotSatisfiable { size: 250 }.into_response();
+
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            response.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */250"
+        );
+    }
+
+    #[test]
+    fn usecase_errors_should_be_mapped_to_status_codes() {
+        assert_eq!(
+            ApiError::from(LoadSecretError::NotFound).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::from(LoadSecretError::Expired).status_code(),
+            StatusCode::GONE
+        );
+        assert_eq!(
+            ApiError::from(StoreSecretError::InvalidTtl).status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(
+            ApiError::from(StoreSecretError::Storage).status_code(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            ApiError::from(RemoveSecretError::NotFound).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::from(UploadSecretError::Store(StoreSecretError::PayloadTooLarge)),
+            ApiError::PayloadTooLarge
+        );
+        assert_eq!(
+            ApiError::from(UploadSecretError::Incomplete).status_code(),
+            StatusCode::CONFLICT
+        );
+    }
+}
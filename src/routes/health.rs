@@ -0,0 +1,260 @@
+use crate::AppState;
+use crate::VERSION;
+use crate::config::model::StorageBackend;
+use crate::dto::model::{
+    HealthDto, ReadinessCheckDto, ReadinessChecksDto, ReadinessDto, StorageCheckDto,
+};
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use log::warn;
+use std::sync::Arc;
+
+const CHECK_OK: &str = "ok";
+const CHECK_FAIL: &str = "fail";
+
+/// Liveness probe, doesn't touch storage
+pub async fn get_health_route(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let health = HealthDto {
+        status: CHECK_OK.to_string(),
+        version: VERSION.to_string(),
+        uptime_seconds: state.metrics_server.get_uptime().as_secs(),
+    };
+
+    (StatusCode::OK, Json(health))
+}
+
+/// Readiness probe, Redis availability is taken from the cached background probe
+pub async fn get_readiness_route(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let config = match state.config_reload.last_error() {
+        Some(error) => ReadinessCheckDto {
+            status: CHECK_FAIL.to_string(),
+            message: Some(format!("unable to reload ip-limits: {error}")),
+        },
+        None => ReadinessCheckDto {
+            status: CHECK_OK.to_string(),
+            message: None,
+        },
+    };
+
+    let storage = get_storage_check(&state);
+
+    let shutdown = if state.shutdown.is_draining() {
+        ReadinessCheckDto {
+            status: CHECK_FAIL.to_string(),
+            message: Some("shutting down".to_string()),
+        }
+    } else {
+        ReadinessCheckDto {
+            status: CHECK_OK.to_string(),
+            message: None,
+        }
+    };
+
+    let ready =
+        config.status == CHECK_OK && storage.status == CHECK_OK && shutdown.status == CHECK_OK;
+
+    let (status_code, status) = if ready {
+        (StatusCode::OK, "ready")
+    } else {
+        warn!(
+            "application is not ready: config {:?}, storage {:?}, shutdown {:?}",
+            config.message, storage.message, shutdown.message
+        );
+        (StatusCode::SERVICE_UNAVAILABLE, "not-ready")
+    };
+
+    let readiness = ReadinessDto {
+        status: status.to_string(),
+        checks: ReadinessChecksDto {
+            config,
+            storage,
+            shutdown,
+        },
+    };
+
+    (status_code, Json(readiness))
+}
+
+fn get_storage_check(state: &AppState) -> StorageCheckDto {
+    let backend = state.config.storage.backend.clone();
+
+    if backend != StorageBackend::Redis {
+        // Embedded and memory storages live in-process
+        return StorageCheckDto {
+            status: CHECK_OK.to_string(),
+            backend,
+            message: None,
+            latency_seconds: None,
+        };
+    }
+
+    match state.metrics_server.get_redis_probe() {
+        Some(probe) if probe.is_up() => StorageCheckDto {
+            status: CHECK_OK.to_string(),
+            backend,
+            message: None,
+            latency_seconds: probe.latency_seconds,
+        },
+        Some(_) => StorageCheckDto {
+            status: CHECK_FAIL.to_string(),
+            backend,
+            message: Some("redis is unavailable".to_string()),
+            latency_seconds: None,
+        },
+        None => StorageCheckDto {
+            status: CHECK_FAIL.to_string(),
+            backend,
+            message: Some("redis wasn't checked yet".to_string()),
+            latency_seconds: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditLog;
+    use crate::config::model::{AppConfig, RedisPoolConfig, StorageConfig};
+    use crate::config::reload::ConfigReloadStatus;
+    use crate::limits::LimitsService;
+    use crate::metrics::service::MetricsServer;
+    use crate::secret::redis_pool::RedisConnectionPool;
+    use crate::secret::storage::MockSecretStorage;
+    use crate::shutdown::ShutdownSignal;
+    use crate::tests::config::get_test_app_config;
+    use axum::response::Response;
+
+    fn create_test_app_state(
+        backend: StorageBackend,
+        redis_pool: Option<RedisConnectionPool>,
+    ) -> Arc<AppState> {
+        let config = AppConfig {
+            storage: StorageConfig {
+                backend,
+                ..StorageConfig::default()
+            },
+            ..get_test_app_config()
+        };
+
+        let limits_service = LimitsService::new(&config);
+        let metrics_server = MetricsServer::new(config.clone(), limits_service.clone(), redis_pool);
+
+        Arc::new(AppState {
+            config,
+            limits_service,
+            secret_storage: Box::new(MockSecretStorage::new()),
+            metrics_server,
+            shutdown: ShutdownSignal::new(),
+            config_reload: ConfigReloadStatus::default(),
+            audit_log: AuditLog::disabled(),
+        })
+    }
+
+    async fn get_json(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn health_route_should_return_ok() {
+        let state = create_test_app_state(StorageBackend::Redis, None);
+
+        let response = get_health_route(State(state)).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = get_json(response).await;
+        assert_eq!(json["status"], "ok");
+        assert_eq!(json["version"], VERSION);
+    }
+
+    #[tokio::test]
+    async fn in_process_storage_should_be_ready() {
+        let state = create_test_app_state(StorageBackend::Memory, None);
+
+        let response = get_readiness_route(State(state)).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let json = get_json(response).await;
+        assert_eq!(json["status"], "ready");
+        assert_eq!(json["checks"]["config"]["status"], "ok");
+        assert_eq!(json["checks"]["storage"]["status"], "ok");
+        assert_eq!(json["checks"]["storage"]["backend"], "memory");
+        assert_eq!(json["checks"]["shutdown"]["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn draining_application_should_not_be_ready() {
+        let state = create_test_app_state(StorageBackend::Memory, None);
+        state.shutdown.trigger();
+
+        let response = get_readiness_route(State(state)).await.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let json = get_json(response).await;
+        assert_eq!(json["status"], "not-ready");
+        assert_eq!(json["checks"]["storage"]["status"], "ok");
+        assert_eq!(json["checks"]["shutdown"]["status"], "fail");
+    }
+
+    #[tokio::test]
+    async fn failed_config_reload_should_not_be_ready() {
+        let state = create_test_app_state(StorageBackend::Memory, None);
+        state
+            .config_reload
+            .record(&Err(anyhow::anyhow!("invalid ip-limits")));
+
+        let response = get_readiness_route(State(state.clone()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let json = get_json(response).await;
+        assert_eq!(json["checks"]["config"]["status"], "fail");
+        assert_eq!(
+            json["checks"]["config"]["message"],
+            "unable to reload ip-limits: invalid ip-limits"
+        );
+
+        state.config_reload.record(&Ok(()));
+
+        let response = get_readiness_route(State(state)).await.into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn redis_storage_should_not_be_ready_before_first_probe() {
+        let state = create_test_app_state(StorageBackend::Redis, None);
+
+        let response = get_readiness_route(State(state)).await.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let json = get_json(response).await;
+        assert_eq!(json["status"], "not-ready");
+        assert_eq!(json["checks"]["storage"]["status"], "fail");
+    }
+
+    #[tokio::test]
+    async fn unavailable_redis_should_not_be_ready() {
+        let redis_pool = RedisConnectionPool::new(
+            "redis://127.0.0.1:1",
+            &RedisPoolConfig {
+                connect_timeout_ms: 100,
+                ..RedisPoolConfig::default()
+            },
+        )
+        .unwrap();
+        let state = create_test_app_state(StorageBackend::Redis, Some(redis_pool));
+
+        state.metrics_server.probe_redis().await;
+
+        let response = get_readiness_route(State(state)).await.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let json = get_json(response).await;
+        assert_eq!(json["checks"]["storage"]["message"], "redis is unavailable");
+    }
+}
@@ -0,0 +1,67 @@
+use crate::routes::error::ApiError;
+use axum::Json;
+use axum::extract::{FromRequest, Request};
+use serde::de::DeserializeOwned;
+
+/// Same as `Json`, but rejects malformed bodies with `ApiError` JSON body instead of plain text
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state).await?;
+        Ok(ApiJson(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{StatusCode, header};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Sample {
+        #[allow(dead_code)]
+        name: String,
+    }
+
+    fn create_request(content_type: &str, body: &'static str) -> Request {
+        Request::builder()
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn valid_body_should_be_parsed() {
+        let request = create_request("application/json", r#"{"name":"test"}"#);
+
+        assert!(ApiJson::<Sample>::from_request(request, &()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn malformed_body_should_be_rejected_as_invalid_request() {
+        for (content_type, body) in [
+            ("application/json", "{"),
+            ("application/json", r#"{"name":1}"#),
+            ("text/plain", r#"{"name":"test"}"#),
+        ] {
+            let request = create_request(content_type, body);
+
+            let error = ApiJson::<Sample>::from_request(request, &())
+                .await
+                .err()
+                .unwrap();
+
+            assert_eq!(error, ApiError::InvalidRequest);
+            assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        }
+    }
+}
@@ -1,16 +1,26 @@
 use crate::AppState;
+use crate::metrics::model::ExpositionFormat;
 use axum::extract::State;
-use axum::http::{StatusCode, header};
+use axum::http::{HeaderMap, StatusCode, header};
 use axum::response::IntoResponse;
 use std::sync::Arc;
 
-pub async fn get_metrics_route(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+pub async fn get_metrics_route(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let format = headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .map(ExpositionFormat::from_accept)
+        .unwrap_or(ExpositionFormat::Prometheus);
+
     let metrics = state.metrics_server.get_metrics().await;
-    let body = metrics.to_prometheus_text();
+    let body = metrics.render(format);
 
     (
         StatusCode::OK,
-        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        [(header::CONTENT_TYPE, format.content_type())],
         body,
     )
 }
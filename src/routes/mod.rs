@@ -1,4 +1,9 @@
+pub mod binary;
 pub mod config;
+pub mod error;
+pub mod health;
+pub mod json;
 pub mod metrics;
 pub mod secret;
+pub mod upload;
 pub mod version;
@@ -1,22 +1,59 @@
 use crate::AppState;
+use crate::audit::{AuditAction, AuditEvent};
+use crate::dto::model::StoredSecretDto;
+use crate::metrics::traffic::{SecretAction, SecretEvent};
 use crate::middleware::client_ip::ClientIp;
+use crate::routes::binary::{SecretBody, accepts_octet_stream, get_binary_secret_response};
+use crate::routes::error::ApiError;
+use crate::routes::json::ApiJson;
 use crate::secret::model::{Secret, SecretContentType};
-use crate::secret::usecase::store_secret;
+use crate::secret::usecase::{
+    LoadSecretError, TtlLimits, generate_secret_id, load_secret, remove_secret, store_secret,
+};
 use axum::Json;
 use axum::extract::{Extension, Path, State};
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use log::{error, info};
 use std::sync::Arc;
 
+pub const REVOCATION_TOKEN_HEADER: &str = "x-revocation-token";
+
+/// Stores secret under a server-generated id, any client-supplied id is ignored.
+///
+/// Accepts JSON or raw `application/octet-stream` payload, see `SecretBody`.
 pub async fn store_secret_route(
     State(state): State<Arc<AppState>>,
     Extension(client_ip): Extension<ClientIp>,
-    secret: Json<Secret>,
-) -> StatusCode {
+    SecretBody(mut secret): SecretBody,
+) -> Response {
+    secret.id = generate_secret_id();
+    store_secret_for_client(&state, &client_ip, &secret).await
+}
+
+/// Legacy endpoint for clients which generate secret ids by themselves
+pub async fn store_secret_with_id_route(
+    State(state): State<Arc<AppState>>,
+    Extension(client_ip): Extension<ClientIp>,
+    ApiJson(secret): ApiJson<Secret>,
+) -> Response {
+    if secret.id.trim().is_empty() {
+        info!("secret id is required");
+        return ApiError::SecretIdRequired.into_response();
+    }
+
+    store_secret_for_client(&state, &client_ip, &secret).await
+}
+
+/// File uploads are gated by `content_type` alone, metadata may be encrypted and isn't looked at
+async fn store_secret_for_client(
+    state: &AppState,
+    client_ip: &ClientIp,
+    secret: &Secret,
+) -> Response {
     if secret.content_type == SecretContentType::File && !state.config.file_upload_enabled {
         info!("file upload is disabled");
-        return StatusCode::BAD_REQUEST;
+        return ApiError::FileUploadDisabled.into_response();
     }
 
     let client_ip_str = client_ip.0.to_string();
@@ -29,91 +66,160 @@ pub async fn store_secret_route(
 
     match store_secret(
         state.secret_storage.as_ref(),
-        &secret,
+        secret,
         client_limits.encrypted_message_max_length,
-    ) {
-        Ok(_) => {
+        &TtlLimits::from(&state.config),
+    )
+    .await
+    {
+        Ok(revocation_token) => {
             info!("secret stored successfully for client {}", client_ip_str);
-            StatusCode::OK
+            state
+                .audit_log
+                .record(AuditEvent::new(AuditAction::Create, secret, &client_ip_str))
+                .await;
+            let event = SecretEvent::new(SecretAction::Create, secret);
+            let dto = StoredSecretDto {
+                id: secret.id.to_string(),
+                revocation_token,
+            };
+            (StatusCode::OK, Extension(event), Json(dto)).into_response()
         }
         Err(e) => {
             error!("failed to store secret for client {}: {}", client_ip_str, e);
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::from(e).into_response()
         }
     }
 }
 
+/// Returns raw payload when `Accept` lists `application/octet-stream`, JSON otherwise
 pub async fn get_secret_route(
     State(state): State<Arc<AppState>>,
+    Extension(client_ip): Extension<ClientIp>,
+    headers: HeaderMap,
     Path(id): Path<String>,
-) -> impl IntoResponse {
-    match state.secret_storage.load(&id) {
-        Ok(secret) => match secret {
-            Some(secret) => (StatusCode::OK, Json(secret)).into_response(),
-            None => StatusCode::BAD_REQUEST.into_response(),
-        },
+) -> Response {
+    let client_ip_str = client_ip.0.to_string();
+
+    match load_secret(
+        state.secret_storage.as_ref(),
+        &id,
+        &client_ip_str,
+        &state.config.lookup_guard,
+    )
+    .await
+    {
+        Ok(secret) => {
+            state
+                .audit_log
+                .record(AuditEvent::new(AuditAction::Read, &secret, &client_ip_str))
+                .await;
+            let event = SecretEvent::new(SecretAction::Read, &secret);
+            if accepts_octet_stream(&headers) {
+                return (Extension(event), get_binary_secret_response(&secret)).into_response();
+            }
+            (StatusCode::OK, Extension(event), Json(secret)).into_response()
+        }
         Err(e) => {
-            error!("{}", e);
-            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+            match e {
+                LoadSecretError::NotFound => state.metrics_server.record_secret_lookup_failure(),
+                LoadSecretError::Expired => state.audit_log.record(AuditEvent::expired(&id)).await,
+                _ => {}
+            }
+            ApiError::from(e).into_response()
         }
     }
 }
 
+/// Removes secret, only the creator holding the revocation token is allowed to
 pub async fn remove_secret_route(
     State(state): State<Arc<AppState>>,
+    Extension(client_ip): Extension<ClientIp>,
+    headers: HeaderMap,
     Path(id): Path<String>,
-) -> StatusCode {
-    match state.secret_storage.remove(&id) {
-        Ok(_) => StatusCode::OK,
-        Err(e) => {
-            error!("{}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
+) -> Response {
+    let Some(revocation_token) = headers
+        .get(REVOCATION_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+    else {
+        info!("revocation token is required to remove secret");
+        return ApiError::RevocationTokenRequired.into_response();
+    };
+
+    match remove_secret(state.secret_storage.as_ref(), &id, revocation_token).await {
+        Ok(secret) => {
+            state
+                .audit_log
+                .record(AuditEvent::new(
+                    AuditAction::Delete,
+                    &secret,
+                    &client_ip.0.to_string(),
+                ))
+                .await;
+            let event = SecretEvent::new(SecretAction::Delete, &secret);
+            (StatusCode::OK, Extension(event)).into_response()
         }
+        Err(e) => ApiError::from(e).into_response(),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::model::{AppConfig, IpLimitEntry, IpLimitsConfig};
+    use crate::audit::AuditLog;
+    use crate::audit::event::get_secret_id_hash;
+    use crate::config::model::{AppConfig, DEFAULT_TTL_MAX, IpLimitEntry, IpLimitsConfig};
+    use crate::config::reload::ConfigReloadStatus;
+    use crate::dto::model::ApiErrorDto;
     use crate::limits::LimitsService;
     use crate::metrics::service::MetricsServer;
     use crate::middleware::client_ip::ClientIp;
     use crate::secret::model::{SecretDownloadPolicy, SecretFileMetadata, SecretTTL};
     use crate::secret::storage::MockSecretStorage;
+    use crate::shutdown::ShutdownSignal;
+    use crate::tests::audit::RecordingAuditSink;
+    use crate::tests::config::get_test_app_config;
+    use crate::tests::string::get_random_string;
+    use axum::http::header;
     use std::sync::Arc;
 
     fn create_test_app_state(
         ip_limits_config: Option<IpLimitsConfig>,
         file_upload_enabled: bool,
+    ) -> Arc<AppState> {
+        create_test_app_state_with_audit_log(
+            ip_limits_config,
+            file_upload_enabled,
+            AuditLog::disabled(),
+        )
+    }
+
+    fn create_test_app_state_with_audit_log(
+        ip_limits_config: Option<IpLimitsConfig>,
+        file_upload_enabled: bool,
+        audit_log: AuditLog,
     ) -> Arc<AppState> {
         let config = AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
-            message_max_length: 1024,
             file_upload_enabled,
-            file_max_size: 10485760,
             encrypted_message_max_length: Some(15485760),
-            redis_url: "redis://localhost".to_string(),
             ip_limits: ip_limits_config,
+            ..get_test_app_config()
         };
 
         let limits_service = LimitsService::new(&config);
         let secret_storage = MockSecretStorage::new();
 
-        let body_limit = limits_service
-            .body_limit_as_usize()
-            .expect("Failed to calculate body limit");
-
-        let metrics_server = MetricsServer::new(config.clone(), body_limit);
+        let metrics_server = MetricsServer::new(config.clone(), limits_service.clone(), None);
 
         Arc::new(AppState {
             config,
             limits_service,
             secret_storage: Box::new(secret_storage),
-            body_limit,
             metrics_server,
+            shutdown: ShutdownSignal::new(),
+            config_reload: ConfigReloadStatus::default(),
+            audit_log,
         })
     }
 
@@ -126,21 +232,124 @@ mod tests {
                 r#type: "text/plain".to_string(),
                 size: payload_size as u64,
             },
-            payload: "A".repeat(payload_size), // Simulate encrypted payload
-            ttl: SecretTTL::OneHour,
+            encrypted_metadata: None,
+            payload: "A".repeat(payload_size).into(), // Simulate encrypted payload
+            ttl: Some(SecretTTL::OneHour),
             download_policy: SecretDownloadPolicy::OneTime,
+            remaining_views: None,
+            revocation_token_hash: None,
+            chunks: None,
         }
     }
 
+    fn get_client_ip() -> ClientIp {
+        ClientIp("192.168.1.100".parse().unwrap())
+    }
+
+    async fn get_stored_secret(response: Response) -> StoredSecretDto {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    async fn get_error_code(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let dto: ApiErrorDto = serde_json::from_slice(&body).unwrap();
+        dto.code
+    }
+
+    async fn get_stored_secret_id(response: Response) -> String {
+        get_stored_secret(response).await.id
+    }
+
+    fn get_revocation_headers(revocation_token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(REVOCATION_TOKEN_HEADER, revocation_token.parse().unwrap());
+        headers
+    }
+
+    #[tokio::test]
+    async fn test_store_secret_ignores_client_supplied_id() {
+        let state = create_test_app_state(None, true);
+        let client_ip = ClientIp("192.168.1.100".parse().unwrap());
+        let secret = create_test_secret(SecretContentType::Text, 1000);
+
+        let response = store_secret_route(
+            State(state.clone()),
+            Extension(client_ip.clone()),
+            SecretBody(secret.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let first_id = get_stored_secret_id(response).await;
+        assert_ne!(first_id, secret.id);
+
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let second_id = get_stored_secret_id(response).await;
+        assert_ne!(first_id, second_id);
+    }
+
+    #[tokio::test]
+    async fn test_store_secret_with_id_returns_conflict_for_existing_id() {
+        let state = create_test_app_state(None, true);
+        let client_ip = ClientIp("192.168.1.100".parse().unwrap());
+        let secret = create_test_secret(SecretContentType::Text, 1000);
+
+        let response = store_secret_with_id_route(
+            State(state.clone()),
+            Extension(client_ip.clone()),
+            ApiJson(secret.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(get_stored_secret_id(response).await, secret.id);
+
+        let response =
+            store_secret_with_id_route(State(state), Extension(client_ip), ApiJson(secret)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_store_secret_with_id_requires_id() {
+        let state = create_test_app_state(None, true);
+        let client_ip = ClientIp("192.168.1.100".parse().unwrap());
+        let mut secret = create_test_secret(SecretContentType::Text, 1000);
+        secret.id = String::new();
+
+        let response =
+            store_secret_with_id_route(State(state), Extension(client_ip), ApiJson(secret)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_store_secret_with_ttl_out_of_range() {
+        let state = create_test_app_state(None, true);
+        let client_ip = ClientIp("192.168.1.100".parse().unwrap());
+        let mut secret = create_test_secret(SecretContentType::Text, 1000);
+        secret.ttl = Some(SecretTTL::Seconds(DEFAULT_TTL_MAX + 1));
+
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     #[tokio::test]
     async fn test_store_text_secret_with_default_limits() {
         let state = create_test_app_state(None, true);
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::Text, 1000); // Within default encrypted limit
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
-        assert_eq!(response, StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
@@ -149,9 +358,11 @@ mod tests {
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::Text, 20_000_000); // Exceeds default encrypted limit
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
-        assert_eq!(response, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(get_error_code(response).await, "payload_too_large");
     }
 
     #[tokio::test]
@@ -161,7 +372,8 @@ mod tests {
             whitelist: vec![IpLimitEntry {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192), // Increased from 1024
-                file_max_size: Some(4096000),   // 4MB instead of 100MB
+                file_max_size: Some(4096000),   // 4MB instead of 100MB,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -172,9 +384,10 @@ mod tests {
         // Expected: max(8192, 4096000) * 1.35 = 4096000 * 1.35 = 5529600
         let secret = create_test_secret(SecretContentType::Text, 3_000_000); // Within increased limit (3MB)
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
-        assert_eq!(response, StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
@@ -184,7 +397,8 @@ mod tests {
             whitelist: vec![IpLimitEntry {
                 ip: "192.168.0.0/16".to_string(),
                 message_max_length: Some(4096),
-                file_max_size: Some(2048000), // 2MB instead of 50MB
+                file_max_size: Some(2048000), // 2MB instead of 50MB,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -195,9 +409,10 @@ mod tests {
         // Expected: max(4096, 2048000) * 1.35 = 2048000 * 1.35 = 2764800
         let secret = create_test_secret(SecretContentType::Text, 1_500_000); // Within CIDR limit (1.5MB)
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
-        assert_eq!(response, StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
@@ -208,6 +423,7 @@ mod tests {
                 ip: "10.0.0.0/8".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -216,10 +432,11 @@ mod tests {
         let client_ip = ClientIp("192.168.1.100".parse().unwrap()); // Doesn't match whitelist
         let secret = create_test_secret(SecretContentType::Text, 20_000_000); // Exceeds default limit
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
         // Should fail because IP doesn't match and falls back to default limits
-        assert_eq!(response, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
 
     #[tokio::test]
@@ -228,9 +445,10 @@ mod tests {
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::File, 1000);
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
-        assert_eq!(response, StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
@@ -239,9 +457,37 @@ mod tests {
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::File, 1000);
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(get_error_code(response).await, "file_upload_disabled");
+    }
+
+    #[tokio::test]
+    async fn test_store_secret_with_encrypted_metadata_gated_by_content_type() {
+        let state = create_test_app_state(None, false);
+        let client_ip = ClientIp("192.168.1.100".parse().unwrap());
+
+        let mut secret = create_test_secret(SecretContentType::File, 1000);
+        secret.metadata = SecretFileMetadata::default();
+        secret.encrypted_metadata = Some(vec![7; 64].into());
+
+        let response = store_secret_route(
+            State(state.clone()),
+            Extension(client_ip.clone()),
+            SecretBody(secret.clone()),
+        )
+        .await;
+
+        assert_eq!(get_error_code(response).await, "file_upload_disabled");
 
-        assert_eq!(response, StatusCode::BAD_REQUEST);
+        secret.content_type = SecretContentType::Text;
+
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
@@ -252,6 +498,7 @@ mod tests {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -260,10 +507,11 @@ mod tests {
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::File, 1000);
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
         // Should still be rejected due to global file upload setting
-        assert_eq!(response, StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
     }
 
     #[tokio::test]
@@ -274,6 +522,7 @@ mod tests {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(8192),
                 file_max_size: Some(104857600),
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -282,10 +531,11 @@ mod tests {
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::Text, 20_000_000); // Exceeds default
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
         // Should fail because IP limits are disabled, so default limits apply
-        assert_eq!(response, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
 
     #[tokio::test]
@@ -295,7 +545,8 @@ mod tests {
             whitelist: vec![IpLimitEntry {
                 ip: "192.168.1.100".to_string(),
                 message_max_length: Some(2048),
-                file_max_size: Some(1024000), // 1MB instead of 100MB
+                file_max_size: Some(1024000), // 1MB instead of 100MB,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -312,19 +563,19 @@ mod tests {
         let response = store_secret_route(
             State(state.clone()),
             Extension(client_ip.clone()),
-            Json(secret),
+            SecretBody(secret),
         )
         .await;
 
-        assert_eq!(response, StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
 
         // Test just over the limit
         let secret_over = create_test_secret(SecretContentType::Text, encrypted_limit + 1);
 
         let response_over =
-            store_secret_route(State(state), Extension(client_ip), Json(secret_over)).await;
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret_over)).await;
 
-        assert_eq!(response_over, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response_over.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
 
     #[tokio::test]
@@ -334,7 +585,8 @@ mod tests {
             whitelist: vec![IpLimitEntry {
                 ip: "2001:db8::/32".to_string(),
                 message_max_length: Some(16384),
-                file_max_size: Some(2048000), // 2MB instead of 200MB
+                file_max_size: Some(2048000), // 2MB instead of 200MB,
+                rate_limits: None,
             }],
             trusted_proxies: vec![],
         };
@@ -345,9 +597,10 @@ mod tests {
         // Dynamic calculation: max(16384, 2048000) * 1.35 = 2764800
         let secret = create_test_secret(SecretContentType::Text, 1_500_000); // 1.5MB
 
-        let response = store_secret_route(State(state), Extension(client_ip), Json(secret)).await;
+        let response =
+            store_secret_route(State(state), Extension(client_ip), SecretBody(secret)).await;
 
-        assert_eq!(response, StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[ignore]
@@ -358,25 +611,127 @@ mod tests {
         // First store a secret
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::Text, 1000);
-        let secret_id = secret.id.clone();
 
-        let store_response =
-            store_secret_route(State(state.clone()), Extension(client_ip), Json(secret)).await;
-        assert_eq!(store_response, StatusCode::OK);
+        let store_response = store_secret_route(
+            State(state.clone()),
+            Extension(client_ip),
+            SecretBody(secret),
+        )
+        .await;
+        assert_eq!(store_response.status(), StatusCode::OK);
+        let secret_id = get_stored_secret_id(store_response).await;
 
         // Then try to get it
-        let response = get_secret_route(State(state), Path(secret_id)).await;
+        let response = get_secret_route(
+            State(state),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(secret_id),
+        )
+        .await;
         let response = response.into_response();
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_get_secret_route_returns_remaining_views() {
+        let state = create_test_app_state(None, true);
+
+        let client_ip = ClientIp("192.168.1.100".parse().unwrap());
+        let mut secret = create_test_secret(SecretContentType::Text, 1000);
+        secret.download_policy = SecretDownloadPolicy::MaxViews(2);
+
+        let store_response = store_secret_route(
+            State(state.clone()),
+            Extension(client_ip),
+            SecretBody(secret),
+        )
+        .await;
+        let secret_id = get_stored_secret_id(store_response).await;
+
+        let response = get_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(secret_id.clone()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["remainingViews"], 1);
+        assert_eq!(json["downloadPolicy"]["MaxViews"], 2);
+
+        let response = get_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(secret_id.clone()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get_secret_route(
+            State(state),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(secret_id),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn test_get_secret_route_nonexistent() {
         let state = create_test_app_state(None, true);
 
-        let response = get_secret_route(State(state), Path("nonexistent-id".to_string())).await;
-        let response = response.into_response();
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = get_secret_route(
+            State(state),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path("nonexistent-id".to_string()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(get_error_code(response).await, "secret_not_found");
+    }
+
+    #[tokio::test]
+    async fn test_get_secret_route_bans_client_after_failed_lookups() {
+        let state = create_test_app_state(None, true);
+        let max_failures = state.config.lookup_guard.max_failures;
+
+        for _ in 0..max_failures {
+            let response = get_secret_route(
+                State(state.clone()),
+                Extension(get_client_ip()),
+                HeaderMap::new(),
+                Path(get_random_string()),
+            )
+            .await
+            .into_response();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+
+        let response = get_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(get_random_string()),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+
+        let metrics = state.metrics_server.get_metrics().await;
+        assert_eq!(metrics.secrets.lookup_failures_total, max_failures as u64);
     }
 
     #[tokio::test]
@@ -386,21 +741,138 @@ mod tests {
         // First store a secret
         let client_ip = ClientIp("192.168.1.100".parse().unwrap());
         let secret = create_test_secret(SecretContentType::Text, 1000);
-        let secret_id = secret.id.clone();
 
-        let _store_response =
-            store_secret_route(State(state.clone()), Extension(client_ip), Json(secret)).await;
+        let store_response = store_secret_route(
+            State(state.clone()),
+            Extension(client_ip),
+            SecretBody(secret),
+        )
+        .await;
+        let stored = get_stored_secret(store_response).await;
 
         // Then remove it
-        let response = remove_secret_route(State(state), Path(secret_id)).await;
-        assert_eq!(response, StatusCode::OK);
+        let response = remove_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            get_revocation_headers(&stored.revocation_token),
+            Path(stored.id.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get_secret_route(
+            State(state),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(stored.id),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_remove_secret_route_requires_revocation_token() {
+        let state = create_test_app_state(None, true);
+
+        let client_ip = ClientIp("192.168.1.100".parse().unwrap());
+        let secret = create_test_secret(SecretContentType::Text, 1000);
+
+        let store_response = store_secret_route(
+            State(state.clone()),
+            Extension(client_ip),
+            SecretBody(secret),
+        )
+        .await;
+        let secret_id = get_stored_secret_id(store_response).await;
+
+        let response = remove_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(secret_id.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = remove_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            get_revocation_headers("wrong-token"),
+            Path(secret_id.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get_secret_route(
+            State(state),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(secret_id),
+        )
+        .await
+        .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
     }
 
     #[tokio::test]
     async fn test_remove_secret_route_nonexistent() {
         let state = create_test_app_state(None, true);
 
-        let response = remove_secret_route(State(state), Path("nonexistent-id".to_string())).await;
-        assert_eq!(response, StatusCode::OK); // Redis doesn't error on removing non-existent keys
+        let response = remove_secret_route(
+            State(state),
+            Extension(get_client_ip()),
+            get_revocation_headers("token"),
+            Path("nonexistent-id".to_string()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_secret_lifecycle_is_audited() {
+        let sink = Arc::new(RecordingAuditSink::default());
+        let state =
+            create_test_app_state_with_audit_log(None, true, AuditLog::with_sink(sink.clone()));
+        let secret = create_test_secret(SecretContentType::Text, 1000);
+
+        let store_response = store_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            SecretBody(secret),
+        )
+        .await;
+        let stored = get_stored_secret(store_response).await;
+
+        let response = get_secret_route(
+            State(state.clone()),
+            Extension(get_client_ip()),
+            HeaderMap::new(),
+            Path(stored.id.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // One-time secret is gone, nothing to remove
+        let response = remove_secret_route(
+            State(state),
+            Extension(get_client_ip()),
+            get_revocation_headers(&stored.revocation_token),
+            Path(stored.id.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let events = sink.events.lock().unwrap();
+        let actions: Vec<AuditAction> = events.iter().map(|event| event.action).collect();
+        assert_eq!(actions, vec![AuditAction::Create, AuditAction::Read]);
+
+        for event in events.iter() {
+            assert_eq!(event.secret_id_hash, get_secret_id_hash(&stored.id));
+            assert_eq!(event.client_ip.as_deref(), Some("192.168.1.100"));
+            assert_eq!(event.size, Some(1000));
+            assert_eq!(event.ttl_seconds, Some(3600));
+            assert_eq!(event.download_policy, Some(SecretDownloadPolicy::OneTime));
+        }
     }
 }
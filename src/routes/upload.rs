@@ -0,0 +1,213 @@
+use crate::AppState;
+use crate::audit::{AuditAction, AuditEvent};
+use crate::dto::model::StoredSecretDto;
+use crate::metrics::traffic::{SecretAction, SecretEvent};
+use crate::middleware::client_ip::ClientIp;
+use crate::routes::error::ApiError;
+use crate::routes::json::ApiJson;
+use crate::routes::secret::REVOCATION_TOKEN_HEADER;
+use crate::secret::upload::{
+    FileRange, LoadFileError, SecretUploadRequest, complete_upload, get_chunk_parts,
+    is_last_download, load_file, load_file_chunk, parse_range, remove_file, start_upload,
+    upload_chunk,
+};
+use crate::secret::usecase::{TtlLimits, generate_secret_id};
+use axum::Json;
+use axum::body::{Body, Bytes};
+use axum::extract::{Extension, Path, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use futures_util::{StreamExt, stream};
+use log::{error, info};
+use std::sync::Arc;
+
+pub const FILE_KEY_HEADER: &str = "x-file-key";
+
+fn get_header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+}
+
+/// Starts chunked file upload, the returned revocation token authorizes chunk uploads
+pub async fn start_upload_route(
+    State(state): State<Arc<AppState>>,
+    Extension(client_ip): Extension<ClientIp>,
+    ApiJson(request): ApiJson<SecretUploadRequest>,
+) -> Response {
+    if !state.config.file_upload_enabled {
+        info!("file upload is disabled");
+        return ApiError::FileUploadDisabled.into_response();
+    }
+
+    let client_ip_str = client_ip.0.to_string();
+    let client_limits = state.limits_service.get_limits_for_ip(&client_ip_str);
+
+    info!(
+        "upload request from {}: applying encrypted_message_max_length: {}, chunk_max_size: {}",
+        client_ip_str, client_limits.encrypted_message_max_length, client_limits.chunk_max_size
+    );
+
+    let id = generate_secret_id();
+
+    match start_upload(
+        state.secret_storage.as_ref(),
+        &id,
+        &request,
+        &client_limits,
+        &TtlLimits::from(&state.config),
+    )
+    .await
+    {
+        Ok(revocation_token) => {
+            let dto = StoredSecretDto {
+                id,
+                revocation_token,
+            };
+            (StatusCode::OK, Json(dto)).into_response()
+        }
+        Err(e) => {
+            error!("failed to start upload for client {}: {}", client_ip_str, e);
+            ApiError::from(e).into_response()
+        }
+    }
+}
+
+/// Stores encrypted chunk sent as raw body
+pub async fn upload_chunk_route(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path((id, index)): Path<(String, u32)>,
+    body: Bytes,
+) -> Response {
+    let Some(revocation_token) = get_header(&headers, REVOCATION_TOKEN_HEADER) else {
+        info!("revocation token is required to upload chunk");
+        return ApiError::RevocationTokenRequired.into_response();
+    };
+
+    match upload_chunk(
+        state.secret_storage.as_ref(),
+        &id,
+        revocation_token,
+        index,
+        &body,
+    )
+    .await
+    {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
+
+/// Makes uploaded file available to readers once all chunks are stored
+pub async fn complete_upload_route(
+    State(state): State<Arc<AppState>>,
+    Extension(client_ip): Extension<ClientIp>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let Some(revocation_token) = get_header(&headers, REVOCATION_TOKEN_HEADER) else {
+        info!("revocation token is required to complete upload");
+        return ApiError::RevocationTokenRequired.into_response();
+    };
+
+    match complete_upload(state.secret_storage.as_ref(), &id, revocation_token).await {
+        Ok(secret) => {
+            state
+                .audit_log
+                .record(AuditEvent::new(
+                    AuditAction::Create,
+                    &secret,
+                    &client_ip.0.to_string(),
+                ))
+                .await;
+            let event = SecretEvent::new(SecretAction::Create, &secret);
+            (StatusCode::OK, Extension(event)).into_response()
+        }
+        Err(e) => ApiError::from(e).into_response(),
+    }
+}
+
+/// Streams file of chunked secret, a single `Range` is supported.
+///
+/// File key is returned with the secret, so only readers who viewed the secret can download it.
+pub async fn get_file_route(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+) -> Response {
+    let Some(file_key) = get_header(&headers, FILE_KEY_HEADER) else {
+        info!("file key is required to download file");
+        return ApiError::FileKeyRequired.into_response();
+    };
+
+    let chunks = match load_file(state.secret_storage.as_ref(), &id, file_key).await {
+        Ok(chunks) => chunks,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    let last_download = match is_last_download(state.secret_storage.as_ref(), &id, &chunks).await {
+        Ok(last_download) => last_download,
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    let (status, range) = match get_header(&headers, header::RANGE.as_str()) {
+        Some(value) => match parse_range(value, chunks.size) {
+            Ok(range) => (StatusCode::PARTIAL_CONTENT, range),
+            Err(e) => return ApiError::from(e).into_response(),
+        },
+        None => (StatusCode::OK, FileRange::full(chunks.size)),
+    };
+
+    let size = chunks.size;
+    let parts = get_chunk_parts(&chunks, &range);
+
+    // Interrupted downloads may be resumed, the file is removed once its last byte is sent
+    let remove_after_index = (last_download && range.end + 1 == size)
+        .then(|| parts.last().map(|part| part.index))
+        .flatten();
+
+    let body = stream::iter(parts).then(move |part| {
+        let state = state.clone();
+        let id = id.clone();
+        let chunks = chunks.clone();
+        async move {
+            let data =
+                load_file_chunk(state.secret_storage.as_ref(), &id, &chunks, part.index).await?;
+            let data = match data.get(part.from..part.to) {
+                Some(data) => Bytes::copy_from_slice(data),
+                None => {
+                    error!("chunk {} of secret '{id}' has unexpected size", part.index);
+                    return Err(LoadFileError::Storage);
+                }
+            };
+
+            if remove_after_index == Some(part.index) {
+                info!("file of used up secret '{id}' has been downloaded, remove it");
+                remove_file(state.secret_storage.as_ref(), &id, &chunks).await;
+            }
+
+            Ok(data)
+        }
+    });
+
+    let mut response = (
+        status,
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_LENGTH, range.get_length().to_string()),
+            (header::ACCEPT_RANGES, "bytes".to_string()),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response();
+
+    if status == StatusCode::PARTIAL_CONTENT
+        && let Ok(value) = format!("bytes {}-{}/{}", range.start, range.end, size).parse()
+    {
+        response.headers_mut().insert(header::CONTENT_RANGE, value);
+    }
+
+    response
+}
@@ -0,0 +1,916 @@
+use crate::audit::{AuditEvent, AuditLog};
+use crate::config::model::LookupGuardConfig;
+use crate::secret::lookup_guard::LookupFailureRecord;
+use crate::secret::model::Secret;
+use crate::secret::record::RecordCodec;
+use crate::secret::storage::{SecretExpiredError, SecretStorage};
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use log::{debug, error, info, warn};
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const SECRETS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("secrets");
+const LOOKUP_FAILURES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("lookup-failures");
+
+/// Chunk value is expiration unix timestamp (8 bytes, big-endian) followed by chunk data
+const CHUNKS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("chunks");
+const CHUNK_HEADER_LENGTH: usize = 8;
+
+/// Secret value is expiration unix timestamp (8 bytes, big-endian) followed by
+/// `secret::record` encoding, JSON records of earlier versions are still readable
+const SECRET_HEADER_LENGTH: usize = 8;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EmbeddedSecretRecord {
+    /// Unix timestamp in seconds
+    expires_at: i64,
+    secret: Secret,
+}
+
+impl EmbeddedSecretRecord {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+
+    fn encode(&self, codec: &RecordCodec) -> anyhow::Result<Vec<u8>> {
+        let secret = codec.encode(&self.secret)?;
+
+        let mut value = Vec::with_capacity(SECRET_HEADER_LENGTH + secret.len());
+        value.extend_from_slice(&self.expires_at.to_be_bytes());
+        value.extend_from_slice(&secret);
+
+        Ok(value)
+    }
+}
+
+/// Expiration of JSON records written by earlier versions
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyRecordExpiration {
+    expires_at: i64,
+}
+
+/// On-disk secret storage for installations without Redis.
+///
+/// Every operation runs inside a single redb write transaction, so TTL checks and
+/// one-time download removal are atomic the same way as the Lua script in `RedisSecretStorage`.
+#[derive(Clone)]
+pub struct EmbeddedSecretStorage {
+    db: Arc<Database>,
+    codec: RecordCodec,
+}
+
+impl EmbeddedSecretStorage {
+    pub fn open(path: &str) -> anyhow::Result<EmbeddedSecretStorage> {
+        info!("open embedded storage '{path}'");
+
+        let db = Database::create(Path::new(path))
+            .with_context(|| format!("unable to open embedded storage '{path}'"))?;
+
+        let txn = db.begin_write()?;
+        txn.open_table(SECRETS_TABLE)?;
+        txn.open_table(LOOKUP_FAILURES_TABLE)?;
+        txn.open_table(CHUNKS_TABLE)?;
+        txn.commit()?;
+
+        Ok(EmbeddedSecretStorage {
+            db: Arc::new(db),
+            codec: RecordCodec::default(),
+        })
+    }
+
+    pub fn with_codec(self, codec: RecordCodec) -> EmbeddedSecretStorage {
+        EmbeddedSecretStorage { codec, ..self }
+    }
+
+    /// Removes expired secrets periodically, every removal is audited
+    pub fn spawn_sweeper(&self, interval: Duration, audit_log: AuditLog) -> JoinHandle<()> {
+        let storage = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                let removed_ids = match storage.remove_expired().await {
+                    Ok(removed_ids) => removed_ids,
+                    Err(e) => {
+                        error!("unable to remove expired secrets: {}", e);
+                        continue;
+                    }
+                };
+
+                match removed_ids.len() {
+                    0 => debug!("no expired secrets found"),
+                    removed => info!("removed expired secrets: {removed}"),
+                }
+
+                for id in &removed_ids {
+                    audit_log.record(AuditEvent::expired(id)).await;
+                }
+            }
+        })
+    }
+
+    /// Returns ids of removed secrets. Only expiration headers are read, so records
+    /// with missing storage keys are removed too
+    pub async fn remove_expired(&self) -> anyhow::Result<Vec<String>> {
+        let db = self.db.clone();
+
+        run_blocking(move || {
+            let now = now_timestamp();
+            let mut removed = Vec::new();
+
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(SECRETS_TABLE)?;
+                table.retain(|id, value| match decode_expires_at(value) {
+                    Ok(expires_at) if expires_at <= now => {
+                        removed.push(id.to_string());
+                        false
+                    }
+                    Ok(_) => true,
+                    Err(e) => {
+                        warn!("remove invalid secret record '{id}': {}", e);
+                        removed.push(id.to_string());
+                        false
+                    }
+                })?;
+
+                let mut lookup_failures = txn.open_table(LOOKUP_FAILURES_TABLE)?;
+                lookup_failures.retain(|_, value| {
+                    decode_lookup_failure_record(value)
+                        .map(|record| !record.is_expired(now))
+                        .unwrap_or(false)
+                })?;
+
+                let mut chunks = txn.open_table(CHUNKS_TABLE)?;
+                chunks.retain(|_, value| {
+                    decode_chunk_expires_at(value)
+                        .map(|expires_at| expires_at > now)
+                        .unwrap_or(false)
+                })?;
+            }
+            txn.commit()?;
+
+            Ok(removed)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl SecretStorage for EmbeddedSecretStorage {
+    async fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<bool> {
+        info!("store secret: {}", secret);
+
+        let db = self.db.clone();
+        let id = id.to_string();
+        let ttl_seconds = secret.ttl_seconds().context("secret ttl isn't set")?;
+        let record = EmbeddedSecretRecord {
+            expires_at: now_timestamp() + ttl_seconds as i64,
+            secret: secret.clone(),
+        };
+        let value = record.encode(&self.codec)?;
+
+        run_blocking(move || {
+            let now = now_timestamp();
+
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(SECRETS_TABLE)?;
+
+                // Undecodable records can't be loaded anyway, so they are overwritten
+                let exists = match table.get(id.as_str())? {
+                    Some(existing) => {
+                        decode_expires_at(existing.value()).is_ok_and(|expires_at| expires_at > now)
+                    }
+                    None => false,
+                };
+
+                if exists {
+                    info!("secret with id '{id}' already exists, skip");
+                    return Ok(false);
+                }
+
+                table.insert(id.as_str(), value.as_slice())?;
+            }
+            txn.commit()?;
+
+            info!("stored secret with id '{id}'");
+
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn load(&self, id: &str) -> anyhow::Result<Option<Secret>> {
+        info!("load secret by id '{id}'..");
+
+        let db = self.db.clone();
+        let id = id.to_string();
+        let codec = self.codec.clone();
+
+        run_blocking(move || {
+            let now = now_timestamp();
+
+            let txn = db.begin_write()?;
+            let (expired, secret) = {
+                let mut table = txn.open_table(SECRETS_TABLE)?;
+
+                let record = match table.get(id.as_str())? {
+                    Some(value) => Some(decode_record(value.value(), &codec)?),
+                    None => None,
+                };
+
+                match record {
+                    Some(record) if record.is_expired(now) => {
+                        table.remove(id.as_str())?;
+                        (true, None)
+                    }
+                    Some(mut record) => {
+                        if record.secret.consume_view() {
+                            table.remove(id.as_str())?;
+                        } else if record.secret.remaining_views.is_some() {
+                            let value = record.encode(&codec)?;
+                            table.insert(id.as_str(), value.as_slice())?;
+                        }
+                        (false, Some(record.secret))
+                    }
+                    None => (false, None),
+                }
+            };
+            txn.commit()?;
+
+            if expired {
+                info!("secret with id '{id}' has expired");
+                return Err(SecretExpiredError { id }.into());
+            }
+
+            match &secret {
+                Some(_) => info!("secret has been found"),
+                None => info!("secret wasn't found by id '{id}'"),
+            }
+
+            Ok(secret)
+        })
+        .await
+    }
+
+    async fn remove(
+        &self,
+        id: &str,
+        revocation_token_hash: &str,
+    ) -> anyhow::Result<Option<Secret>> {
+        info!("remove secret by id '{id}'..");
+
+        let db = self.db.clone();
+        let id = id.to_string();
+        let revocation_token_hash = revocation_token_hash.to_string();
+        let codec = self.codec.clone();
+
+        run_blocking(move || {
+            let now = now_timestamp();
+
+            let txn = db.begin_write()?;
+            let removed = {
+                let mut table = txn.open_table(SECRETS_TABLE)?;
+
+                let record = match table.get(id.as_str())? {
+                    Some(value) => Some(decode_record(value.value(), &codec)?),
+                    None => None,
+                };
+
+                match record {
+                    Some(record)
+                        if !record.is_expired(now)
+                            && record.secret.is_revocable_by(&revocation_token_hash) =>
+                    {
+                        table.remove(id.as_str())?;
+                        Some(record.secret)
+                    }
+                    _ => None,
+                }
+            };
+            txn.commit()?;
+
+            match &removed {
+                Some(_) => info!("secret with id '{id}' has been removed"),
+                None => info!("secret wasn't found by id '{id}' and revocation token"),
+            }
+
+            Ok(removed)
+        })
+        .await
+    }
+
+    async fn get_lookup_ban(&self, client_ip: &str) -> anyhow::Result<Option<u64>> {
+        let db = self.db.clone();
+        let client_ip = client_ip.to_string();
+
+        run_blocking(move || {
+            let txn = db.begin_read()?;
+            let table = txn.open_table(LOOKUP_FAILURES_TABLE)?;
+
+            let ban_seconds_left = match table.get(client_ip.as_str())? {
+                Some(value) => decode_lookup_failure_record(value.value())?
+                    .get_ban_seconds_left(now_timestamp()),
+                None => None,
+            };
+
+            Ok(ban_seconds_left)
+        })
+        .await
+    }
+
+    async fn register_lookup_failure(
+        &self,
+        client_ip: &str,
+        config: &LookupGuardConfig,
+    ) -> anyhow::Result<Option<u64>> {
+        let db = self.db.clone();
+        let client_ip = client_ip.to_string();
+        let config = config.clone();
+
+        run_blocking(move || {
+            let txn = db.begin_write()?;
+            let ban_seconds = {
+                let mut table = txn.open_table(LOOKUP_FAILURES_TABLE)?;
+
+                let mut record = match table.get(client_ip.as_str())? {
+                    Some(value) => decode_lookup_failure_record(value.value())?,
+                    None => LookupFailureRecord::default(),
+                };
+
+                let ban_seconds = record.register_failure(now_timestamp(), &config);
+
+                let value = serde_json::to_vec(&record).context("record serialization error")?;
+                table.insert(client_ip.as_str(), value.as_slice())?;
+
+                ban_seconds
+            };
+            txn.commit()?;
+
+            Ok(ban_seconds)
+        })
+        .await
+    }
+
+    async fn store_chunk(&self, key: &str, data: &[u8], ttl_seconds: u64) -> anyhow::Result<()> {
+        debug!("store chunk '{key}', {} bytes", data.len());
+
+        let db = self.db.clone();
+        let key = key.to_string();
+
+        let expires_at = now_timestamp() + ttl_seconds as i64;
+        let mut value = Vec::with_capacity(CHUNK_HEADER_LENGTH + data.len());
+        value.extend_from_slice(&expires_at.to_be_bytes());
+        value.extend_from_slice(data);
+
+        run_blocking(move || {
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(CHUNKS_TABLE)?;
+                table.insert(key.as_str(), value.as_slice())?;
+            }
+            txn.commit()?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn store_upload_session(
+        &self,
+        key: &str,
+        secret: &Secret,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<()> {
+        let record = self.codec.encode(secret)?;
+        self.store_chunk(key, &record, ttl_seconds).await
+    }
+
+    async fn load_upload_session(&self, key: &str) -> anyhow::Result<Option<Secret>> {
+        self.load_chunk(key)
+            .await?
+            .map(|record| self.codec.decode(&record))
+            .transpose()
+    }
+
+    async fn load_chunk(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let db = self.db.clone();
+        let key = key.to_string();
+
+        run_blocking(move || {
+            let txn = db.begin_read()?;
+            let table = txn.open_table(CHUNKS_TABLE)?;
+
+            let data = match table.get(key.as_str())? {
+                Some(value) if decode_chunk_expires_at(value.value())? > now_timestamp() => {
+                    Some(value.value()[CHUNK_HEADER_LENGTH..].to_vec())
+                }
+                _ => None,
+            };
+
+            Ok(data)
+        })
+        .await
+    }
+
+    async fn get_chunk_size(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let db = self.db.clone();
+        let key = key.to_string();
+
+        run_blocking(move || {
+            let txn = db.begin_read()?;
+            let table = txn.open_table(CHUNKS_TABLE)?;
+
+            let size = match table.get(key.as_str())? {
+                Some(value) if decode_chunk_expires_at(value.value())? > now_timestamp() => {
+                    Some((value.value().len() - CHUNK_HEADER_LENGTH) as u64)
+                }
+                _ => None,
+            };
+
+            Ok(size)
+        })
+        .await
+    }
+
+    async fn remove_chunks(&self, keys: &[String]) -> anyhow::Result<()> {
+        let db = self.db.clone();
+        let keys = keys.to_vec();
+
+        run_blocking(move || {
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(CHUNKS_TABLE)?;
+                for key in &keys {
+                    table.remove(key.as_str())?;
+                }
+            }
+            txn.commit()?;
+
+            Ok(())
+        })
+        .await
+    }
+}
+
+async fn run_blocking<T, F>(task: F) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|e| anyhow!("embedded storage task failed: {}", e))?
+}
+
+fn decode_record(value: &[u8], codec: &RecordCodec) -> anyhow::Result<EmbeddedSecretRecord> {
+    if value.first() == Some(&b'{') {
+        return serde_json::from_slice(value).map_err(|e| {
+            error!("{}", e);
+            anyhow!("unable to deserialize secret")
+        });
+    }
+
+    let header: [u8; SECRET_HEADER_LENGTH] = value
+        .get(..SECRET_HEADER_LENGTH)
+        .and_then(|header| header.try_into().ok())
+        .context("invalid secret record")?;
+
+    Ok(EmbeddedSecretRecord {
+        expires_at: i64::from_be_bytes(header),
+        secret: codec.decode(&value[SECRET_HEADER_LENGTH..])?,
+    })
+}
+
+/// Reads expiration time only, the secret itself may be compressed or encrypted
+fn decode_expires_at(value: &[u8]) -> anyhow::Result<i64> {
+    if value.first() == Some(&b'{') {
+        let record: LegacyRecordExpiration = serde_json::from_slice(value).map_err(|e| {
+            error!("{}", e);
+            anyhow!("unable to deserialize secret")
+        })?;
+        return Ok(record.expires_at);
+    }
+
+    let header: [u8; SECRET_HEADER_LENGTH] = value
+        .get(..SECRET_HEADER_LENGTH)
+        .and_then(|header| header.try_into().ok())
+        .context("invalid secret record")?;
+
+    Ok(i64::from_be_bytes(header))
+}
+
+fn decode_lookup_failure_record(value: &[u8]) -> anyhow::Result<LookupFailureRecord> {
+    serde_json::from_slice(value).map_err(|e| {
+        error!("{}", e);
+        anyhow!("unable to deserialize lookup failure record")
+    })
+}
+
+fn decode_chunk_expires_at(value: &[u8]) -> anyhow::Result<i64> {
+    let header: [u8; CHUNK_HEADER_LENGTH] = value
+        .get(..CHUNK_HEADER_LENGTH)
+        .and_then(|header| header.try_into().ok())
+        .context("invalid chunk record")?;
+
+    Ok(i64::from_be_bytes(header))
+}
+
+fn now_timestamp() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::StorageCompression;
+    use crate::secret::model::SecretDownloadPolicy;
+    use crate::tests::secret::{get_sample_secret, get_sample_storage_keys};
+    use crate::tests::string::get_random_string;
+    use redb::ReadableTableMetadata;
+
+    struct TempPath(std::path::PathBuf);
+
+    impl TempPath {
+        fn as_str(&self) -> &str {
+            self.0.to_str().unwrap()
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn get_storage() -> (TempPath, EmbeddedSecretStorage) {
+        let path =
+            TempPath(std::env::temp_dir().join(format!("pw-test-{}.redb", uuid::Uuid::new_v4())));
+        let storage = EmbeddedSecretStorage::open(path.as_str()).unwrap();
+        (path, storage)
+    }
+
+    fn insert_value(storage: &EmbeddedSecretStorage, id: &str, value: &[u8]) {
+        let txn = storage.db.begin_write().unwrap();
+        {
+            let mut table = txn.open_table(SECRETS_TABLE).unwrap();
+            table.insert(id, value).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    fn get_value(storage: &EmbeddedSecretStorage, id: &str) -> Option<Vec<u8>> {
+        let txn = storage.db.begin_read().unwrap();
+        let table = txn.open_table(SECRETS_TABLE).unwrap();
+        table.get(id).unwrap().map(|value| value.value().to_vec())
+    }
+
+    fn store_expired_secret(storage: &EmbeddedSecretStorage, secret: &Secret) {
+        let record = EmbeddedSecretRecord {
+            expires_at: now_timestamp() - 1,
+            secret: secret.clone(),
+        };
+        let value = record.encode(&RecordCodec::default()).unwrap();
+
+        insert_value(storage, &secret.id, &value);
+    }
+
+    #[tokio::test]
+    async fn secret_with_one_time_download_should_be_removed_after_load() {
+        let (_path, storage) = get_storage();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::OneTime;
+
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+
+        assert_eq!(
+            storage.load(&secret.id).await.unwrap(),
+            Some(secret.clone())
+        );
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn secret_with_unlimited_download_should_not_be_removed_after_load() {
+        let (_path, storage) = get_storage();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::Unlimited;
+
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn secret_with_max_views_should_be_removed_after_last_view() {
+        let (_path, storage) = get_storage();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(2);
+        secret.remaining_views = Some(2);
+
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        let first = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(first.remaining_views, Some(1));
+        let second = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(second.remaining_views, Some(0));
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn secret_with_existing_id_should_not_be_overwritten() {
+        let (_path, storage) = get_storage();
+
+        let secret = get_sample_secret();
+        let mut other_secret = get_sample_secret();
+        other_secret.id = secret.id.clone();
+
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+        assert!(
+            !storage
+                .store(&other_secret.id, &other_secret)
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(storage.load(&secret.id).await.unwrap(), Some(secret));
+    }
+
+    #[tokio::test]
+    async fn expired_secret_should_not_be_loaded() {
+        let (_path, storage) = get_storage();
+
+        let secret = get_sample_secret();
+        store_expired_secret(&storage, &secret);
+
+        let error = storage.load(&secret.id).await.unwrap_err();
+        assert!(error.downcast_ref::<SecretExpiredError>().is_some());
+
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn remove_expired_secrets() {
+        let (_path, storage) = get_storage();
+
+        let expired_secret = get_sample_secret();
+        store_expired_secret(&storage, &expired_secret);
+
+        let secret = get_sample_secret();
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        assert_eq!(
+            storage.remove_expired().await.unwrap(),
+            vec![expired_secret.id.clone()]
+        );
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn remove_secret_test() {
+        let (_path, storage) = get_storage();
+
+        let mut secret = get_sample_secret();
+        secret.revocation_token_hash = Some("token-hash".to_string());
+
+        storage.store(&secret.id, &secret).await.unwrap();
+        assert!(
+            storage
+                .remove(&secret.id, "other-hash")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            storage.remove(&secret.id, "token-hash").await.unwrap(),
+            Some(secret.clone())
+        );
+
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn chunks_test() {
+        let (_path, storage) = get_storage();
+
+        storage.store_chunk("a:0", b"chunk-data", 60).await.unwrap();
+        storage.store_chunk("a:1", b"expired", 0).await.unwrap();
+
+        assert_eq!(storage.get_chunk_size("a:0").await.unwrap(), Some(10));
+        assert_eq!(
+            storage.load_chunk("a:0").await.unwrap(),
+            Some(b"chunk-data".to_vec())
+        );
+        assert!(storage.load_chunk("a:1").await.unwrap().is_none());
+
+        storage.remove_expired().await.unwrap();
+        storage.remove_chunks(&["a:0".to_string()]).await.unwrap();
+
+        assert!(storage.get_chunk_size("a:0").await.unwrap().is_none());
+
+        let txn = storage.db.begin_read().unwrap();
+        assert!(txn.open_table(CHUNKS_TABLE).unwrap().is_empty().unwrap());
+    }
+
+    #[tokio::test]
+    async fn secrets_should_survive_reopen() {
+        let (path, storage) = get_storage();
+
+        let secret = get_sample_secret();
+        storage.store(&secret.id, &secret).await.unwrap();
+        drop(storage);
+
+        let reopened = EmbeddedSecretStorage::open(path.as_str()).unwrap();
+        assert_eq!(reopened.load(&secret.id).await.unwrap(), Some(secret));
+    }
+
+    #[tokio::test]
+    async fn client_ban_should_survive_reopen() {
+        let (path, storage) = get_storage();
+        let config = LookupGuardConfig {
+            max_failures: 1,
+            ..LookupGuardConfig::default()
+        };
+
+        assert_eq!(
+            storage
+                .register_lookup_failure("10.0.0.1", &config)
+                .await
+                .unwrap(),
+            Some(config.ban_seconds)
+        );
+        drop(storage);
+
+        let reopened = EmbeddedSecretStorage::open(path.as_str()).unwrap();
+        assert!(reopened.get_lookup_ban("10.0.0.1").await.unwrap().is_some());
+        assert!(reopened.get_lookup_ban("10.0.0.2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn legacy_json_record_should_be_readable() {
+        let (_path, storage) = get_storage();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(2);
+        secret.remaining_views = Some(2);
+
+        let record = EmbeddedSecretRecord {
+            expires_at: now_timestamp() + 60,
+            secret: secret.clone(),
+        };
+        insert_value(&storage, &secret.id, &serde_json::to_vec(&record).unwrap());
+
+        let loaded = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(loaded.payload, secret.payload);
+        assert_eq!(loaded.remaining_views, Some(1));
+
+        // Updated record is written in the current encoding
+        let value = get_value(&storage, &secret.id).unwrap();
+        assert_ne!(value[0], b'{');
+
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn compressed_secret_should_be_loaded() {
+        let (_path, storage) = get_storage();
+        let storage = storage.with_codec(RecordCodec::new(StorageCompression::Deflate, None));
+
+        let mut secret = get_sample_secret();
+        secret.payload = vec![b'A'; 4096].into();
+
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        assert!(get_value(&storage, &secret.id).unwrap().len() < 1024);
+        assert_eq!(storage.load(&secret.id).await.unwrap(), Some(secret));
+    }
+
+    #[tokio::test]
+    async fn encrypted_secret_should_be_loaded_after_key_rotation() {
+        let (_path, storage) = get_storage();
+        let old_storage = storage.clone().with_codec(RecordCodec::new(
+            StorageCompression::None,
+            Some(get_sample_storage_keys("k1", &["k1"])),
+        ));
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(2);
+        secret.remaining_views = Some(2);
+        secret.metadata.name = "confidential-report.pdf".to_string();
+
+        old_storage.store(&secret.id, &secret).await.unwrap();
+
+        let value = get_value(&storage, &secret.id).unwrap();
+        assert!(
+            !value
+                .windows(secret.metadata.name.len())
+                .any(|window| window == secret.metadata.name.as_bytes())
+        );
+        assert!(storage.load(&secret.id).await.is_err());
+
+        let storage = storage.with_codec(RecordCodec::new(
+            StorageCompression::None,
+            Some(get_sample_storage_keys("k2", &["k1", "k2"])),
+        ));
+
+        let loaded = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(loaded.remaining_views, Some(1));
+
+        // Updated record is encrypted with the current key
+        assert!(old_storage.load(&secret.id).await.is_err());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn upload_session_should_be_encrypted() {
+        let (_path, storage) = get_storage();
+        let storage = storage.with_codec(RecordCodec::new(
+            StorageCompression::None,
+            Some(get_sample_storage_keys("k1", &["k1"])),
+        ));
+
+        let mut secret = get_sample_secret();
+        secret.metadata.name = "confidential-report.pdf".to_string();
+
+        storage
+            .store_upload_session("upload", &secret, 60)
+            .await
+            .unwrap();
+
+        let value = storage.load_chunk("upload").await.unwrap().unwrap();
+        assert!(
+            !value
+                .windows(secret.metadata.name.len())
+                .any(|window| window == secret.metadata.name.as_bytes())
+        );
+        assert_eq!(
+            storage.load_upload_session("upload").await.unwrap(),
+            Some(secret)
+        );
+    }
+
+    #[tokio::test]
+    async fn expired_secret_should_be_removed_without_storage_key() {
+        let (_path, storage) = get_storage();
+        let encrypted_storage = storage.clone().with_codec(RecordCodec::new(
+            StorageCompression::None,
+            Some(get_sample_storage_keys("k1", &["k1"])),
+        ));
+
+        let secret = get_sample_secret();
+        encrypted_storage.store(&secret.id, &secret).await.unwrap();
+
+        let mut value = get_value(&storage, &secret.id).unwrap();
+        assert!(storage.remove_expired().await.unwrap().is_empty());
+
+        value[..SECRET_HEADER_LENGTH].copy_from_slice(&(now_timestamp() - 1).to_be_bytes());
+        insert_value(&storage, &secret.id, &value);
+
+        assert_eq!(
+            storage.remove_expired().await.unwrap(),
+            vec![secret.id.clone()]
+        );
+        assert!(get_value(&storage, &secret.id).is_none());
+    }
+
+    #[tokio::test]
+    async fn undecodable_secret_should_be_overwritten_after_expiration() {
+        let (_path, storage) = get_storage();
+        let secret = get_sample_secret();
+
+        let mut value = (now_timestamp() + 60).to_be_bytes().to_vec();
+        value.extend_from_slice(&[0xff; 16]);
+        insert_value(&storage, &secret.id, &value);
+
+        assert!(!storage.store(&secret.id, &secret).await.unwrap());
+
+        value[..SECRET_HEADER_LENGTH].copy_from_slice(&(now_timestamp() - 1).to_be_bytes());
+        insert_value(&storage, &secret.id, &value);
+
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+        assert_eq!(storage.load(&secret.id).await.unwrap(), Some(secret));
+    }
+
+    #[tokio::test]
+    async fn return_none_for_unknown_secret() {
+        let (_path, storage) = get_storage();
+        assert!(storage.load(&get_random_string()).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::config::model::{StorageEncryptionConfig, StorageKeyConfig};
+use anyhow::{Context, anyhow};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::collections::HashMap;
+use std::fs;
+
+/// AES-256-GCM keys of storage records, encrypted data is `nonce || ciphertext || tag`
+pub struct StorageKeys {
+    /// Key of new records, `None` keeps them unencrypted while old ones are still readable
+    write_key_id: Option<String>,
+
+    keys: HashMap<String, LessSafeKey>,
+
+    random: SystemRandom,
+}
+
+impl StorageKeys {
+    /// Returns `None` when no keys are configured
+    pub fn from_config(config: &StorageEncryptionConfig) -> anyhow::Result<Option<Self>> {
+        if config.keys.is_empty() {
+            return Ok(None);
+        }
+
+        let mut keys = HashMap::with_capacity(config.keys.len());
+
+        for key_config in &config.keys {
+            let key = load_key(key_config)
+                .with_context(|| format!("unable to load storage key '{}'", key_config.id))?;
+            keys.insert(key_config.id.clone(), key);
+        }
+
+        let write_key_id = if config.enabled {
+            if !keys.contains_key(&config.key_id) {
+                return Err(anyhow!("storage key '{}' isn't configured", config.key_id));
+            }
+            Some(config.key_id.clone())
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            write_key_id,
+            keys,
+            random: SystemRandom::new(),
+        }))
+    }
+
+    pub fn write_key_id(&self) -> Option<&str> {
+        self.write_key_id.as_deref()
+    }
+
+    pub fn encrypt(&self, key_id: &str, aad: &[u8], data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let key = self.get_key(key_id)?;
+
+        let mut nonce = [0u8; NONCE_LEN];
+        self.random
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("unable to generate nonce"))?;
+
+        let mut in_out = data.to_vec();
+        key.seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut in_out,
+        )
+        .map_err(|_| anyhow!("unable to encrypt secret record"))?;
+
+        let mut encrypted = Vec::with_capacity(NONCE_LEN + in_out.len());
+        encrypted.extend_from_slice(&nonce);
+        encrypted.extend_from_slice(&in_out);
+
+        Ok(encrypted)
+    }
+
+    pub fn decrypt(&self, key_id: &str, aad: &[u8], encrypted: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let key = self.get_key(key_id)?;
+
+        if encrypted.len() < NONCE_LEN {
+            return Err(anyhow!("encrypted secret record is truncated"));
+        }
+        let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce)
+            .map_err(|_| anyhow!("invalid secret record nonce"))?;
+
+        let mut in_out = ciphertext.to_vec();
+        let data = key
+            .open_in_place(nonce, Aad::from(aad), &mut in_out)
+            .map_err(|_| anyhow!("unable to decrypt secret record with key '{key_id}'"))?;
+
+        Ok(data.to_vec())
+    }
+
+    fn get_key(&self, key_id: &str) -> anyhow::Result<&LessSafeKey> {
+        self.keys
+            .get(key_id)
+            .ok_or_else(|| anyhow!("storage key '{key_id}' isn't configured"))
+    }
+}
+
+fn load_key(config: &StorageKeyConfig) -> anyhow::Result<LessSafeKey> {
+    let encoded = match (&config.key, &config.key_file) {
+        (Some(key), None) => key.clone(),
+        (None, Some(path)) => fs::read_to_string(path).context("unable to read key file")?,
+        _ => return Err(anyhow!("either key or key-file must be set")),
+    };
+
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .context("key must be base64 encoded")?;
+
+    let key = UnboundKey::new(&AES_256_GCM, &bytes)
+        .map_err(|_| anyhow!("key must be {} bytes long", AES_256_GCM.key_len()))?;
+
+    Ok(LessSafeKey::new(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::secret::{get_sample_storage_key, get_sample_storage_keys};
+
+    #[test]
+    fn data_should_survive_encryption() {
+        let keys = get_sample_storage_keys("a", &["a"]);
+
+        let encrypted = keys.encrypt("a", b"aad", b"secret").unwrap();
+
+        assert_eq!(encrypted.len(), NONCE_LEN + 6 + AES_256_GCM.tag_len());
+        assert_eq!(keys.decrypt("a", b"aad", &encrypted).unwrap(), b"secret");
+        assert_ne!(keys.encrypt("a", b"aad", b"secret").unwrap(), encrypted);
+    }
+
+    #[test]
+    fn tampered_data_should_not_be_decrypted() {
+        let keys = get_sample_storage_keys("a", &["a", "b"]);
+
+        let mut encrypted = keys.encrypt("a", b"aad", b"secret").unwrap();
+
+        assert!(keys.decrypt("a", b"other", &encrypted).is_err());
+        assert!(keys.decrypt("b", b"aad", &encrypted).is_err());
+        assert!(keys.decrypt("c", b"aad", &encrypted).is_err());
+        assert!(keys.decrypt("a", b"aad", &encrypted[..5]).is_err());
+
+        encrypted[NONCE_LEN] ^= 1;
+        assert!(keys.decrypt("a", b"aad", &encrypted).is_err());
+    }
+
+    #[test]
+    fn keys_should_be_loaded_from_file() {
+        let path = std::env::temp_dir().join(format!("pw-key-{}", uuid::Uuid::new_v4()));
+        fs::write(&path, format!("{}\n", STANDARD.encode([3; 32]))).unwrap();
+
+        let config = StorageEncryptionConfig {
+            enabled: false,
+            key_id: String::new(),
+            keys: vec![StorageKeyConfig {
+                id: "file".to_string(),
+                key: None,
+                key_file: Some(path.to_string_lossy().to_string()),
+            }],
+        };
+
+        let keys = StorageKeys::from_config(&config).unwrap().unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(keys.write_key_id(), None);
+        assert!(keys.encrypt("file", b"", b"data").is_ok());
+    }
+
+    #[test]
+    fn invalid_keys_should_be_rejected() {
+        let mut short_key = get_sample_storage_key("a");
+        short_key.key = Some(STANDARD.encode([1; 16]));
+
+        let mut both = get_sample_storage_key("a");
+        both.key_file = Some("/tmp/key".to_string());
+
+        for key in [short_key, both, StorageKeyConfig::default()] {
+            let config = StorageEncryptionConfig {
+                enabled: false,
+                key_id: String::new(),
+                keys: vec![key],
+            };
+            assert!(StorageKeys::from_config(&config).is_err());
+        }
+
+        let config = StorageEncryptionConfig {
+            enabled: true,
+            key_id: "b".to_string(),
+            keys: vec![get_sample_storage_key("a")],
+        };
+        assert!(StorageKeys::from_config(&config).is_err());
+
+        assert!(
+            StorageKeys::from_config(&StorageEncryptionConfig::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+}
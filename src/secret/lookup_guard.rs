@@ -0,0 +1,149 @@
+use crate::config::model::LookupGuardConfig;
+use serde::{Deserialize, Serialize};
+
+/// Failed secret lookups of a single client, timestamps are unix seconds
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LookupFailureRecord {
+    pub failures: u32,
+    pub window_ends_at: i64,
+    /// Bans in a row, used for exponential backoff
+    pub bans: u32,
+    pub banned_until: i64,
+    /// Record can be dropped after this time
+    pub expires_at: i64,
+}
+
+impl LookupFailureRecord {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Seconds left until the ban ends
+    pub fn get_ban_seconds_left(&self, now: i64) -> Option<u64> {
+        if self.banned_until > now {
+            Some((self.banned_until - now) as u64)
+        } else {
+            None
+        }
+    }
+
+    /// Returns ban duration in seconds when the failure starts a new ban
+    pub fn register_failure(&mut self, now: i64, config: &LookupGuardConfig) -> Option<u64> {
+        if self.bans > 0 && now >= self.banned_until + config.max_ban_seconds as i64 {
+            self.bans = 0;
+        }
+
+        if now >= self.window_ends_at {
+            self.failures = 0;
+            self.window_ends_at = now + config.failure_window_seconds as i64;
+        }
+
+        self.failures += 1;
+
+        let ban_seconds = if self.failures >= config.max_failures {
+            self.bans += 1;
+            self.failures = 0;
+            self.window_ends_at = now;
+
+            let ban_seconds = config.get_ban_seconds(self.bans);
+            self.banned_until = now + ban_seconds as i64;
+            Some(ban_seconds)
+        } else {
+            None
+        };
+
+        self.expires_at =
+            self.window_ends_at.max(self.banned_until) + config.max_ban_seconds as i64;
+
+        ban_seconds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_config() -> LookupGuardConfig {
+        LookupGuardConfig {
+            enabled: true,
+            max_failures: 3,
+            failure_window_seconds: 60,
+            ban_seconds: 10,
+            max_ban_seconds: 100,
+        }
+    }
+
+    #[test]
+    fn client_should_be_banned_after_max_failures() {
+        let config = get_config();
+        let mut record = LookupFailureRecord::default();
+
+        assert_eq!(record.register_failure(1000, &config), None);
+        assert_eq!(record.register_failure(1001, &config), None);
+        assert_eq!(record.register_failure(1002, &config), Some(10));
+
+        assert_eq!(record.get_ban_seconds_left(1002), Some(10));
+        assert_eq!(record.get_ban_seconds_left(1011), Some(1));
+        assert_eq!(record.get_ban_seconds_left(1012), None);
+    }
+
+    #[test]
+    fn failures_should_be_forgotten_after_window() {
+        let config = get_config();
+        let mut record = LookupFailureRecord::default();
+
+        record.register_failure(1000, &config);
+        record.register_failure(1001, &config);
+
+        assert_eq!(record.register_failure(1060, &config), None);
+        assert_eq!(record.failures, 1);
+    }
+
+    #[test]
+    fn ban_duration_should_grow_exponentially_up_to_max() {
+        let config = get_config();
+        let mut record = LookupFailureRecord::default();
+        let mut now = 1000;
+        let mut bans = vec![];
+
+        for _ in 0..5 {
+            for _ in 0..2 {
+                record.register_failure(now, &config);
+            }
+            let ban_seconds = record.register_failure(now, &config).unwrap();
+            bans.push(ban_seconds);
+            now += ban_seconds as i64;
+        }
+
+        assert_eq!(bans, vec![10, 20, 40, 80, 100]);
+    }
+
+    #[test]
+    fn backoff_should_be_reset_after_quiet_period() {
+        let config = get_config();
+        let mut record = LookupFailureRecord::default();
+
+        for _ in 0..3 {
+            record.register_failure(1000, &config);
+        }
+        assert_eq!(record.bans, 1);
+
+        let now = record.banned_until + config.max_ban_seconds as i64;
+        for _ in 0..2 {
+            record.register_failure(now, &config);
+        }
+        assert_eq!(record.register_failure(now, &config), Some(10));
+    }
+
+    #[test]
+    fn record_should_expire_after_ban_history() {
+        let config = get_config();
+        let mut record = LookupFailureRecord::default();
+
+        record.register_failure(1000, &config);
+
+        assert!(!record.is_expired(1060));
+        assert!(record.is_expired(1160));
+    }
+}
@@ -0,0 +1,694 @@
+use crate::audit::{AuditEvent, AuditLog};
+use crate::config::model::{LookupGuardConfig, MemoryStorageConfig};
+use crate::secret::lookup_guard::LookupFailureRecord;
+use crate::secret::model::Secret;
+use crate::secret::storage::{SecretExpiredError, SecretStorage, StorageFullError};
+use anyhow::Context;
+use async_trait::async_trait;
+use log::{debug, error, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+struct MemoryEntry {
+    secret: Secret,
+    expires_at: Instant,
+    size: u64,
+}
+
+impl MemoryEntry {
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at <= now
+    }
+}
+
+struct MemoryChunk {
+    data: Vec<u8>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    entries: HashMap<String, MemoryEntry>,
+    chunks: HashMap<String, MemoryChunk>,
+    total_bytes: u64,
+    lookup_failures: HashMap<String, LookupFailureRecord>,
+}
+
+impl MemoryState {
+    fn remove(&mut self, id: &str) -> Option<MemoryEntry> {
+        let entry = self.entries.remove(id)?;
+        self.total_bytes -= entry.size;
+        Some(entry)
+    }
+
+    fn remove_chunk(&mut self, key: &str) -> Option<MemoryChunk> {
+        let chunk = self.chunks.remove(key)?;
+        self.total_bytes -= chunk.data.len() as u64;
+        Some(chunk)
+    }
+
+    /// Returns live chunk, expired chunk is removed
+    fn get_chunk(&mut self, key: &str, now: Instant) -> Option<&MemoryChunk> {
+        if self
+            .chunks
+            .get(key)
+            .is_some_and(|chunk| chunk.expires_at <= now)
+        {
+            self.remove_chunk(key);
+        }
+
+        self.chunks.get(key)
+    }
+
+    /// Returns ids of removed secrets
+    fn remove_expired(&mut self, now: Instant) -> Vec<String> {
+        let expired_ids: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.is_expired(now))
+            .map(|(id, _)| id.to_string())
+            .collect();
+
+        for id in &expired_ids {
+            self.remove(id);
+        }
+
+        let expired_chunk_keys: Vec<String> = self
+            .chunks
+            .iter()
+            .filter(|(_, chunk)| chunk.expires_at <= now)
+            .map(|(key, _)| key.to_string())
+            .collect();
+
+        for key in &expired_chunk_keys {
+            self.remove_chunk(key);
+        }
+
+        expired_ids
+    }
+}
+
+/// In-process secret storage with a total byte budget and entry count limit.
+///
+/// Secrets are lost on restart. New secrets are rejected with [`StorageFullError`]
+/// when limits are reached, live secrets are never evicted.
+/// File chunks count toward the byte budget only.
+#[derive(Clone)]
+pub struct MemorySecretStorage {
+    state: Arc<Mutex<MemoryState>>,
+    max_bytes: u64,
+    max_entries: usize,
+}
+
+impl MemorySecretStorage {
+    pub fn new(config: &MemoryStorageConfig) -> MemorySecretStorage {
+        info!(
+            "memory storage limits: max bytes {}, max entries {}",
+            config.max_bytes, config.max_entries
+        );
+
+        MemorySecretStorage {
+            state: Arc::new(Mutex::new(MemoryState::default())),
+            max_bytes: config.max_bytes,
+            max_entries: config.max_entries,
+        }
+    }
+
+    /// Removes expired secrets periodically, every removal is audited
+    pub fn spawn_sweeper(&self, interval: Duration, audit_log: AuditLog) -> JoinHandle<()> {
+        let storage = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+
+            loop {
+                ticker.tick().await;
+
+                let removed_ids = storage.remove_expired();
+                match removed_ids.len() {
+                    0 => debug!("no expired secrets found"),
+                    removed => info!("removed expired secrets: {removed}"),
+                }
+
+                for id in &removed_ids {
+                    audit_log.record(AuditEvent::expired(id)).await;
+                }
+            }
+        })
+    }
+
+    /// Returns ids of removed secrets
+    pub fn remove_expired(&self) -> Vec<String> {
+        let mut state = self.state.lock().unwrap();
+
+        let now = chrono::Utc::now().timestamp();
+        state
+            .lookup_failures
+            .retain(|_, record| !record.is_expired(now));
+
+        state.remove_expired(Instant::now())
+    }
+
+    fn check_capacity(&self, state: &MemoryState, size: u64) -> Result<(), StorageFullError> {
+        if state.entries.len() >= self.max_entries {
+            return Err(StorageFullError {
+                reason: format!("max entries limit {} reached", self.max_entries),
+            });
+        }
+
+        self.check_bytes(state, size)
+    }
+
+    fn check_bytes(&self, state: &MemoryState, size: u64) -> Result<(), StorageFullError> {
+        if state.total_bytes + size > self.max_bytes {
+            return Err(StorageFullError {
+                reason: format!(
+                    "secret size {} bytes doesn't fit into budget ({} of {} bytes used)",
+                    size, state.total_bytes, self.max_bytes
+                ),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SecretStorage for MemorySecretStorage {
+    async fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<bool> {
+        info!("store secret: {}", secret);
+
+        let now = Instant::now();
+        let size = get_secret_size(secret);
+        let ttl_seconds = secret.ttl_seconds().context("secret ttl isn't set")?;
+
+        let mut state = self.state.lock().unwrap();
+
+        match state.entries.get(id) {
+            Some(entry) if entry.is_expired(now) => {
+                state.remove(id);
+            }
+            Some(_) => {
+                info!("secret with id '{id}' already exists, skip");
+                return Ok(false);
+            }
+            None => {}
+        }
+
+        if self.check_capacity(&state, size).is_err() {
+            let removed = state.remove_expired(now);
+            debug!(
+                "storage is full, expired secrets removed: {}",
+                removed.len()
+            );
+        }
+
+        if let Err(e) = self.check_capacity(&state, size) {
+            error!("unable to store secret with id '{id}': {}", e);
+            return Err(e.into());
+        }
+
+        state.entries.insert(
+            id.to_string(),
+            MemoryEntry {
+                secret: secret.clone(),
+                expires_at: now + Duration::from_secs(ttl_seconds),
+                size,
+            },
+        );
+        state.total_bytes += size;
+
+        info!("stored secret with id '{id}'");
+
+        Ok(true)
+    }
+
+    async fn load(&self, id: &str) -> anyhow::Result<Option<Secret>> {
+        info!("load secret by id '{id}'..");
+
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+
+        let (expired, remove, secret) = match state.entries.get_mut(id) {
+            Some(entry) if entry.is_expired(now) => (true, true, None),
+            Some(entry) => {
+                let remove = entry.secret.consume_view();
+                (false, remove, Some(entry.secret.clone()))
+            }
+            None => {
+                info!("secret wasn't found by id '{id}'");
+                return Ok(None);
+            }
+        };
+
+        if remove {
+            state.remove(id);
+        }
+
+        if expired {
+            info!("secret with id '{id}' has expired");
+            return Err(SecretExpiredError { id: id.to_string() }.into());
+        }
+
+        info!("secret has been found");
+
+        Ok(secret)
+    }
+
+    async fn remove(
+        &self,
+        id: &str,
+        revocation_token_hash: &str,
+    ) -> anyhow::Result<Option<Secret>> {
+        info!("remove secret by id '{id}'..");
+
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        let revocable = state.entries.get(id).is_some_and(|entry| {
+            !entry.is_expired(now) && entry.secret.is_revocable_by(revocation_token_hash)
+        });
+
+        if !revocable {
+            info!("secret wasn't found by id '{id}' and revocation token");
+            return Ok(None);
+        }
+
+        info!("secret with id '{id}' has been removed");
+
+        Ok(state.remove(id).map(|entry| entry.secret))
+    }
+
+    async fn get_lookup_ban(&self, client_ip: &str) -> anyhow::Result<Option<u64>> {
+        let state = self.state.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        Ok(state
+            .lookup_failures
+            .get(client_ip)
+            .and_then(|record| record.get_ban_seconds_left(now)))
+    }
+
+    async fn register_lookup_failure(
+        &self,
+        client_ip: &str,
+        config: &LookupGuardConfig,
+    ) -> anyhow::Result<Option<u64>> {
+        let mut state = self.state.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+
+        let record = state
+            .lookup_failures
+            .entry(client_ip.to_string())
+            .or_default();
+
+        Ok(record.register_failure(now, config))
+    }
+
+    async fn store_chunk(&self, key: &str, data: &[u8], ttl_seconds: u64) -> anyhow::Result<()> {
+        debug!("store chunk '{key}', {} bytes", data.len());
+
+        let now = Instant::now();
+        let size = data.len() as u64;
+
+        let mut state = self.state.lock().unwrap();
+
+        state.remove_chunk(key);
+
+        if self.check_bytes(&state, size).is_err() {
+            let removed = state.remove_expired(now);
+            debug!(
+                "storage is full, expired secrets removed: {}",
+                removed.len()
+            );
+        }
+
+        if let Err(e) = self.check_bytes(&state, size) {
+            error!("unable to store chunk '{key}': {}", e);
+            return Err(e.into());
+        }
+
+        state.chunks.insert(
+            key.to_string(),
+            MemoryChunk {
+                data: data.to_vec(),
+                expires_at: now + Duration::from_secs(ttl_seconds),
+            },
+        );
+        state.total_bytes += size;
+
+        Ok(())
+    }
+
+    async fn load_chunk(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut state = self.state.lock().unwrap();
+
+        Ok(state
+            .get_chunk(key, Instant::now())
+            .map(|chunk| chunk.data.clone()))
+    }
+
+    async fn get_chunk_size(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let mut state = self.state.lock().unwrap();
+
+        Ok(state
+            .get_chunk(key, Instant::now())
+            .map(|chunk| chunk.data.len() as u64))
+    }
+
+    async fn remove_chunks(&self, keys: &[String]) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+
+        for key in keys {
+            state.remove_chunk(key);
+        }
+
+        Ok(())
+    }
+}
+
+fn get_secret_size(secret: &Secret) -> u64 {
+    (secret.id.len()
+        + secret.payload.len()
+        + secret
+            .encrypted_metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.len())
+        + secret.metadata.name.len()
+        + secret.metadata.r#type.len()) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditAction;
+    use crate::audit::event::get_secret_id_hash;
+    use crate::secret::model::SecretDownloadPolicy;
+    use crate::tests::audit::RecordingAuditSink;
+    use crate::tests::secret::get_sample_secret;
+    use crate::tests::string::get_random_string;
+
+    fn get_storage(max_bytes: u64, max_entries: usize) -> MemorySecretStorage {
+        MemorySecretStorage::new(&MemoryStorageConfig {
+            max_bytes,
+            max_entries,
+            sweep_interval_seconds: 60,
+        })
+    }
+
+    fn expire_secret(storage: &MemorySecretStorage, id: &str) {
+        let mut state = storage.state.lock().unwrap();
+        let entry = state.entries.get_mut(id).unwrap();
+        entry.expires_at = Instant::now() - Duration::from_secs(1);
+    }
+
+    #[tokio::test]
+    async fn secret_with_one_time_download_should_be_removed_after_load() {
+        let storage = get_storage(1_000_000, 100);
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::OneTime;
+
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+
+        assert_eq!(
+            storage.load(&secret.id).await.unwrap(),
+            Some(secret.clone())
+        );
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+        assert_eq!(storage.state.lock().unwrap().total_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn secret_with_unlimited_download_should_not_be_removed_after_load() {
+        let storage = get_storage(1_000_000, 100);
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::Unlimited;
+
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn secret_with_max_views_should_be_removed_after_last_view() {
+        let storage = get_storage(1_000_000, 100);
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(2);
+        secret.remaining_views = Some(2);
+
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        let first = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(first.remaining_views, Some(1));
+        let second = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(second.remaining_views, Some(0));
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+        assert_eq!(storage.state.lock().unwrap().total_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn secret_with_existing_id_should_not_be_overwritten() {
+        let storage = get_storage(1_000_000, 100);
+
+        let secret = get_sample_secret();
+        let mut other_secret = get_sample_secret();
+        other_secret.id = secret.id.clone();
+
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+        assert!(
+            !storage
+                .store(&other_secret.id, &other_secret)
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(storage.load(&secret.id).await.unwrap(), Some(secret));
+    }
+
+    #[tokio::test]
+    async fn expired_secret_should_not_be_loaded() {
+        let storage = get_storage(1_000_000, 100);
+
+        let secret = get_sample_secret();
+        storage.store(&secret.id, &secret).await.unwrap();
+        expire_secret(&storage, &secret.id);
+
+        let error = storage.load(&secret.id).await.unwrap_err();
+        assert!(error.downcast_ref::<SecretExpiredError>().is_some());
+
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn return_error_when_entries_limit_reached() {
+        let storage = get_storage(1_000_000, 1);
+
+        let secret = get_sample_secret();
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        let other_secret = get_sample_secret();
+        let error = storage
+            .store(&other_secret.id, &other_secret)
+            .await
+            .unwrap_err();
+
+        assert!(error.downcast_ref::<StorageFullError>().is_some());
+    }
+
+    #[tokio::test]
+    async fn return_error_when_bytes_budget_exceeded() {
+        let mut secret = get_sample_secret();
+        secret.payload = "A".repeat(100).into();
+
+        let storage = get_storage(get_secret_size(&secret) + 10, 100);
+
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        let mut other_secret = get_sample_secret();
+        other_secret.payload = "B".repeat(100).into();
+
+        let error = storage
+            .store(&other_secret.id, &other_secret)
+            .await
+            .unwrap_err();
+
+        assert!(error.downcast_ref::<StorageFullError>().is_some());
+    }
+
+    #[tokio::test]
+    async fn expired_secrets_should_free_budget() {
+        let storage = get_storage(1_000_000, 1);
+
+        let secret = get_sample_secret();
+        storage.store(&secret.id, &secret).await.unwrap();
+        expire_secret(&storage, &secret.id);
+
+        let other_secret = get_sample_secret();
+        assert!(
+            storage
+                .store(&other_secret.id, &other_secret)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn remove_expired_secrets() {
+        let storage = get_storage(1_000_000, 100);
+
+        let expired_secret = get_sample_secret();
+        storage
+            .store(&expired_secret.id, &expired_secret)
+            .await
+            .unwrap();
+        expire_secret(&storage, &expired_secret.id);
+
+        let secret = get_sample_secret();
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        assert_eq!(storage.remove_expired(), vec![expired_secret.id.clone()]);
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert_eq!(
+            storage.state.lock().unwrap().total_bytes,
+            get_secret_size(&secret)
+        );
+    }
+
+    #[tokio::test]
+    async fn sweeper_should_audit_removed_secrets() {
+        let storage = get_storage(1_000_000, 100);
+
+        let expired_secret = get_sample_secret();
+        storage
+            .store(&expired_secret.id, &expired_secret)
+            .await
+            .unwrap();
+        expire_secret(&storage, &expired_secret.id);
+
+        let sink = Arc::new(RecordingAuditSink::default());
+        let sweeper =
+            storage.spawn_sweeper(Duration::from_millis(10), AuditLog::with_sink(sink.clone()));
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while sink.events.lock().unwrap().is_empty() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        sweeper.abort();
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].action, AuditAction::Expire);
+        assert_eq!(
+            events[0].secret_id_hash,
+            get_secret_id_hash(&expired_secret.id)
+        );
+        assert!(events[0].client_ip.is_none());
+    }
+
+    #[tokio::test]
+    async fn remove_secret_test() {
+        let storage = get_storage(1_000_000, 100);
+
+        let mut secret = get_sample_secret();
+        secret.revocation_token_hash = Some("token-hash".to_string());
+
+        storage.store(&secret.id, &secret).await.unwrap();
+        assert!(
+            storage
+                .remove(&secret.id, "other-hash")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            storage.remove(&secret.id, "token-hash").await.unwrap(),
+            Some(secret.clone())
+        );
+
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+        assert_eq!(storage.state.lock().unwrap().total_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn chunks_should_use_bytes_budget() {
+        let storage = get_storage(20, 1);
+
+        storage.store_chunk("a:0", &[1; 10], 60).await.unwrap();
+        storage.store_chunk("a:1", &[2; 5], 60).await.unwrap();
+
+        let error = storage.store_chunk("a:2", &[3; 10], 60).await.unwrap_err();
+        assert!(error.downcast_ref::<StorageFullError>().is_some());
+
+        assert_eq!(storage.get_chunk_size("a:1").await.unwrap(), Some(5));
+        assert_eq!(storage.load_chunk("a:0").await.unwrap(), Some(vec![1; 10]));
+
+        storage
+            .remove_chunks(&["a:0".to_string(), "a:1".to_string()])
+            .await
+            .unwrap();
+
+        assert!(storage.load_chunk("a:0").await.unwrap().is_none());
+        assert_eq!(storage.state.lock().unwrap().total_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn expired_chunks_should_be_removed() {
+        let storage = get_storage(1_000_000, 100);
+
+        storage.store_chunk("a:0", &[1; 10], 60).await.unwrap();
+        storage
+            .state
+            .lock()
+            .unwrap()
+            .chunks
+            .get_mut("a:0")
+            .unwrap()
+            .expires_at = Instant::now() - Duration::from_secs(1);
+
+        assert!(storage.get_chunk_size("a:0").await.unwrap().is_none());
+        assert_eq!(storage.state.lock().unwrap().total_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn client_should_be_banned_after_lookup_failures() {
+        let storage = get_storage(1_000_000, 100);
+        let config = LookupGuardConfig {
+            max_failures: 2,
+            ..LookupGuardConfig::default()
+        };
+
+        assert_eq!(
+            storage
+                .register_lookup_failure("10.0.0.1", &config)
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            storage
+                .register_lookup_failure("10.0.0.1", &config)
+                .await
+                .unwrap(),
+            Some(config.ban_seconds)
+        );
+
+        assert!(storage.get_lookup_ban("10.0.0.1").await.unwrap().is_some());
+        assert!(storage.get_lookup_ban("10.0.0.2").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn return_none_for_unknown_secret() {
+        let storage = get_storage(1_000_000, 100);
+        assert!(storage.load(&get_random_string()).await.unwrap().is_none());
+    }
+}
@@ -1,3 +1,10 @@
+pub mod embedded_storage;
+pub mod encryption;
+pub mod lookup_guard;
+pub mod memory_storage;
 pub mod model;
+pub mod record;
+pub mod redis_pool;
 pub mod storage;
+pub mod upload;
 pub mod usecase;
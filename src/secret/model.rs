@@ -1,32 +1,219 @@
-use serde::{Deserialize, Serialize};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{Display, Formatter};
 
+/// Encrypted file metadata is opaque to the server, so only its length is limited
+pub const ENCRYPTED_METADATA_MAX_LENGTH: usize = 4096;
+
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Secret {
+    /// Assigned by the server on creation, only the legacy `/api/v1/secret` endpoint accepts client ids
+    #[serde(default)]
     pub id: String,
 
     pub content_type: SecretContentType,
+
+    /// Left empty by clients which send `encrypted_metadata`
+    #[serde(default)]
     pub metadata: SecretFileMetadata,
 
+    /// File name, type and size encrypted on frontend side, the server never interprets it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_metadata: Option<SecretPayload>,
+
     /// Data encrypted on frontend side
-    pub payload: String,
+    pub payload: SecretPayload,
 
-    pub ttl: SecretTTL,
+    /// Server default TTL is used when not provided
+    #[serde(default)]
+    pub ttl: Option<SecretTTL>,
 
     pub download_policy: SecretDownloadPolicy,
+
+    /// Views left for `MaxViews` download policy, managed by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remaining_views: Option<u32>,
+
+    /// SHA-256 of the revocation token given to the creator, never returned to readers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation_token_hash: Option<String>,
+
+    /// Set for files uploaded in chunks, `payload` is empty then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<SecretChunks>,
 }
 
+impl Secret {
+    pub fn ttl_seconds(&self) -> Option<u64> {
+        self.ttl.as_ref().map(SecretTTL::as_seconds)
+    }
+
+    /// Encrypted data length, chunked files are stored outside of `payload`
+    pub fn payload_size(&self) -> usize {
+        match &self.chunks {
+            Some(chunks) => chunks.size as usize,
+            None => self.payload.len(),
+        }
+    }
+
+    /// Registers one download according to the download policy.
+    ///
+    /// Returns `true` when the secret must be removed after this download.
+    pub fn consume_view(&mut self) -> bool {
+        match self.download_policy {
+            SecretDownloadPolicy::OneTime => true,
+            SecretDownloadPolicy::Unlimited => false,
+            SecretDownloadPolicy::MaxViews(views) => {
+                let remaining = self.remaining_views.unwrap_or(views).saturating_sub(1);
+                self.remaining_views = Some(remaining);
+                remaining == 0
+            }
+        }
+    }
+
+    /// Loaded secret has no views left, the storage has already removed it
+    pub fn is_used_up(&self) -> bool {
+        match self.download_policy {
+            SecretDownloadPolicy::OneTime => true,
+            SecretDownloadPolicy::Unlimited => false,
+            SecretDownloadPolicy::MaxViews(_) => self.remaining_views == Some(0),
+        }
+    }
+
+    /// Secrets stored without revocation token can't be removed, they only expire
+    pub fn is_revocable_by(&self, revocation_token_hash: &str) -> bool {
+        self.revocation_token_hash.as_deref() == Some(revocation_token_hash)
+    }
+}
+
+/// File name and type may reveal the secret, so metadata is never logged
 impl Display for Secret {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "[Secret] id: '{}', content_type: {:?}, payload: '<encrypted>', ttl: {:?}, download-policy: {:?}, metadata: {:?}, [/Secret]",
-            self.id, self.content_type, self.ttl, self.download_policy, self.metadata,
+            "[Secret] id: '{}', content_type: {:?}, payload: '<encrypted>', ttl: {:?}, download-policy: {:?}, remaining-views: {:?}, metadata: '<redacted>', chunks: {:?} [/Secret]",
+            self.id,
+            self.content_type,
+            self.ttl,
+            self.download_policy,
+            self.remaining_views,
+            self.chunks.as_ref().map(|chunks| chunks.count),
         )
     }
 }
 
+/// Encrypted data as raw bytes, JSON carries it as standard base64 string,
+/// binary formats as bytes.
+///
+/// JSON strings which aren't canonical base64 come from clients predating binary
+/// payloads, they're kept as UTF-8 bytes and returned as the same string.
+#[derive(PartialEq, Clone, Debug, Default)]
+pub struct SecretPayload {
+    bytes: Vec<u8>,
+
+    /// Payload was sent as plain string, MessagePack keeps it as `str` instead of `bin`
+    text: bool,
+}
+
+impl SecretPayload {
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(&self.bytes)
+    }
+
+    fn from_json_string(value: &str) -> Self {
+        match STANDARD.decode(value) {
+            Ok(bytes) if STANDARD.encode(&bytes) == value => SecretPayload { bytes, text: false },
+            _ => SecretPayload::from(value),
+        }
+    }
+}
+
+impl From<Vec<u8>> for SecretPayload {
+    fn from(value: Vec<u8>) -> Self {
+        SecretPayload {
+            bytes: value,
+            text: false,
+        }
+    }
+}
+
+/// Takes bytes of the string as is, no base64 decoding
+impl From<String> for SecretPayload {
+    fn from(value: String) -> Self {
+        SecretPayload {
+            bytes: value.into_bytes(),
+            text: true,
+        }
+    }
+}
+
+impl From<&str> for SecretPayload {
+    fn from(value: &str) -> Self {
+        SecretPayload::from(value.to_string())
+    }
+}
+
+impl Serialize for SecretPayload {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self.text, std::str::from_utf8(&self.bytes)) {
+            (true, Ok(text)) => serializer.serialize_str(text),
+            _ if serializer.is_human_readable() => serializer.serialize_str(&self.to_base64()),
+            _ => serializer.serialize_bytes(&self.bytes),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretPayload {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return deserializer.deserialize_any(PayloadBytesVisitor);
+        }
+
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        Ok(SecretPayload::from_json_string(&value))
+    }
+}
+
+struct PayloadBytesVisitor;
+
+impl<'de> serde::de::Visitor<'de> for PayloadBytesVisitor {
+    type Value = SecretPayload;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "payload bytes or string")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(SecretPayload::from(value.to_vec()))
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(SecretPayload::from(value))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(SecretPayload::from(value))
+    }
+
+    fn visit_string<E: serde::de::Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(SecretPayload::from(value))
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub enum SecretContentType {
@@ -41,6 +228,26 @@ pub enum SecretTTL {
     TwoHours,
     OneDay,
     OneWeek,
+    /// Lifetime in seconds
+    Seconds(u64),
+    /// Absolute expiration time, unix timestamp in seconds
+    ExpiresAt(i64),
+}
+
+impl SecretTTL {
+    /// Seconds left until expiration, zero for expiration time in the past
+    pub fn as_seconds(&self) -> u64 {
+        match self {
+            SecretTTL::OneHour => 60 * 60,
+            SecretTTL::TwoHours => 60 * 60 * 2,
+            SecretTTL::OneDay => 60 * 60 * 24,
+            SecretTTL::OneWeek => 60 * 60 * 24 * 7,
+            SecretTTL::Seconds(seconds) => *seconds,
+            SecretTTL::ExpiresAt(timestamp) => {
+                (*timestamp - chrono::Utc::now().timestamp()).max(0) as u64
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
@@ -48,12 +255,104 @@ pub enum SecretTTL {
 pub enum SecretDownloadPolicy {
     OneTime,
     Unlimited,
+    /// Secret is removed after given number of downloads
+    MaxViews(u32),
 }
 
+impl SecretDownloadPolicy {
+    /// Initial value of the remaining views counter
+    pub fn initial_views(&self) -> Option<u32> {
+        match self {
+            SecretDownloadPolicy::MaxViews(views) => Some(*views),
+            _ => None,
+        }
+    }
+}
+
+/// File kept in chunks next to the secret, see `secret::upload`
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
+pub struct SecretChunks {
+    /// Random key required to download chunks, readers get it with the secret
+    pub key: String,
+
+    pub count: u32,
+
+    /// Size of every chunk except the last one
+    pub chunk_size: u64,
+
+    /// Total encrypted file size
+    pub size: u64,
+}
+
+impl SecretChunks {
+    /// Returns expected size of the chunk, `None` for index out of range
+    pub fn get_chunk_size(&self, index: u32) -> Option<u64> {
+        if index >= self.count {
+            return None;
+        }
+
+        if index + 1 < self.count {
+            return Some(self.chunk_size);
+        }
+
+        Some(self.size - self.chunk_size * (self.count as u64 - 1))
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
 pub struct SecretFileMetadata {
     pub name: String,
     pub r#type: String,
     pub size: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payload_should_be_serialized_as_base64() {
+        let payload = SecretPayload::from(vec![0, 159, 255]);
+
+        let json = serde_json::to_string(&payload).unwrap();
+
+        assert_eq!(json, r#""AJ//""#);
+        assert_eq!(
+            serde_json::from_str::<SecretPayload>(&json).unwrap(),
+            payload
+        );
+    }
+
+    #[test]
+    fn legacy_payload_should_be_returned_as_sent() {
+        for legacy in [r#""not base64!""#, r#""U2FsdGVkX1""#] {
+            let payload = serde_json::from_str::<SecretPayload>(legacy).unwrap();
+
+            assert_eq!(payload.as_bytes(), &legacy.as_bytes()[1..legacy.len() - 1]);
+            assert_eq!(serde_json::to_string(&payload).unwrap(), legacy);
+
+            let packed = rmp_serde::to_vec(&payload).unwrap();
+            assert_eq!(
+                rmp_serde::from_slice::<SecretPayload>(&packed).unwrap(),
+                payload
+            );
+        }
+    }
+
+    #[test]
+    fn secret_display_should_not_reveal_metadata() {
+        let mut secret = crate::tests::secret::get_sample_secret();
+        secret.metadata.name = "salaries-2025.xlsx".to_string();
+        secret.metadata.r#type = "application/vnd.ms-excel".to_string();
+        secret.payload = "U2FsdGVkX18xMjM0NTY3OA==".into();
+
+        let text = secret.to_string();
+
+        assert!(text.contains(&secret.id));
+        assert!(!text.contains("salaries-2025.xlsx"));
+        assert!(!text.contains("application/vnd.ms-excel"));
+        assert!(!text.contains("U2FsdGVkX18xMjM0NTY3OA=="));
+    }
+}
@@ -0,0 +1,385 @@
+use crate::config::model::StorageCompression;
+use crate::secret::encryption::StorageKeys;
+use crate::secret::model::{Secret, SecretDownloadPolicy};
+use anyhow::{Context, anyhow};
+use log::error;
+use std::sync::Arc;
+
+/// Storage encoding of a secret, numbers are big-endian:
+///
+/// | offset | length | field                                                   |
+/// |--------|--------|---------------------------------------------------------|
+/// | 0      | 1      | version, `RECORD_VERSION`                               |
+/// | 1      | 1      | flags, see `FLAG_DEFLATE` and `FLAG_AES_256_GCM`        |
+/// | 2      | 4      | views left, `UNLIMITED_VIEWS` for `Unlimited` policy    |
+/// | 6      | 1      | revocation token hash length                            |
+/// | 7      | n      | revocation token hash                                   |
+/// | 7 + n  | ..     | secret as MessagePack, compressed when flag is set      |
+///
+/// Encrypted records have key id length (1 byte) and key id after the token hash,
+/// body is then `nonce || ciphertext || tag` of the (compressed) secret.
+/// Version, flags, token hash and key id are authenticated, views left aren't
+/// since Lua scripts update them.
+///
+/// Header is read and updated in place by Redis Lua scripts, so one-time and
+/// max-views downloads stay atomic without decoding the secret.
+/// JSON records written by earlier versions start with `{` and are still readable.
+pub const RECORD_VERSION: u8 = 1;
+
+pub const UNLIMITED_VIEWS: u32 = u32::MAX;
+
+/// Secret is compressed with raw deflate
+const FLAG_DEFLATE: u8 = 0b0000_0001;
+
+/// Secret is encrypted with AES-256-GCM, key id follows the token hash
+const FLAG_AES_256_GCM: u8 = 0b0000_0010;
+
+const HEADER_LENGTH: usize = 7;
+
+const LEGACY_JSON_START: u8 = b'{';
+
+const DEFLATE_LEVEL: u8 = 6;
+
+/// Encodes records of redis and embedded storages
+#[derive(Clone, Default)]
+pub struct RecordCodec {
+    compression: StorageCompression,
+
+    /// Encrypted records can't be decoded without keys, even with encryption disabled
+    keys: Option<Arc<StorageKeys>>,
+}
+
+impl RecordCodec {
+    pub fn new(compression: StorageCompression, keys: Option<StorageKeys>) -> RecordCodec {
+        RecordCodec {
+            compression,
+            keys: keys.map(Arc::new),
+        }
+    }
+
+    pub fn encode(&self, secret: &Secret) -> anyhow::Result<Vec<u8>> {
+        let token_hash = secret.revocation_token_hash.as_deref().unwrap_or_default();
+        let token_hash_length =
+            u8::try_from(token_hash.len()).context("revocation token hash is too long")?;
+
+        let body = rmp_serde::to_vec_named(secret).context("secret serialization error")?;
+
+        let (mut flags, body) = match self.compression {
+            StorageCompression::None => (0, body),
+            StorageCompression::Deflate => {
+                let compressed = miniz_oxide::deflate::compress_to_vec(&body, DEFLATE_LEVEL);
+                // Encrypted payloads don't compress, small records may even grow
+                if compressed.len() < body.len() {
+                    (FLAG_DEFLATE, compressed)
+                } else {
+                    (0, body)
+                }
+            }
+        };
+
+        let write_key = self
+            .keys
+            .as_deref()
+            .and_then(|keys| Some((keys, keys.write_key_id()?)));
+
+        let mut record = Vec::with_capacity(HEADER_LENGTH + token_hash.len() + body.len());
+        if write_key.is_some() {
+            flags |= FLAG_AES_256_GCM;
+        }
+        record.push(RECORD_VERSION);
+        record.push(flags);
+        record.extend_from_slice(&get_views_left(secret).to_be_bytes());
+        record.push(token_hash_length);
+        record.extend_from_slice(token_hash.as_bytes());
+
+        match write_key {
+            Some((keys, key_id)) => {
+                let key_id_length = u8::try_from(key_id.len()).context("key id is too long")?;
+                record.push(key_id_length);
+                record.extend_from_slice(key_id.as_bytes());
+
+                let aad = get_aad(&record, HEADER_LENGTH + token_hash.len());
+                let encrypted = keys.encrypt(key_id, &aad, &body)?;
+                record.extend_from_slice(&encrypted);
+            }
+            None => record.extend_from_slice(&body),
+        }
+
+        Ok(record)
+    }
+
+    pub fn decode(&self, record: &[u8]) -> anyhow::Result<Secret> {
+        match record.first() {
+            Some(&LEGACY_JSON_START) => serde_json::from_slice(record).map_err(|e| {
+                error!("{}", e);
+                anyhow!("unable to deserialize secret")
+            }),
+            Some(&RECORD_VERSION) => self.decode_record(record),
+            Some(version) => Err(anyhow!("unsupported secret record version {version}")),
+            None => Err(anyhow!("secret record is empty")),
+        }
+    }
+
+    fn decode_record(&self, record: &[u8]) -> anyhow::Result<Secret> {
+        let header = record
+            .get(..HEADER_LENGTH)
+            .context("secret record header is truncated")?;
+
+        let flags = header[1];
+        let views_left = u32::from_be_bytes([header[2], header[3], header[4], header[5]]);
+        let mut body_start = HEADER_LENGTH + header[6] as usize;
+
+        let decrypted;
+        let body = if flags & FLAG_AES_256_GCM != 0 {
+            let key_id_length = *record
+                .get(body_start)
+                .context("secret record header is truncated")?
+                as usize;
+            let key_id = record
+                .get(body_start + 1..body_start + 1 + key_id_length)
+                .context("secret record header is truncated")?;
+            let key_id = std::str::from_utf8(key_id).context("invalid storage key id")?;
+
+            let keys = self
+                .keys
+                .as_deref()
+                .context("secret record is encrypted, but storage keys aren't configured")?;
+
+            let aad = get_aad(record, body_start);
+            body_start += 1 + key_id_length;
+
+            decrypted = keys.decrypt(key_id, &aad, &record[body_start..])?;
+            decrypted.as_slice()
+        } else {
+            record
+                .get(body_start..)
+                .context("secret record header is truncated")?
+        };
+
+        let body = if flags & FLAG_DEFLATE != 0 {
+            miniz_oxide::inflate::decompress_to_vec(body)
+                .map_err(|e| anyhow!("unable to decompress secret: {e:?}"))?
+        } else {
+            body.to_vec()
+        };
+
+        let mut secret: Secret = rmp_serde::from_slice(&body).map_err(|e| {
+            error!("{}", e);
+            anyhow!("unable to deserialize secret")
+        })?;
+
+        // Views counter in the header is the only one updated by Lua scripts
+        if let SecretDownloadPolicy::MaxViews(_) = secret.download_policy {
+            secret.remaining_views = Some(views_left);
+        }
+
+        Ok(secret)
+    }
+}
+
+/// Version and flags, token hash and key id; `key_id_start` points to the key id length
+fn get_aad(record: &[u8], key_id_start: usize) -> Vec<u8> {
+    let key_id_end = key_id_start + 1 + record[key_id_start] as usize;
+
+    let mut aad = Vec::with_capacity(key_id_end);
+    aad.extend_from_slice(&record[..2]);
+    aad.extend_from_slice(&record[HEADER_LENGTH..key_id_end]);
+    aad
+}
+
+fn get_views_left(secret: &Secret) -> u32 {
+    match secret.download_policy {
+        SecretDownloadPolicy::OneTime => 1,
+        SecretDownloadPolicy::Unlimited => UNLIMITED_VIEWS,
+        SecretDownloadPolicy::MaxViews(views) => secret.remaining_views.unwrap_or(views),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::model::SecretChunks;
+    use crate::tests::secret::{get_sample_secret, get_sample_storage_keys};
+
+    #[test]
+    fn secret_should_survive_encoding() {
+        let mut secret = get_sample_secret();
+        secret.payload = vec![0, 1, 2, 255].into();
+        secret.revocation_token_hash = Some("a".repeat(64));
+        secret.encrypted_metadata = Some(vec![9, 0, 9].into());
+        secret.chunks = Some(SecretChunks {
+            key: "key".to_string(),
+            count: 2,
+            chunk_size: 10,
+            size: 15,
+        });
+
+        for compression in [StorageCompression::None, StorageCompression::Deflate] {
+            let record = RecordCodec::new(compression, None).encode(&secret).unwrap();
+
+            assert_eq!(record[0], RECORD_VERSION);
+            assert_eq!(&record[7..71], "a".repeat(64).as_bytes());
+            assert_eq!(RecordCodec::default().decode(&record).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn record_should_be_smaller_than_json() {
+        let mut secret = get_sample_secret();
+        secret.payload = vec![7; 3000].into();
+
+        let json = serde_json::to_vec(&secret).unwrap();
+        let record = RecordCodec::default().encode(&secret).unwrap();
+        let compressed = RecordCodec::new(StorageCompression::Deflate, None)
+            .encode(&secret)
+            .unwrap();
+
+        assert!(record.len() < json.len() * 4 / 5);
+        assert!(compressed.len() < record.len() / 10);
+        assert_eq!(compressed[1], FLAG_DEFLATE);
+        assert_eq!(RecordCodec::default().decode(&compressed).unwrap(), secret);
+    }
+
+    #[test]
+    fn incompressible_secret_should_be_stored_uncompressed() {
+        let mut secret = get_sample_secret();
+        secret.id = String::new();
+        secret.metadata.name = String::new();
+        secret.payload = (0..100)
+            .flat_map(|_| *uuid::Uuid::new_v4().as_bytes())
+            .collect::<Vec<u8>>()
+            .into();
+
+        let record = RecordCodec::new(StorageCompression::Deflate, None)
+            .encode(&secret)
+            .unwrap();
+
+        assert_eq!(record[1], 0);
+        assert_eq!(RecordCodec::default().decode(&record).unwrap(), secret);
+    }
+
+    #[test]
+    fn views_left_should_be_read_from_header() {
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(3);
+        secret.remaining_views = Some(3);
+
+        let mut record = RecordCodec::default().encode(&secret).unwrap();
+        assert_eq!(&record[2..6], &3u32.to_be_bytes());
+
+        // The same update is done by the Redis Lua script
+        record[2..6].copy_from_slice(&2u32.to_be_bytes());
+
+        assert_eq!(
+            RecordCodec::default()
+                .decode(&record)
+                .unwrap()
+                .remaining_views,
+            Some(2)
+        );
+
+        secret.download_policy = SecretDownloadPolicy::Unlimited;
+        secret.remaining_views = None;
+        let record = RecordCodec::default().encode(&secret).unwrap();
+        assert_eq!(&record[2..6], &UNLIMITED_VIEWS.to_be_bytes());
+    }
+
+    #[test]
+    fn encrypted_secret_should_survive_encoding() {
+        let mut secret = get_sample_secret();
+        secret.payload = vec![7; 3000].into();
+        secret.metadata.name = "confidential-report.pdf".to_string();
+        secret.revocation_token_hash = Some("a".repeat(64));
+        secret.download_policy = SecretDownloadPolicy::MaxViews(3);
+        secret.remaining_views = Some(3);
+
+        for compression in [StorageCompression::None, StorageCompression::Deflate] {
+            let codec = RecordCodec::new(compression, Some(get_sample_storage_keys("k1", &["k1"])));
+            let record = codec.encode(&secret).unwrap();
+
+            assert_ne!(record[1] & FLAG_AES_256_GCM, 0);
+            assert_eq!(&record[2..6], &3u32.to_be_bytes());
+            assert_eq!(&record[7..71], "a".repeat(64).as_bytes());
+            assert_eq!(&record[71..74], &[2, b'k', b'1']);
+            assert!(
+                !record
+                    .windows(secret.metadata.name.len())
+                    .any(|window| window == secret.metadata.name.as_bytes())
+            );
+            assert_eq!(codec.decode(&record).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn records_should_be_readable_after_key_rotation() {
+        let secret = get_sample_secret();
+
+        let old_codec = RecordCodec::new(
+            StorageCompression::None,
+            Some(get_sample_storage_keys("k1", &["k1"])),
+        );
+        let old_record = old_codec.encode(&secret).unwrap();
+        let plain_record = RecordCodec::default().encode(&secret).unwrap();
+
+        let codec = RecordCodec::new(
+            StorageCompression::None,
+            Some(get_sample_storage_keys("k2", &["k1", "k2"])),
+        );
+        let record = codec.encode(&secret).unwrap();
+
+        assert_eq!(&record[7..10], &[2, b'k', b'2']);
+        assert_eq!(codec.decode(&old_record).unwrap(), secret);
+        assert_eq!(codec.decode(&plain_record).unwrap(), secret);
+        assert_eq!(codec.decode(&record).unwrap(), secret);
+        assert!(old_codec.decode(&record).is_err());
+    }
+
+    #[test]
+    fn encrypted_record_should_not_be_decoded_without_keys() {
+        let secret = get_sample_secret();
+        let codec = RecordCodec::new(
+            StorageCompression::None,
+            Some(get_sample_storage_keys("k1", &["k1"])),
+        );
+        let record = codec.encode(&secret).unwrap();
+
+        assert!(RecordCodec::default().decode(&record).is_err());
+
+        // Flags and key id are authenticated
+        let mut tampered = record.clone();
+        tampered[1] |= FLAG_DEFLATE;
+        assert!(codec.decode(&tampered).is_err());
+
+        let mut truncated = record.clone();
+        truncated.truncate(9);
+        assert!(codec.decode(&truncated).is_err());
+
+        // Views counter is updated in place by the Lua script
+        let mut record = record;
+        record[2..6].copy_from_slice(&5u32.to_be_bytes());
+        assert_eq!(codec.decode(&record).unwrap(), secret);
+    }
+
+    #[test]
+    fn legacy_json_record_should_be_readable() {
+        let secret = get_sample_secret();
+        let json = serde_json::to_vec(&secret).unwrap();
+
+        assert_eq!(RecordCodec::default().decode(&json).unwrap(), secret);
+    }
+
+    #[test]
+    fn unknown_version_should_be_rejected() {
+        assert!(RecordCodec::default().decode(&[]).is_err());
+        assert!(
+            RecordCodec::default()
+                .decode(&[2, 0, 0, 0, 0, 1, 0])
+                .is_err()
+        );
+        assert!(
+            RecordCodec::default()
+                .decode(&[RECORD_VERSION, 0, 0])
+                .is_err()
+        );
+    }
+}
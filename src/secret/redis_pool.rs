@@ -0,0 +1,60 @@
+use crate::config::model::RedisPoolConfig;
+use anyhow::Context;
+use log::debug;
+use redis::aio::{ConnectionManager, ConnectionManagerConfig};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+
+const CONNECTION_RETRIES: usize = 2;
+
+/// Fixed set of shared multiplexed Redis connections.
+///
+/// Connections are established lazily on first use and reconnect automatically,
+/// so the pool can be created before Redis is reachable.
+#[derive(Clone)]
+pub struct RedisConnectionPool {
+    client: redis::Client,
+    manager_config: ConnectionManagerConfig,
+    connections: Arc<Vec<OnceCell<ConnectionManager>>>,
+    next: Arc<AtomicUsize>,
+}
+
+impl RedisConnectionPool {
+    /// For example: `redis://127.0.0.1/`
+    pub fn new(cnn_url: &str, config: &RedisPoolConfig) -> anyhow::Result<RedisConnectionPool> {
+        let client = redis::Client::open(cnn_url).context("invalid redis url")?;
+
+        let manager_config = ConnectionManagerConfig::new()
+            .set_number_of_retries(CONNECTION_RETRIES)
+            .set_connection_timeout(Some(Duration::from_millis(config.connect_timeout_ms)))
+            .set_response_timeout(Some(Duration::from_millis(config.command_timeout_ms)));
+
+        let size = config.size.max(1);
+        let connections = (0..size).map(|_| OnceCell::new()).collect();
+
+        Ok(RedisConnectionPool {
+            client,
+            manager_config,
+            connections: Arc::new(connections),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Returns next connection in round-robin order
+    pub async fn get(&self) -> anyhow::Result<ConnectionManager> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+
+        let cnn = self.connections[index]
+            .get_or_try_init(|| async {
+                debug!("open redis connection #{index}");
+                ConnectionManager::new_with_config(self.client.clone(), self.manager_config.clone())
+                    .await
+            })
+            .await
+            .context("couldn't connect to redis")?;
+
+        Ok(cnn.clone())
+    }
+}
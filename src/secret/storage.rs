@@ -1,91 +1,195 @@
-use crate::secret::model::{Secret, SecretDownloadPolicy, SecretTTL};
-use anyhow::{Context, anyhow};
-use log::{debug, error, info};
-use redis::{Commands, ExistenceCheck, SetExpiry, SetOptions};
+use crate::config::model::LookupGuardConfig;
+use crate::secret::lookup_guard::LookupFailureRecord;
+use crate::secret::model::Secret;
+use crate::secret::record::RecordCodec;
+use crate::secret::redis_pool::RedisConnectionPool;
+use anyhow::Context;
+use async_trait::async_trait;
+use log::{debug, info};
+use redis::{AsyncCommands, ExistenceCheck, SetExpiry, SetOptions};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use thiserror::Error;
 
 pub const DEFAULT_REDIS_CNN_URL: &str = "redis://127.0.0.1";
 
+const LOOKUP_FAILURES_KEY_PREFIX: &str = "pw:lookup-failures";
+const LOOKUP_BANS_KEY_PREFIX: &str = "pw:lookup-bans";
+const LOOKUP_BAN_KEY_PREFIX: &str = "pw:lookup-ban";
+const CHUNK_KEY_PREFIX: &str = "pw:chunk";
+
+/// Returned by storages with limited capacity when a new secret doesn't fit
+#[derive(Error, Debug)]
+#[error("storage capacity exceeded: {reason}")]
+pub struct StorageFullError {
+    pub reason: String,
+}
+
+/// Returned on load by storages which keep expired secrets until the next sweep,
+/// the secret is removed then
+#[derive(Error, Debug)]
+#[error("secret '{id}' has expired")]
+pub struct SecretExpiredError {
+    pub id: String,
+}
+
+#[async_trait]
 pub trait SecretStorage: Send + Sync {
-    fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<()>;
-    fn load(&self, id: &str) -> anyhow::Result<Option<Secret>>;
-    fn remove(&self, id: &str) -> anyhow::Result<()>;
+    /// Returns `false` if a secret with the same id already exists, nothing is stored then
+    async fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<bool>;
+    async fn load(&self, id: &str) -> anyhow::Result<Option<Secret>>;
+
+    /// Removes secret if its revocation token hash matches, returns the removed secret
+    async fn remove(&self, id: &str, revocation_token_hash: &str)
+    -> anyhow::Result<Option<Secret>>;
+
+    /// Returns seconds left until the client ban ends
+    async fn get_lookup_ban(&self, client_ip: &str) -> anyhow::Result<Option<u64>>;
+
+    /// Registers failed secret lookup, returns ban duration in seconds when the client gets banned
+    async fn register_lookup_failure(
+        &self,
+        client_ip: &str,
+        config: &LookupGuardConfig,
+    ) -> anyhow::Result<Option<u64>>;
+
+    /// Stores file chunk, an existing chunk with the same key is replaced
+    async fn store_chunk(&self, key: &str, data: &[u8], ttl_seconds: u64) -> anyhow::Result<()>;
+    async fn load_chunk(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+
+    /// Returns chunk length without loading it
+    async fn get_chunk_size(&self, key: &str) -> anyhow::Result<Option<u64>>;
+    async fn remove_chunks(&self, keys: &[String]) -> anyhow::Result<()>;
+
+    /// Stores upload session as chunk, persistent storages encode it with their
+    /// `RecordCodec` like secrets, so metadata and token hash get encrypted too
+    async fn store_upload_session(
+        &self,
+        key: &str,
+        secret: &Secret,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<()> {
+        let record = RecordCodec::default().encode(secret)?;
+        self.store_chunk(key, &record, ttl_seconds).await
+    }
+
+    async fn load_upload_session(&self, key: &str) -> anyhow::Result<Option<Secret>> {
+        let codec = RecordCodec::default();
+        self.load_chunk(key)
+            .await?
+            .map(|record| codec.decode(&record))
+            .transpose()
+    }
 }
 
+/// Secrets are kept in `secret::record` encoding, JSON records of earlier versions
+/// are handled by the same Lua scripts
 #[derive(Clone)]
 pub struct RedisSecretStorage {
-    cnn_url: String,
+    pool: RedisConnectionPool,
+    codec: RecordCodec,
 }
 
 impl RedisSecretStorage {
-    /// For example: `redis://127.0.0.1/`
-    pub fn new(cnn_url: &str) -> RedisSecretStorage {
+    pub fn new(pool: RedisConnectionPool) -> RedisSecretStorage {
         RedisSecretStorage {
-            cnn_url: cnn_url.to_string(),
+            pool,
+            codec: RecordCodec::default(),
         }
     }
+
+    pub fn with_codec(self, codec: RecordCodec) -> RedisSecretStorage {
+        RedisSecretStorage { codec, ..self }
+    }
 }
 
+#[async_trait]
 impl SecretStorage for RedisSecretStorage {
-    fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<()> {
+    async fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<bool> {
         info!("store secret: {}", secret);
-        debug!("cnn url: {}", self.cnn_url);
-        let client = redis::Client::open(&*self.cnn_url)?;
-        let mut cnn = client
-            .get_connection()
-            .context("couldn't connect to redis")?;
-
-        let ttl_seconds = match secret.ttl {
-            SecretTTL::OneHour => 60 * 60,
-            SecretTTL::TwoHours => 60 * 60 * 2,
-            SecretTTL::OneDay => 60 * 60 * 24,
-            SecretTTL::OneWeek => 60 * 60 * 24 * 7,
-        };
+        let mut cnn = self.pool.get().await?;
+
+        let ttl_seconds = secret.ttl_seconds().context("secret ttl isn't set")?;
 
         debug!("ttl seconds: {ttl_seconds}");
 
         let opts = SetOptions::default()
             .conditional_set(ExistenceCheck::NX)
-            .with_expiration(SetExpiry::EX(ttl_seconds))
-            .get(true);
+            .with_expiration(SetExpiry::EX(ttl_seconds));
 
-        let json = serde_json::to_string(&secret).context("secret deserialization error")?;
+        let record = self.codec.encode(secret)?;
 
-        let _: Option<String> = cnn.set_options(id.to_string(), json, opts)?;
+        let result: Option<String> = cnn.set_options(id.to_string(), record, opts).await?;
+
+        if result.is_none() {
+            info!("secret with id '{id}' already exists, skip");
+            return Ok(false);
+        }
 
         info!("stored secret entity: {}", secret);
 
-        Ok(())
+        Ok(true)
     }
 
-    fn load(&self, id: &str) -> anyhow::Result<Option<Secret>> {
+    async fn load(&self, id: &str) -> anyhow::Result<Option<Secret>> {
         info!("load secret by id '{id}'..");
 
-        let client = redis::Client::open(&*self.cnn_url)?;
-        let mut cnn = client.get_connection()?;
+        let mut cnn = self.pool.get().await?;
 
+        // Views left are kept in bytes 3..6 of the record (Lua strings are 1-based),
+        // 4294967295 means unlimited views
         let script = redis::Script::new(
             r#"
             local value = redis.call('GET', KEYS[1])
-            if value then
+            if not value then
+                return false
+            end
+            if string.byte(value, 1) == 123 then
                 local secret = cjson.decode(value)
-                if secret.downloadPolicy == "OneTime" then
+                local policy = secret.downloadPolicy
+                if policy == "OneTime" then
                     redis.call('DEL', KEYS[1])
+                elseif type(policy) == "table" and policy.MaxViews then
+                    local remaining = (secret.remainingViews or policy.MaxViews) - 1
+                    if remaining < 0 then
+                        remaining = 0
+                    end
+                    secret.remainingViews = remaining
+                    value = cjson.encode(secret)
+                    if remaining == 0 then
+                        redis.call('DEL', KEYS[1])
+                    else
+                        redis.call('SET', KEYS[1], value, 'KEEPTTL')
+                    end
                 end
+                return value
             end
-            return value
+            local b1, b2, b3, b4 = string.byte(value, 3, 6)
+            local views = ((b1 * 256 + b2) * 256 + b3) * 256 + b4
+            if views == 4294967295 then
+                return value
+            end
+            views = math.max(views - 1, 0)
+            local encoded = string.char(
+                math.floor(views / 16777216) % 256,
+                math.floor(views / 65536) % 256,
+                math.floor(views / 256) % 256,
+                views % 256
+            )
+            if views == 0 then
+                redis.call('DEL', KEYS[1])
+            else
+                redis.call('SETRANGE', KEYS[1], 2, encoded)
+            end
+            return string.sub(value, 1, 2) .. encoded .. string.sub(value, 7)
             "#,
         );
 
-        let res: Option<String> = script.key(id).invoke(&mut cnn)?;
+        let res: Option<Vec<u8>> = script.key(id).invoke_async(&mut cnn).await?;
 
         match res {
-            Some(json) => {
-                let secret = serde_json::from_str::<Secret>(&json).map_err(|e| {
-                    error!("{}", e);
-                    anyhow!("unable to deserialize secret")
-                })?;
+            Some(record) => {
+                let secret = self.codec.decode(&record)?;
                 info!("secret has been found");
                 Ok(Some(secret))
             }
@@ -96,30 +200,187 @@ impl SecretStorage for RedisSecretStorage {
         }
     }
 
-    fn remove(&self, id: &str) -> anyhow::Result<()> {
+    async fn remove(
+        &self,
+        id: &str,
+        revocation_token_hash: &str,
+    ) -> anyhow::Result<Option<Secret>> {
         info!("remove secret by id '{id}'..");
 
-        let client = redis::Client::open(&*self.cnn_url)?;
-        let mut cnn = client.get_connection()?;
+        let mut cnn = self.pool.get().await?;
+
+        // Revocation token hash follows its length in byte 7 of the record
+        let script = redis::Script::new(
+            r#"
+            local value = redis.call('GET', KEYS[1])
+            if not value then
+                return false
+            end
+            local token_hash
+            if string.byte(value, 1) == 123 then
+                token_hash = cjson.decode(value).revocationTokenHash
+            else
+                token_hash = string.sub(value, 8, 7 + string.byte(value, 7))
+            end
+            if token_hash ~= ARGV[1] then
+                return false
+            end
+            redis.call('DEL', KEYS[1])
+            return value
+            "#,
+        );
+
+        let res: Option<Vec<u8>> = script
+            .key(id)
+            .arg(revocation_token_hash)
+            .invoke_async(&mut cnn)
+            .await?;
+
+        match res {
+            Some(record) => {
+                let secret = self.codec.decode(&record)?;
+                info!("secret with id '{id}' has been removed");
+                Ok(Some(secret))
+            }
+            None => {
+                info!("secret wasn't found by id '{id}' and revocation token");
+                Ok(None)
+            }
+        }
+    }
 
-        let id = id.to_string();
+    async fn get_lookup_ban(&self, client_ip: &str) -> anyhow::Result<Option<u64>> {
+        let mut cnn = self.pool.get().await?;
 
-        if cnn.exists(&id)? {
-            let _: String = cnn.del(&id)?;
+        let ttl: i64 = cnn
+            .ttl(format!("{LOOKUP_BAN_KEY_PREFIX}:{client_ip}"))
+            .await?;
 
-            info!("secret with id '{id}' has been removed");
+        Ok((ttl > 0).then_some(ttl as u64))
+    }
 
-            Ok(())
-        } else {
-            info!("secret wasn't found by id '{id}'");
-            Ok(())
+    async fn register_lookup_failure(
+        &self,
+        client_ip: &str,
+        config: &LookupGuardConfig,
+    ) -> anyhow::Result<Option<u64>> {
+        let mut cnn = self.pool.get().await?;
+
+        let script = redis::Script::new(
+            r#"
+            local failures = redis.call('INCR', KEYS[1])
+            if redis.call('TTL', KEYS[1]) < 0 then
+                redis.call('EXPIRE', KEYS[1], ARGV[1])
+            end
+            if failures < tonumber(ARGV[2]) then
+                return 0
+            end
+            redis.call('DEL', KEYS[1])
+            return redis.call('INCR', KEYS[2])
+            "#,
+        );
+
+        let bans_key = format!("{LOOKUP_BANS_KEY_PREFIX}:{client_ip}");
+
+        let bans: u32 = script
+            .key(format!("{LOOKUP_FAILURES_KEY_PREFIX}:{client_ip}"))
+            .key(&bans_key)
+            .arg(config.failure_window_seconds.max(1))
+            .arg(config.max_failures)
+            .invoke_async(&mut cnn)
+            .await?;
+
+        if bans == 0 {
+            return Ok(None);
         }
+
+        let ban_seconds = config.get_ban_seconds(bans).max(1);
+
+        let _: () = redis::pipe()
+            .set_ex(
+                format!("{LOOKUP_BAN_KEY_PREFIX}:{client_ip}"),
+                bans,
+                ban_seconds,
+            )
+            .ignore()
+            .expire(&bans_key, (ban_seconds + config.max_ban_seconds) as i64)
+            .ignore()
+            .query_async(&mut cnn)
+            .await?;
+
+        Ok(Some(ban_seconds))
+    }
+
+    async fn store_chunk(&self, key: &str, data: &[u8], ttl_seconds: u64) -> anyhow::Result<()> {
+        debug!("store chunk '{key}', {} bytes", data.len());
+        let mut cnn = self.pool.get().await?;
+
+        let _: () = cnn
+            .set_ex(
+                format!("{CHUNK_KEY_PREFIX}:{key}"),
+                data,
+                ttl_seconds.max(1),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn load_chunk(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let mut cnn = self.pool.get().await?;
+
+        Ok(cnn.get(format!("{CHUNK_KEY_PREFIX}:{key}")).await?)
+    }
+
+    async fn store_upload_session(
+        &self,
+        key: &str,
+        secret: &Secret,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<()> {
+        let record = self.codec.encode(secret)?;
+        self.store_chunk(key, &record, ttl_seconds).await
+    }
+
+    async fn load_upload_session(&self, key: &str) -> anyhow::Result<Option<Secret>> {
+        self.load_chunk(key)
+            .await?
+            .map(|record| self.codec.decode(&record))
+            .transpose()
+    }
+
+    async fn get_chunk_size(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let mut cnn = self.pool.get().await?;
+
+        // STRLEN returns 0 for missing keys, chunks are never empty
+        let size: u64 = cnn.strlen(format!("{CHUNK_KEY_PREFIX}:{key}")).await?;
+
+        Ok((size > 0).then_some(size))
+    }
+
+    async fn remove_chunks(&self, keys: &[String]) -> anyhow::Result<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+
+        let mut cnn = self.pool.get().await?;
+
+        let keys: Vec<String> = keys
+            .iter()
+            .map(|key| format!("{CHUNK_KEY_PREFIX}:{key}"))
+            .collect();
+
+        let _: () = cnn.del(keys).await?;
+
+        Ok(())
     }
 }
 
 #[derive(Clone)]
 pub struct MockSecretStorage {
     store: Arc<Mutex<HashMap<String, Secret>>>,
+    chunks: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    lookup_failures: Arc<Mutex<HashMap<String, LookupFailureRecord>>>,
 }
 
 impl Default for MockSecretStorage {
@@ -132,22 +393,29 @@ impl MockSecretStorage {
     pub fn new() -> Self {
         Self {
             store: Arc::new(Mutex::new(HashMap::new())),
+            chunks: Arc::new(Mutex::new(HashMap::new())),
+            lookup_failures: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
 
+#[async_trait]
 impl SecretStorage for MockSecretStorage {
-    fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<()> {
+    async fn store(&self, id: &str, secret: &Secret) -> anyhow::Result<bool> {
         let mut store = self.store.lock().unwrap();
+        if store.contains_key(id) {
+            return Ok(false);
+        }
         store.insert(id.to_string(), secret.clone());
-        Ok(())
+        Ok(true)
     }
 
-    fn load(&self, id: &str) -> anyhow::Result<Option<Secret>> {
+    async fn load(&self, id: &str) -> anyhow::Result<Option<Secret>> {
         let mut store = self.store.lock().unwrap();
-        if let Some(secret) = store.get(id) {
+        if let Some(secret) = store.get_mut(id) {
+            let remove = secret.consume_view();
             let secret = secret.clone();
-            if secret.download_policy == SecretDownloadPolicy::OneTime {
+            if remove {
                 store.remove(id);
             }
             Ok(Some(secret))
@@ -156,73 +424,199 @@ impl SecretStorage for MockSecretStorage {
         }
     }
 
-    fn remove(&self, id: &str) -> anyhow::Result<()> {
+    async fn remove(
+        &self,
+        id: &str,
+        revocation_token_hash: &str,
+    ) -> anyhow::Result<Option<Secret>> {
         let mut store = self.store.lock().unwrap();
-        store.remove(id);
+        match store.get(id) {
+            Some(secret) if secret.is_revocable_by(revocation_token_hash) => Ok(store.remove(id)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn get_lookup_ban(&self, client_ip: &str) -> anyhow::Result<Option<u64>> {
+        let lookup_failures = self.lookup_failures.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        Ok(lookup_failures
+            .get(client_ip)
+            .and_then(|record| record.get_ban_seconds_left(now)))
+    }
+
+    async fn register_lookup_failure(
+        &self,
+        client_ip: &str,
+        config: &LookupGuardConfig,
+    ) -> anyhow::Result<Option<u64>> {
+        let mut lookup_failures = self.lookup_failures.lock().unwrap();
+        let now = chrono::Utc::now().timestamp();
+        let record = lookup_failures.entry(client_ip.to_string()).or_default();
+        Ok(record.register_failure(now, config))
+    }
+
+    async fn store_chunk(&self, key: &str, data: &[u8], _ttl_seconds: u64) -> anyhow::Result<()> {
+        let mut chunks = self.chunks.lock().unwrap();
+        chunks.insert(key.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn load_chunk(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let chunks = self.chunks.lock().unwrap();
+        Ok(chunks.get(key).cloned())
+    }
+
+    async fn get_chunk_size(&self, key: &str) -> anyhow::Result<Option<u64>> {
+        let chunks = self.chunks.lock().unwrap();
+        Ok(chunks.get(key).map(|data| data.len() as u64))
+    }
+
+    async fn remove_chunks(&self, keys: &[String]) -> anyhow::Result<()> {
+        let mut chunks = self.chunks.lock().unwrap();
+        for key in keys {
+            chunks.remove(key);
+        }
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::config::model::RedisPoolConfig;
     use crate::secret::model::SecretDownloadPolicy;
+    use crate::secret::redis_pool::RedisConnectionPool;
     use crate::secret::storage::{DEFAULT_REDIS_CNN_URL, RedisSecretStorage, SecretStorage};
     use crate::tests::secret::get_sample_secret;
     use crate::tests::string::get_random_string;
 
     #[ignore]
-    #[test]
-    fn secret_with_one_time_download_should_be_removed_after_load() {
+    #[tokio::test]
+    async fn secret_with_one_time_download_should_be_removed_after_load() {
         let storage = get_storage();
 
         let mut secret = get_sample_secret();
         secret.download_policy = SecretDownloadPolicy::OneTime;
 
-        storage.store(&secret.id, &secret).unwrap();
+        storage.store(&secret.id, &secret).await.unwrap();
 
-        assert!(storage.load(&secret.id).unwrap().is_some());
-        assert!(storage.load(&secret.id).unwrap().is_none());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
     }
 
     #[ignore]
-    #[test]
-    fn secret_with_unlimited_time_download_should_not_be_removed_after_load() {
+    #[tokio::test]
+    async fn secret_with_unlimited_time_download_should_not_be_removed_after_load() {
         let storage = get_storage();
 
         let mut secret = get_sample_secret();
         secret.download_policy = SecretDownloadPolicy::Unlimited;
 
-        storage.store(&secret.id, &secret).unwrap();
+        storage.store(&secret.id, &secret).await.unwrap();
 
-        assert!(storage.load(&secret.id).unwrap().is_some());
-        assert!(storage.load(&secret.id).unwrap().is_some());
-        assert!(storage.load(&secret.id).unwrap().is_some());
-        assert!(storage.load(&secret.id).unwrap().is_some());
-        assert!(storage.load(&secret.id).unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
+        assert!(storage.load(&secret.id).await.unwrap().is_some());
     }
 
     #[ignore]
-    #[test]
-    fn remove_secret_test() {
+    #[tokio::test]
+    async fn secret_with_max_views_should_be_removed_after_last_view() {
+        let storage = get_storage();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(2);
+        secret.remaining_views = Some(2);
+
+        storage.store(&secret.id, &secret).await.unwrap();
+
+        let first = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(first.remaining_views, Some(1));
+        let second = storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(second.remaining_views, Some(0));
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn remove_secret_test() {
         let storage = get_storage();
 
         let mut secret = get_sample_secret();
         secret.download_policy = SecretDownloadPolicy::Unlimited;
+        secret.revocation_token_hash = Some("token-hash".to_string());
+
+        storage.store(&secret.id, &secret).await.unwrap();
+        assert!(
+            storage
+                .remove(&secret.id, "other-hash")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            storage.remove(&secret.id, "token-hash").await.unwrap(),
+            Some(secret.clone())
+        );
+
+        assert!(storage.load(&secret.id).await.unwrap().is_none());
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn secret_with_existing_id_should_not_be_overwritten() {
+        let storage = get_storage();
+
+        let secret = get_sample_secret();
+        let mut other_secret = get_sample_secret();
+        other_secret.id = secret.id.clone();
+
+        assert!(storage.store(&secret.id, &secret).await.unwrap());
+        assert!(
+            !storage
+                .store(&other_secret.id, &other_secret)
+                .await
+                .unwrap()
+        );
+
+        assert_eq!(storage.load(&secret.id).await.unwrap(), Some(secret));
+    }
+
+    #[ignore]
+    #[tokio::test]
+    async fn chunks_test() {
+        let storage = get_storage();
+
+        let key = get_random_string();
+
+        storage.store_chunk(&key, b"chunk-data", 60).await.unwrap();
+
+        assert_eq!(storage.get_chunk_size(&key).await.unwrap(), Some(10));
+        assert_eq!(
+            storage.load_chunk(&key).await.unwrap(),
+            Some(b"chunk-data".to_vec())
+        );
 
-        storage.store(&secret.id, &secret).unwrap();
-        storage.remove(&secret.id).unwrap();
+        storage
+            .remove_chunks(std::slice::from_ref(&key))
+            .await
+            .unwrap();
 
-        assert!(storage.load(&secret.id).unwrap().is_none());
+        assert!(storage.get_chunk_size(&key).await.unwrap().is_none());
+        assert!(storage.load_chunk(&key).await.unwrap().is_none());
     }
 
     #[ignore]
-    #[test]
-    fn return_none_for_unknown_secret() {
+    #[tokio::test]
+    async fn return_none_for_unknown_secret() {
         let storage = get_storage();
-        assert!(storage.load(&get_random_string()).unwrap().is_none());
+        assert!(storage.load(&get_random_string()).await.unwrap().is_none());
     }
 
     fn get_storage() -> RedisSecretStorage {
-        RedisSecretStorage::new(DEFAULT_REDIS_CNN_URL)
+        let pool =
+            RedisConnectionPool::new(DEFAULT_REDIS_CNN_URL, &RedisPoolConfig::default()).unwrap();
+        RedisSecretStorage::new(pool)
     }
 }
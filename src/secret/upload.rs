@@ -0,0 +1,765 @@
+use crate::limits::service::ClientLimits;
+use crate::secret::model::{
+    Secret, SecretChunks, SecretContentType, SecretDownloadPolicy, SecretFileMetadata,
+    SecretPayload, SecretTTL,
+};
+use crate::secret::storage::SecretStorage;
+use crate::secret::usecase::{
+    StoreSecretError, TtlLimits, generate_revocation_token, get_revocation_token_hash,
+    get_store_error, validate_new_secret,
+};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Upload session key suffix, the session keeps secret until upload is completed
+const UPLOAD_KEY_SUFFIX: &str = "upload";
+
+/// Marks file of used up secret, the file is removed once it's downloaded to the end
+const LAST_DOWNLOAD_KEY_SUFFIX: &str = "last";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum UploadSecretError {
+    #[error(transparent)]
+    Store(#[from] StoreSecretError),
+
+    #[error("chunk layout doesn't match file size")]
+    InvalidChunk,
+
+    /// Unknown id, wrong token and expired upload are indistinguishable for the caller
+    #[error("upload wasn't found or revocation token doesn't match")]
+    NotFound,
+
+    #[error("not all chunks have been uploaded")]
+    Incomplete,
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum LoadFileError {
+    #[error("file wasn't found")]
+    NotFound,
+
+    #[error("range isn't satisfiable for file of {size} bytes")]
+    RangeNotSatisfiable { size: u64 },
+
+    #[error("unable to load file")]
+    Storage,
+}
+
+/// Starts chunked file upload, the file is encrypted on frontend side chunk by chunk
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretUploadRequest {
+    /// Left empty by clients which send `encrypted_metadata`
+    #[serde(default)]
+    pub metadata: SecretFileMetadata,
+
+    /// Encrypted file name, type and size, the server never interprets it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_metadata: Option<SecretPayload>,
+
+    /// Server default TTL is used when not provided
+    #[serde(default)]
+    pub ttl: Option<SecretTTL>,
+
+    pub download_policy: SecretDownloadPolicy,
+
+    pub chunk_count: u32,
+
+    /// Size of every encrypted chunk except the last one
+    pub chunk_size: u64,
+
+    /// Total encrypted file size
+    pub size: u64,
+}
+
+/// Byte range of the file, both ends are inclusive
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl FileRange {
+    pub fn full(size: u64) -> FileRange {
+        FileRange {
+            start: 0,
+            end: size.saturating_sub(1),
+        }
+    }
+
+    pub fn get_length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+}
+
+/// Part of the chunk which falls into requested range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkPart {
+    pub index: u32,
+    pub from: usize,
+    pub to: usize,
+}
+
+fn get_upload_key(id: &str) -> String {
+    format!("{id}:{UPLOAD_KEY_SUFFIX}")
+}
+
+/// File manifest keeps chunk layout for downloads, readers don't touch the secret itself
+fn get_manifest_key(id: &str, file_key: &str) -> String {
+    format!("{id}:{file_key}")
+}
+
+fn get_chunk_key(id: &str, file_key: &str, index: u32) -> String {
+    format!("{id}:{file_key}:{index}")
+}
+
+fn get_last_download_key(id: &str, file_key: &str) -> String {
+    format!("{id}:{file_key}:{LAST_DOWNLOAD_KEY_SUFFIX}")
+}
+
+/// File key has the same entropy as secret id
+pub fn generate_file_key() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Keys of all chunks, the manifest and the last download mark of the file
+pub fn get_file_keys(id: &str, chunks: &SecretChunks) -> Vec<String> {
+    let mut keys: Vec<String> = (0..chunks.count)
+        .map(|index| get_chunk_key(id, &chunks.key, index))
+        .collect();
+    keys.push(get_manifest_key(id, &chunks.key));
+    keys.push(get_last_download_key(id, &chunks.key));
+    keys
+}
+
+/// Called when the view which used up the secret is given to the reader,
+/// the file stays available for this reader until downloaded to the end
+pub async fn mark_last_download(secret_storage: &dyn SecretStorage, secret: &Secret) {
+    let Some(chunks) = &secret.chunks else {
+        return;
+    };
+
+    let ttl_seconds = secret.ttl_seconds().unwrap_or_default().max(1);
+
+    // Mark isn't empty, storages treat empty chunks as missing
+    if let Err(e) = secret_storage
+        .store_chunk(
+            &get_last_download_key(&secret.id, &chunks.key),
+            &[1],
+            ttl_seconds,
+        )
+        .await
+    {
+        // The file still expires with the secret
+        error!(
+            "unable to mark last download of secret '{}': {}",
+            secret.id, e
+        );
+    }
+}
+
+/// File of used up secret is removed after the download which reaches its end
+pub async fn is_last_download(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    chunks: &SecretChunks,
+) -> Result<bool, LoadFileError> {
+    secret_storage
+        .get_chunk_size(&get_last_download_key(id, &chunks.key))
+        .await
+        .map(|size| size.is_some())
+        .map_err(|e| {
+            error!("unable to check last download: {}", e);
+            LoadFileError::Storage
+        })
+}
+
+/// Removes file of the removed secret, failures are logged, chunks expire with the secret anyway
+pub async fn remove_file(secret_storage: &dyn SecretStorage, id: &str, chunks: &SecretChunks) {
+    if let Err(e) = secret_storage
+        .remove_chunks(&get_file_keys(id, chunks))
+        .await
+    {
+        warn!("unable to remove file chunks of secret '{id}': {}", e);
+    }
+}
+
+/// Creates upload session, returns revocation token which authorizes chunk uploads.
+///
+/// Session expires at the same time as the secret, so do chunks.
+pub async fn start_upload(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    request: &SecretUploadRequest,
+    client_limits: &ClientLimits,
+    ttl_limits: &TtlLimits,
+) -> Result<String, UploadSecretError> {
+    if request.size > client_limits.encrypted_message_max_length
+        || request.chunk_size > client_limits.chunk_max_size
+    {
+        error!(
+            "file of {} bytes in chunks of {} bytes is bigger than allowed {} ({} per chunk)",
+            request.size,
+            request.chunk_size,
+            client_limits.encrypted_message_max_length,
+            client_limits.chunk_max_size
+        );
+        return Err(StoreSecretError::PayloadTooLarge.into());
+    }
+
+    let chunks = SecretChunks {
+        key: generate_file_key(),
+        count: request.chunk_count,
+        chunk_size: request.chunk_size,
+        size: request.size,
+    };
+
+    let layout_size = chunks.chunk_size.saturating_mul(chunks.count as u64);
+
+    if chunks.count == 0
+        || chunks.chunk_size == 0
+        || chunks.size > layout_size
+        || chunks.size <= layout_size - chunks.chunk_size
+    {
+        error!(
+            "{} chunks of {} bytes don't match file size {}",
+            chunks.count, chunks.chunk_size, chunks.size
+        );
+        return Err(UploadSecretError::InvalidChunk);
+    }
+
+    let revocation_token = generate_revocation_token();
+
+    let mut secret = Secret {
+        id: id.to_string(),
+        content_type: SecretContentType::File,
+        metadata: request.metadata.clone(),
+        encrypted_metadata: request.encrypted_metadata.clone(),
+        payload: SecretPayload::default(),
+        ttl: request.ttl.clone(),
+        download_policy: request.download_policy.clone(),
+        remaining_views: request.download_policy.initial_views(),
+        revocation_token_hash: Some(get_revocation_token_hash(&revocation_token)),
+        chunks: Some(chunks),
+    };
+
+    let ttl_seconds = validate_new_secret(&secret, ttl_limits)?.as_seconds();
+
+    // Absolute expiration keeps the secret lifetime from the upload start
+    secret.ttl = Some(SecretTTL::ExpiresAt(
+        chrono::Utc::now().timestamp() + ttl_seconds as i64,
+    ));
+
+    secret_storage
+        .store_upload_session(&get_upload_key(id), &secret, ttl_seconds)
+        .await
+        .map_err(get_store_error)?;
+
+    info!("upload of secret '{id}' has been started: {}", secret);
+
+    Ok(revocation_token)
+}
+
+async fn load_upload(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    revocation_token: &str,
+) -> Result<(Secret, SecretChunks), UploadSecretError> {
+    let secret = match secret_storage
+        .load_upload_session(&get_upload_key(id))
+        .await
+    {
+        Ok(Some(secret)) => secret,
+        Ok(None) => {
+            info!("upload wasn't found by id '{id}'");
+            return Err(UploadSecretError::NotFound);
+        }
+        Err(e) => {
+            error!("unable to load upload: {}", e);
+            return Err(StoreSecretError::Storage.into());
+        }
+    };
+
+    if !secret.is_revocable_by(&get_revocation_token_hash(revocation_token))
+        || secret.ttl_seconds().unwrap_or_default() == 0
+    {
+        info!("upload wasn't found by id '{id}' and revocation token");
+        return Err(UploadSecretError::NotFound);
+    }
+
+    let chunks = secret.chunks.clone().ok_or(UploadSecretError::NotFound)?;
+
+    Ok((secret, chunks))
+}
+
+/// Stores chunk of the upload, the chunk size must match the layout given on start.
+///
+/// Chunks may be uploaded in any order, repeated upload replaces the chunk.
+pub async fn upload_chunk(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    revocation_token: &str,
+    index: u32,
+    data: &[u8],
+) -> Result<(), UploadSecretError> {
+    let (secret, chunks) = load_upload(secret_storage, id, revocation_token).await?;
+
+    if chunks.get_chunk_size(index) != Some(data.len() as u64) {
+        error!(
+            "chunk {} of {} bytes doesn't match upload of secret '{id}'",
+            index,
+            data.len()
+        );
+        return Err(UploadSecretError::InvalidChunk);
+    }
+
+    secret_storage
+        .store_chunk(
+            &get_chunk_key(id, &chunks.key, index),
+            data,
+            secret.ttl_seconds().unwrap_or_default(),
+        )
+        .await
+        .map_err(get_store_error)?;
+
+    Ok(())
+}
+
+/// Makes uploaded file available to readers, returns the stored secret
+pub async fn complete_upload(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    revocation_token: &str,
+) -> Result<Secret, UploadSecretError> {
+    let (secret, chunks) = load_upload(secret_storage, id, revocation_token).await?;
+
+    for index in 0..chunks.count {
+        let size = secret_storage
+            .get_chunk_size(&get_chunk_key(id, &chunks.key, index))
+            .await
+            .map_err(get_store_error)?;
+
+        if size != chunks.get_chunk_size(index) {
+            info!("chunk {index} of secret '{id}' is missing");
+            return Err(UploadSecretError::Incomplete);
+        }
+    }
+
+    let ttl_seconds = secret.ttl_seconds().unwrap_or_default();
+
+    let manifest = serde_json::to_vec(&chunks).map_err(|e| {
+        error!("manifest serialization error: {}", e);
+        StoreSecretError::Storage
+    })?;
+
+    secret_storage
+        .store_chunk(&get_manifest_key(id, &chunks.key), &manifest, ttl_seconds)
+        .await
+        .map_err(get_store_error)?;
+
+    match secret_storage.store(id, &secret).await {
+        Ok(true) => {}
+        Ok(false) => {
+            error!("secret with id '{id}' already exists");
+            return Err(StoreSecretError::AlreadyExists.into());
+        }
+        Err(e) => return Err(get_store_error(e).into()),
+    }
+
+    if let Err(e) = secret_storage.remove_chunks(&[get_upload_key(id)]).await {
+        warn!("unable to remove upload of secret '{id}': {}", e);
+    }
+
+    info!("upload of secret '{id}' has been completed");
+
+    Ok(secret)
+}
+
+/// Returns chunk layout of the file, the file key is given to readers with the secret
+pub async fn load_file(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    file_key: &str,
+) -> Result<SecretChunks, LoadFileError> {
+    let value = match secret_storage
+        .load_chunk(&get_manifest_key(id, file_key))
+        .await
+    {
+        Ok(Some(value)) => value,
+        Ok(None) => {
+            info!("file wasn't found by secret id '{id}' and file key");
+            return Err(LoadFileError::NotFound);
+        }
+        Err(e) => {
+            error!("unable to load file: {}", e);
+            return Err(LoadFileError::Storage);
+        }
+    };
+
+    serde_json::from_slice(&value).map_err(|e| {
+        error!("unable to deserialize file manifest: {}", e);
+        LoadFileError::Storage
+    })
+}
+
+pub async fn load_file_chunk(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    chunks: &SecretChunks,
+    index: u32,
+) -> Result<Vec<u8>, LoadFileError> {
+    match secret_storage
+        .load_chunk(&get_chunk_key(id, &chunks.key, index))
+        .await
+    {
+        Ok(Some(data)) => Ok(data),
+        Ok(None) => {
+            info!("chunk {index} of secret '{id}' wasn't found");
+            Err(LoadFileError::NotFound)
+        }
+        Err(e) => {
+            error!("unable to load chunk: {}", e);
+            Err(LoadFileError::Storage)
+        }
+    }
+}
+
+/// Parses single `bytes=` range of `Range` header, multiple ranges aren't supported
+pub fn parse_range(value: &str, size: u64) -> Result<FileRange, LoadFileError> {
+    let not_satisfiable = LoadFileError::RangeNotSatisfiable { size };
+
+    let Some((start, end)) = value
+        .trim()
+        .strip_prefix("bytes=")
+        .and_then(|range| range.split_once('-'))
+    else {
+        return Err(not_satisfiable);
+    };
+
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => {
+            let suffix: u64 = suffix.parse().map_err(|_| not_satisfiable.clone())?;
+            (size.saturating_sub(suffix), size.saturating_sub(1))
+        }
+        (start, "") => (
+            start.parse().map_err(|_| not_satisfiable.clone())?,
+            size.saturating_sub(1),
+        ),
+        (start, end) => (
+            start.parse().map_err(|_| not_satisfiable.clone())?,
+            end.parse::<u64>()
+                .map_err(|_| not_satisfiable.clone())?
+                .min(size.saturating_sub(1)),
+        ),
+    };
+
+    if size == 0 || start > end || start >= size {
+        return Err(not_satisfiable);
+    }
+
+    Ok(FileRange { start, end })
+}
+
+/// Returns chunk parts which make up the range, in order
+pub fn get_chunk_parts(chunks: &SecretChunks, range: &FileRange) -> Vec<ChunkPart> {
+    let first = range.start / chunks.chunk_size;
+    let last = range.end / chunks.chunk_size;
+
+    (first..=last)
+        .map(|index| {
+            let chunk_start = index * chunks.chunk_size;
+            ChunkPart {
+                index: index as u32,
+                from: (range.start.max(chunk_start) - chunk_start) as usize,
+                to: ((range.end + 1).min(chunk_start + chunks.chunk_size) - chunk_start) as usize,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::{
+        DEFAULT_TTL_DEFAULT, DEFAULT_TTL_MAX, DEFAULT_TTL_MIN, MemoryStorageConfig,
+    };
+    use crate::secret::memory_storage::MemorySecretStorage;
+    use crate::secret::usecase::{load_secret, remove_secret};
+    use crate::tests::secret::get_sample_secret;
+
+    const TTL_LIMITS: TtlLimits = TtlLimits {
+        min: DEFAULT_TTL_MIN,
+        max: DEFAULT_TTL_MAX,
+        default: DEFAULT_TTL_DEFAULT,
+    };
+
+    const CLIENT_LIMITS: ClientLimits = ClientLimits {
+        message_max_length: 1024,
+        file_max_size: 1000,
+        encrypted_message_max_length: 1350,
+        chunk_max_size: 100,
+    };
+
+    fn get_storage() -> MemorySecretStorage {
+        MemorySecretStorage::new(&MemoryStorageConfig {
+            max_bytes: 1_000_000,
+            max_entries: 100,
+            sweep_interval_seconds: 60,
+        })
+    }
+
+    fn get_request(chunk_count: u32, chunk_size: u64, size: u64) -> SecretUploadRequest {
+        SecretUploadRequest {
+            metadata: get_sample_secret().metadata,
+            encrypted_metadata: None,
+            ttl: None,
+            download_policy: SecretDownloadPolicy::Unlimited,
+            chunk_count,
+            chunk_size,
+            size,
+        }
+    }
+
+    fn get_chunks(count: u32, chunk_size: u64, size: u64) -> SecretChunks {
+        SecretChunks {
+            key: generate_file_key(),
+            count,
+            chunk_size,
+            size,
+        }
+    }
+
+    #[tokio::test]
+    async fn uploaded_file_should_be_available_to_readers() {
+        let storage = get_storage();
+
+        let token = start_upload(
+            &storage,
+            "abc",
+            &get_request(3, 100, 250),
+            &CLIENT_LIMITS,
+            &TTL_LIMITS,
+        )
+        .await
+        .unwrap();
+
+        upload_chunk(&storage, "abc", &token, 2, &[3; 50])
+            .await
+            .unwrap();
+        upload_chunk(&storage, "abc", &token, 0, &[1; 100])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            complete_upload(&storage, "abc", &token).await,
+            Err(UploadSecretError::Incomplete)
+        );
+
+        upload_chunk(&storage, "abc", &token, 1, &[2; 100])
+            .await
+            .unwrap();
+
+        let stored = complete_upload(&storage, "abc", &token).await.unwrap();
+
+        let secret = load_secret(&storage, "abc", "10.0.0.1", &Default::default())
+            .await
+            .unwrap();
+        let chunks = secret.chunks.clone().unwrap();
+
+        assert_eq!(secret.payload_size(), 250);
+        assert_eq!(secret.content_type, SecretContentType::File);
+        assert_eq!(chunks, stored.chunks.unwrap());
+        assert_eq!(
+            load_file(&storage, "abc", &chunks.key).await,
+            Ok(chunks.clone())
+        );
+        assert_eq!(
+            load_file_chunk(&storage, "abc", &chunks, 2).await,
+            Ok(vec![3; 50])
+        );
+
+        // Session is removed, the upload can't be repeated
+        assert_eq!(
+            upload_chunk(&storage, "abc", &token, 0, &[1; 100]).await,
+            Err(UploadSecretError::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn chunks_should_be_removed_with_secret() {
+        let storage = get_storage();
+
+        let token = start_upload(
+            &storage,
+            "abc",
+            &get_request(1, 100, 10),
+            &CLIENT_LIMITS,
+            &TTL_LIMITS,
+        )
+        .await
+        .unwrap();
+        upload_chunk(&storage, "abc", &token, 0, &[1; 10])
+            .await
+            .unwrap();
+        let secret = complete_upload(&storage, "abc", &token).await.unwrap();
+        let chunks = secret.chunks.unwrap();
+
+        remove_secret(&storage, "abc", &token).await.unwrap();
+
+        assert_eq!(
+            load_file(&storage, "abc", &chunks.key).await,
+            Err(LoadFileError::NotFound)
+        );
+        assert_eq!(
+            load_file_chunk(&storage, "abc", &chunks, 0).await,
+            Err(LoadFileError::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn upload_should_respect_client_limits() {
+        let storage = get_storage();
+
+        for request in [get_request(2, 101, 150), get_request(20, 100, 1351)] {
+            assert_eq!(
+                start_upload(&storage, "abc", &request, &CLIENT_LIMITS, &TTL_LIMITS).await,
+                Err(UploadSecretError::Store(StoreSecretError::PayloadTooLarge))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn upload_with_invalid_layout_should_be_rejected() {
+        let storage = get_storage();
+
+        for request in [
+            get_request(0, 100, 0),
+            get_request(2, 100, 201),
+            get_request(2, 100, 100),
+            get_request(1, 0, 0),
+        ] {
+            assert_eq!(
+                start_upload(&storage, "abc", &request, &CLIENT_LIMITS, &TTL_LIMITS).await,
+                Err(UploadSecretError::InvalidChunk)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn chunk_should_match_layout_and_token() {
+        let storage = get_storage();
+
+        let token = start_upload(
+            &storage,
+            "abc",
+            &get_request(2, 100, 150),
+            &CLIENT_LIMITS,
+            &TTL_LIMITS,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            upload_chunk(&storage, "abc", &token, 0, &[1; 50]).await,
+            Err(UploadSecretError::InvalidChunk)
+        );
+        assert_eq!(
+            upload_chunk(&storage, "abc", &token, 2, &[1; 50]).await,
+            Err(UploadSecretError::InvalidChunk)
+        );
+        assert_eq!(
+            upload_chunk(&storage, "abc", "other-token", 1, &[1; 50]).await,
+            Err(UploadSecretError::NotFound)
+        );
+        assert_eq!(
+            upload_chunk(&storage, "other", &token, 1, &[1; 50]).await,
+            Err(UploadSecretError::NotFound)
+        );
+    }
+
+    #[test]
+    fn chunk_sizes_should_follow_layout() {
+        let chunks = get_chunks(3, 100, 250);
+
+        assert_eq!(chunks.get_chunk_size(0), Some(100));
+        assert_eq!(chunks.get_chunk_size(2), Some(50));
+        assert_eq!(chunks.get_chunk_size(3), None);
+    }
+
+    #[test]
+    fn range_header_should_be_parsed() {
+        assert_eq!(
+            parse_range("bytes=0-99", 250),
+            Ok(FileRange { start: 0, end: 99 })
+        );
+        assert_eq!(
+            parse_range("bytes=200-", 250),
+            Ok(FileRange {
+                start: 200,
+                end: 249
+            })
+        );
+        assert_eq!(
+            parse_range("bytes=-10", 250),
+            Ok(FileRange {
+                start: 240,
+                end: 249
+            })
+        );
+        assert_eq!(
+            parse_range("bytes=100-1000", 250),
+            Ok(FileRange {
+                start: 100,
+                end: 249
+            })
+        );
+
+        for value in [
+            "bytes=250-",
+            "bytes=10-5",
+            "bytes=0-1,5-6",
+            "items=0-1",
+            "bytes=a-",
+        ] {
+            assert_eq!(
+                parse_range(value, 250),
+                Err(LoadFileError::RangeNotSatisfiable { size: 250 })
+            );
+        }
+    }
+
+    #[test]
+    fn range_should_be_split_into_chunk_parts() {
+        let chunks = get_chunks(3, 100, 250);
+
+        assert_eq!(
+            get_chunk_parts(
+                &chunks,
+                &FileRange {
+                    start: 50,
+                    end: 220
+                }
+            ),
+            vec![
+                ChunkPart {
+                    index: 0,
+                    from: 50,
+                    to: 100
+                },
+                ChunkPart {
+                    index: 1,
+                    from: 0,
+                    to: 100
+                },
+                ChunkPart {
+                    index: 2,
+                    from: 0,
+                    to: 21
+                },
+            ]
+        );
+        assert_eq!(get_chunk_parts(&chunks, &FileRange::full(250)).len(), 3);
+    }
+}
@@ -1,75 +1,687 @@
-use crate::secret::model::Secret;
-use crate::secret::storage::SecretStorage;
-use anyhow::anyhow;
-use log::error;
+use crate::config::model::{AppConfig, LookupGuardConfig};
+use crate::secret::model::{
+    ENCRYPTED_METADATA_MAX_LENGTH, Secret, SecretDownloadPolicy, SecretFileMetadata, SecretTTL,
+};
+use crate::secret::storage::{SecretExpiredError, SecretStorage, StorageFullError};
+use crate::secret::upload::{mark_last_download, remove_file};
+use log::{error, info, warn};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use uuid::Uuid;
 
-pub fn store_secret(
+#[derive(Error, Debug, PartialEq)]
+pub enum StoreSecretError {
+    #[error("payload length is bigger than allowed")]
+    PayloadTooLarge,
+
+    #[error("ttl is outside of allowed range")]
+    InvalidTtl,
+
+    #[error("invalid download policy")]
+    InvalidDownloadPolicy,
+
+    #[error("cleartext metadata can't be sent along with encrypted one")]
+    InvalidMetadata,
+
+    #[error("secret with the same id already exists")]
+    AlreadyExists,
+
+    #[error("storage capacity exceeded")]
+    StorageFull,
+
+    #[error("unable to store secret")]
+    Storage,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum LoadSecretError {
+    #[error("client is banned, retry after {retry_after_seconds} seconds")]
+    Banned { retry_after_seconds: u64 },
+
+    #[error("secret wasn't found")]
+    NotFound,
+
+    #[error("secret has expired")]
+    Expired,
+
+    #[error("unable to load secret")]
+    Storage,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RemoveSecretError {
+    /// Unknown id and wrong token are indistinguishable for the caller
+    #[error("secret wasn't found or revocation token doesn't match")]
+    NotFound,
+
+    #[error("unable to remove secret")]
+    Storage,
+}
+
+/// Secret lifetime limits in seconds
+#[derive(Clone, Debug, PartialEq)]
+pub struct TtlLimits {
+    pub min: u64,
+    pub max: u64,
+    pub default: u64,
+}
+
+impl From<&AppConfig> for TtlLimits {
+    fn from(config: &AppConfig) -> Self {
+        TtlLimits {
+            min: config.ttl_min,
+            max: config.ttl_max,
+            default: config.ttl_default,
+        }
+    }
+}
+
+/// Generates a secret id with 122 bits of entropy (UUID v4 without hyphens)
+pub fn generate_secret_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// Revocation token has the same entropy as secret id, only its hash is stored
+pub fn generate_revocation_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+pub fn get_revocation_token_hash(revocation_token: &str) -> String {
+    format!("{:x}", Sha256::digest(revocation_token.as_bytes()))
+}
+
+/// Checks download policy, ttl and metadata of a new secret, returns ttl to store it with
+pub fn validate_new_secret(
+    secret: &Secret,
+    ttl_limits: &TtlLimits,
+) -> Result<SecretTTL, StoreSecretError> {
+    if secret.download_policy == SecretDownloadPolicy::MaxViews(0) {
+        error!("max views download policy requires at least one view");
+        return Err(StoreSecretError::InvalidDownloadPolicy);
+    }
+
+    if let Some(encrypted_metadata) = &secret.encrypted_metadata {
+        if secret.metadata != SecretFileMetadata::default() {
+            error!("secret has both cleartext and encrypted metadata");
+            return Err(StoreSecretError::InvalidMetadata);
+        }
+
+        if encrypted_metadata.len() > ENCRYPTED_METADATA_MAX_LENGTH {
+            error!(
+                "encrypted metadata length ({}) is bigger than allowed {}",
+                encrypted_metadata.len(),
+                ENCRYPTED_METADATA_MAX_LENGTH
+            );
+            return Err(StoreSecretError::PayloadTooLarge);
+        }
+    }
+
+    let ttl = secret
+        .ttl
+        .clone()
+        .unwrap_or(SecretTTL::Seconds(ttl_limits.default));
+    let ttl_seconds = ttl.as_seconds();
+
+    if ttl_seconds < ttl_limits.min || ttl_seconds > ttl_limits.max {
+        error!(
+            "ttl {} seconds is outside of allowed range {}..={}",
+            ttl_seconds, ttl_limits.min, ttl_limits.max
+        );
+        return Err(StoreSecretError::InvalidTtl);
+    }
+
+    Ok(ttl)
+}
+
+/// Maps storage failure to store error, capacity errors are reported separately
+pub fn get_store_error(error: anyhow::Error) -> StoreSecretError {
+    error!("unable to store secret: {}", error);
+
+    if error.downcast_ref::<StorageFullError>().is_some() {
+        StoreSecretError::StorageFull
+    } else {
+        StoreSecretError::Storage
+    }
+}
+
+/// Returns revocation token for the creator, the secret keeps only its hash
+pub async fn store_secret(
     secret_storage: &dyn SecretStorage,
     secret: &Secret,
     payload_max_length: u64,
-) -> anyhow::Result<()> {
-    let mut payload = secret.payload.to_string();
+    ttl_limits: &TtlLimits,
+) -> Result<String, StoreSecretError> {
+    let ttl = validate_new_secret(secret, ttl_limits)?;
 
-    if payload.len() <= payload_max_length as usize {
-        payload.truncate(payload_max_length as usize);
+    if secret.payload.len() <= payload_max_length as usize {
+        let revocation_token = generate_revocation_token();
 
         let new_secret = Secret {
             id: secret.id.to_string(),
             metadata: secret.metadata.clone(),
-            payload: payload.to_string(),
-            ttl: secret.ttl.clone(),
+            encrypted_metadata: secret.encrypted_metadata.clone(),
+            payload: secret.payload.clone(),
+            ttl: Some(ttl),
             download_policy: secret.download_policy.clone(),
+            remaining_views: secret.download_policy.initial_views(),
             content_type: secret.content_type.clone(),
+            revocation_token_hash: Some(get_revocation_token_hash(&revocation_token)),
+            chunks: None,
         };
 
-        match secret_storage.store(&secret.id, &new_secret) {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                error!("unable to store secret: {}", e);
-                Err(anyhow!("unable to store secret"))
+        match secret_storage.store(&secret.id, &new_secret).await {
+            Ok(true) => Ok(revocation_token),
+            Ok(false) => {
+                error!("secret with id '{}' already exists", secret.id);
+                Err(StoreSecretError::AlreadyExists)
             }
+            Err(e) => Err(get_store_error(e)),
         }
     } else {
         error!(
             "payload length ({}) is bigger than allowed {}",
-            payload.len(),
+            secret.payload.len(),
             payload_max_length
         );
-        Err(anyhow!("payload length is bigger than allowed"))
+        Err(StoreSecretError::PayloadTooLarge)
+    }
+}
+
+/// Loads secret and tracks failed lookups of the client, banned clients are rejected
+/// without touching secrets
+pub async fn load_secret(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    client_ip: &str,
+    lookup_guard: &LookupGuardConfig,
+) -> Result<Secret, LoadSecretError> {
+    if lookup_guard.enabled {
+        match secret_storage.get_lookup_ban(client_ip).await {
+            Ok(Some(retry_after_seconds)) => {
+                info!("client {client_ip} is banned for {retry_after_seconds} seconds");
+                return Err(LoadSecretError::Banned {
+                    retry_after_seconds,
+                });
+            }
+            Ok(None) => {}
+            Err(e) => warn!("unable to check lookup ban for client {client_ip}: {}", e),
+        }
+    }
+
+    match secret_storage.load(id).await {
+        Ok(Some(mut secret)) => {
+            if secret.is_used_up() {
+                mark_last_download(secret_storage, &secret).await;
+            }
+            secret.revocation_token_hash = None;
+            Ok(secret)
+        }
+        Ok(None) => {
+            if lookup_guard.enabled {
+                match secret_storage
+                    .register_lookup_failure(client_ip, lookup_guard)
+                    .await
+                {
+                    Ok(Some(ban_seconds)) => warn!(
+                        "client {client_ip} is banned for {ban_seconds} seconds after failed lookups"
+                    ),
+                    Ok(None) => {}
+                    Err(e) => error!(
+                        "unable to register lookup failure for client {client_ip}: {}",
+                        e
+                    ),
+                }
+            }
+            Err(LoadSecretError::NotFound)
+        }
+        Err(e) if e.downcast_ref::<SecretExpiredError>().is_some() => {
+            info!("{}", e);
+            Err(LoadSecretError::Expired)
+        }
+        Err(e) => {
+            error!("unable to load secret: {}", e);
+            Err(LoadSecretError::Storage)
+        }
+    }
+}
+
+/// Removes secret on behalf of its creator, returns the removed secret without revocation token hash
+pub async fn remove_secret(
+    secret_storage: &dyn SecretStorage,
+    id: &str,
+    revocation_token: &str,
+) -> Result<Secret, RemoveSecretError> {
+    let revocation_token_hash = get_revocation_token_hash(revocation_token);
+
+    match secret_storage.remove(id, &revocation_token_hash).await {
+        Ok(Some(mut secret)) => {
+            if let Some(chunks) = &secret.chunks {
+                remove_file(secret_storage, id, chunks).await;
+            }
+            secret.revocation_token_hash = None;
+            Ok(secret)
+        }
+        Ok(None) => Err(RemoveSecretError::NotFound),
+        Err(e) => {
+            error!("unable to remove secret: {}", e);
+            Err(RemoveSecretError::Storage)
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::secret::model::{SecretDownloadPolicy, SecretTTL};
-    use crate::secret::storage::{DEFAULT_REDIS_CNN_URL, RedisSecretStorage};
-    use crate::secret::usecase::store_secret;
+    use crate::config::model::{
+        DEFAULT_TTL_DEFAULT, DEFAULT_TTL_MAX, DEFAULT_TTL_MIN, LookupGuardConfig,
+        MemoryStorageConfig, RedisPoolConfig,
+    };
+    use crate::secret::memory_storage::MemorySecretStorage;
+    use crate::secret::model::{
+        ENCRYPTED_METADATA_MAX_LENGTH, SecretDownloadPolicy, SecretFileMetadata, SecretTTL,
+    };
+    use crate::secret::redis_pool::RedisConnectionPool;
+    use crate::secret::storage::{
+        DEFAULT_REDIS_CNN_URL, MockSecretStorage, RedisSecretStorage, SecretStorage,
+    };
+    use crate::secret::usecase::{
+        LoadSecretError, RemoveSecretError, StoreSecretError, TtlLimits, generate_secret_id,
+        get_revocation_token_hash, load_secret, remove_secret, store_secret,
+    };
     use crate::tests::secret::get_sample_secret;
     use crate::tests::string::get_random_string;
 
+    const TTL_LIMITS: TtlLimits = TtlLimits {
+        min: DEFAULT_TTL_MIN,
+        max: DEFAULT_TTL_MAX,
+        default: DEFAULT_TTL_DEFAULT,
+    };
+
     #[ignore]
-    #[test]
-    fn valid_payload_length_test() {
-        let secret_storage = RedisSecretStorage::new(DEFAULT_REDIS_CNN_URL);
+    #[tokio::test]
+    async fn valid_payload_length_test() {
+        let secret_storage = get_redis_storage();
 
         let mut secret = get_sample_secret();
-        secret.payload = get_random_string();
+        secret.payload = get_random_string().into();
         secret.download_policy = SecretDownloadPolicy::Unlimited;
-        secret.ttl = SecretTTL::OneDay;
+        secret.ttl = Some(SecretTTL::OneDay);
 
-        assert!(store_secret(&secret_storage, &secret, 3000).is_ok());
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
     }
 
     #[ignore]
-    #[test]
-    fn return_error_for_too_large_payload() {
-        let secret_storage = RedisSecretStorage::new(DEFAULT_REDIS_CNN_URL);
+    #[tokio::test]
+    async fn return_error_for_too_large_payload() {
+        let secret_storage = get_redis_storage();
+
+        let mut secret = get_sample_secret();
+        secret.payload = get_random_string().into();
+        secret.download_policy = SecretDownloadPolicy::Unlimited;
+        secret.ttl = Some(SecretTTL::OneDay);
+
+        assert!(
+            store_secret(&secret_storage, &secret, 3, &TTL_LIMITS)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn return_error_for_existing_secret_id() {
+        let secret_storage = MockSecretStorage::new();
+
+        let secret = get_sample_secret();
+
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS).await,
+            Err(StoreSecretError::AlreadyExists)
+        );
+    }
+
+    #[tokio::test]
+    async fn return_error_for_full_storage() {
+        let secret_storage = MemorySecretStorage::new(&MemoryStorageConfig {
+            max_bytes: 1_000_000,
+            max_entries: 1,
+            sweep_interval_seconds: 60,
+        });
+
+        let secret = get_sample_secret();
+        let other_secret = get_sample_secret();
+
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            store_secret(&secret_storage, &other_secret, 3000, &TTL_LIMITS).await,
+            Err(StoreSecretError::StorageFull)
+        );
+    }
+
+    #[tokio::test]
+    async fn max_views_secret_should_be_stored_with_views_counter() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(3);
+        secret.remaining_views = Some(100);
+
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+
+        let stored = secret_storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(stored.remaining_views, Some(2));
+    }
+
+    #[tokio::test]
+    async fn return_error_for_zero_max_views() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::MaxViews(0);
+
+        assert_eq!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS).await,
+            Err(StoreSecretError::InvalidDownloadPolicy)
+        );
+    }
+
+    #[tokio::test]
+    async fn secret_with_encrypted_metadata_should_be_stored_as_is() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.metadata = SecretFileMetadata::default();
+        secret.encrypted_metadata = Some(vec![0, 1, 255].into());
+
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+
+        let stored = secret_storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(stored.encrypted_metadata, secret.encrypted_metadata);
+        assert_eq!(stored.metadata, SecretFileMetadata::default());
+    }
+
+    #[tokio::test]
+    async fn return_error_for_invalid_encrypted_metadata() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.encrypted_metadata = Some(vec![1; 16].into());
+
+        assert_eq!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS).await,
+            Err(StoreSecretError::InvalidMetadata)
+        );
+
+        secret.metadata = SecretFileMetadata::default();
+        secret.encrypted_metadata = Some(vec![1; ENCRYPTED_METADATA_MAX_LENGTH + 1].into());
+
+        // Only ciphertext length is checked, the payload itself fits
+        assert_eq!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS).await,
+            Err(StoreSecretError::PayloadTooLarge)
+        );
+    }
+
+    #[tokio::test]
+    async fn secret_without_ttl_should_be_stored_with_default_ttl() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.ttl = None;
+
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+
+        let stored = secret_storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(stored.ttl, Some(SecretTTL::Seconds(DEFAULT_TTL_DEFAULT)));
+    }
+
+    #[tokio::test]
+    async fn secret_with_ttl_in_allowed_range_should_be_stored() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.ttl = Some(SecretTTL::Seconds(DEFAULT_TTL_MIN));
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+
+        let mut secret = get_sample_secret();
+        secret.ttl = Some(SecretTTL::ExpiresAt(chrono::Utc::now().timestamp() + 600));
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+
+        let mut secret = get_sample_secret();
+        secret.ttl = Some(SecretTTL::OneWeek);
+        assert!(
+            store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn return_error_for_ttl_out_of_range() {
+        let secret_storage = MockSecretStorage::new();
+
+        for ttl in [
+            SecretTTL::Seconds(DEFAULT_TTL_MIN - 1),
+            SecretTTL::Seconds(DEFAULT_TTL_MAX + 1),
+            SecretTTL::ExpiresAt(chrono::Utc::now().timestamp() - 600),
+        ] {
+            let mut secret = get_sample_secret();
+            secret.ttl = Some(ttl);
+
+            assert_eq!(
+                store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS).await,
+                Err(StoreSecretError::InvalidTtl)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn legacy_ttl_should_be_limited_by_server_config() {
+        let secret_storage = MockSecretStorage::new();
+
+        let ttl_limits = TtlLimits {
+            min: 60,
+            max: 60 * 60 * 24,
+            default: 60 * 60,
+        };
+
+        let mut secret = get_sample_secret();
+        secret.ttl = Some(SecretTTL::OneWeek);
+
+        assert_eq!(
+            store_secret(&secret_storage, &secret, 3000, &ttl_limits).await,
+            Err(StoreSecretError::InvalidTtl)
+        );
+    }
+
+    #[tokio::test]
+    async fn client_should_be_banned_after_failed_lookups() {
+        let secret_storage = MockSecretStorage::new();
+        let lookup_guard = LookupGuardConfig {
+            max_failures: 2,
+            ..LookupGuardConfig::default()
+        };
+
+        let secret = get_sample_secret();
+        store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(
+                load_secret(&secret_storage, "unknown", "10.0.0.1", &lookup_guard).await,
+                Err(LoadSecretError::NotFound)
+            );
+        }
+
+        assert!(matches!(
+            load_secret(&secret_storage, &secret.id, "10.0.0.1", &lookup_guard).await,
+            Err(LoadSecretError::Banned { .. })
+        ));
+
+        // Other clients are not affected
+        assert!(
+            load_secret(&secret_storage, &secret.id, "10.0.0.2", &lookup_guard)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn disabled_lookup_guard_should_not_ban_clients() {
+        let secret_storage = MockSecretStorage::new();
+        let lookup_guard = LookupGuardConfig {
+            enabled: false,
+            max_failures: 1,
+            ..LookupGuardConfig::default()
+        };
+
+        for _ in 0..3 {
+            assert_eq!(
+                load_secret(&secret_storage, "unknown", "10.0.0.1", &lookup_guard).await,
+                Err(LoadSecretError::NotFound)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn secret_should_be_removed_with_revocation_token() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.download_policy = SecretDownloadPolicy::Unlimited;
+
+        let revocation_token = store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            remove_secret(&secret_storage, &secret.id, "wrong-token").await,
+            Err(RemoveSecretError::NotFound)
+        );
+        assert!(
+            remove_secret(&secret_storage, &secret.id, &revocation_token)
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            remove_secret(&secret_storage, &secret.id, &revocation_token).await,
+            Err(RemoveSecretError::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn revocation_token_hash_should_not_be_returned_to_readers() {
+        let secret_storage = MockSecretStorage::new();
+
+        let mut secret = get_sample_secret();
+        secret.revocation_token_hash = Some("client-supplied".to_string());
+
+        let revocation_token = store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+            .await
+            .unwrap();
+
+        let stored = secret_storage.load(&secret.id).await.unwrap().unwrap();
+        assert_eq!(
+            stored.revocation_token_hash,
+            Some(get_revocation_token_hash(&revocation_token))
+        );
 
         let mut secret = get_sample_secret();
-        secret.payload = get_random_string();
         secret.download_policy = SecretDownloadPolicy::Unlimited;
-        secret.ttl = SecretTTL::OneDay;
+        store_secret(&secret_storage, &secret, 3000, &TTL_LIMITS)
+            .await
+            .unwrap();
+
+        let loaded = load_secret(
+            &secret_storage,
+            &secret.id,
+            "10.0.0.1",
+            &LookupGuardConfig::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(loaded.revocation_token_hash, None);
+    }
+
+    #[tokio::test]
+    async fn legacy_secret_without_revocation_token_should_not_be_removed() {
+        let secret_storage = MockSecretStorage::new();
+
+        let secret = get_sample_secret();
+        secret_storage.store(&secret.id, &secret).await.unwrap();
+
+        assert_eq!(
+            remove_secret(&secret_storage, &secret.id, "").await,
+            Err(RemoveSecretError::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn return_error_for_expired_secret() {
+        let secret_storage = MemorySecretStorage::new(&MemoryStorageConfig::default());
+
+        let mut secret = get_sample_secret();
+        secret.ttl = Some(SecretTTL::ExpiresAt(chrono::Utc::now().timestamp() + 1));
+        secret_storage.store(&secret.id, &secret).await.unwrap();
+
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+        assert_eq!(
+            load_secret(
+                &secret_storage,
+                &secret.id,
+                "10.0.0.1",
+                &LookupGuardConfig::default()
+            )
+            .await,
+            Err(LoadSecretError::Expired)
+        );
+    }
+
+    #[test]
+    fn generated_secret_ids_should_be_unique() {
+        let first = generate_secret_id();
+        let second = generate_secret_id();
+
+        assert_eq!(first.len(), 32);
+        assert_ne!(first, second);
+    }
 
-        assert!(store_secret(&secret_storage, &secret, 3).is_err());
+    fn get_redis_storage() -> RedisSecretStorage {
+        let pool =
+            RedisConnectionPool::new(DEFAULT_REDIS_CNN_URL, &RedisPoolConfig::default()).unwrap();
+        RedisSecretStorage::new(pool)
     }
 }
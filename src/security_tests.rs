@@ -1,16 +1,21 @@
 #[cfg(test)]
 mod tests {
     use crate::AppState;
+    use crate::audit::AuditLog;
     use crate::config::model::{AppConfig, IpLimitEntry, IpLimitsConfig};
+    use crate::config::reload::ConfigReloadStatus;
     use crate::dto::model::AppConfigDto;
     use crate::limits::LimitsService;
     use crate::metrics::service::MetricsServer;
+    use crate::middleware::body_limit::BodyLimit;
     use crate::middleware::client_ip::ClientIpExtractor;
     use crate::routes::{config::get_config_route, secret::store_secret_route};
     use crate::secret::model::{
         Secret, SecretContentType, SecretDownloadPolicy, SecretFileMetadata, SecretTTL,
     };
     use crate::secret::storage::MockSecretStorage;
+    use crate::shutdown::ShutdownSignal;
+    use crate::tests::config::get_test_app_config;
     use axum::{
         Router,
         body::Body,
@@ -31,59 +36,57 @@ mod tests {
                     ip: "192.168.1.100".to_string(),
                     message_max_length: Some(8192),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "192.168.1.2".to_string(),
                     message_max_length: Some(8192),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 },
                 IpLimitEntry {
                     ip: "10.0.0.0/8".to_string(),
                     message_max_length: Some(4096),
                     file_max_size: Some(52428800),
+                    rate_limits: None,
                 },
             ],
             trusted_proxies: vec![],
         };
 
         let config = AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
-            message_max_length: 1024,
-            file_upload_enabled: true,
-            file_max_size: 10485760,
             encrypted_message_max_length: Some(15485760),
-            redis_url: "redis://localhost".to_string(),
             ip_limits: Some(ip_limits),
+            ..get_test_app_config()
         };
 
         let limits_service = LimitsService::new(&config);
         let secret_storage = MockSecretStorage::new();
 
-        let body_limit = limits_service
-            .body_limit_as_usize()
-            .expect("Failed to calculate body limit");
-
-        let metrics_server = MetricsServer::new(config.clone(), body_limit);
+        let metrics_server = MetricsServer::new(config.clone(), limits_service.clone(), None);
 
         Arc::new(AppState {
             config,
             limits_service,
             secret_storage: Box::new(secret_storage),
-            body_limit,
             metrics_server,
+            shutdown: ShutdownSignal::new(),
+            config_reload: ConfigReloadStatus::default(),
+            audit_log: AuditLog::disabled(),
         })
     }
 
     fn create_security_test_router(app_state: Arc<AppState>) -> Router {
-        let body_limit = app_state.body_limit;
+        let body_limit_layer =
+            middleware::from_fn_with_state(app_state.limits_service.clone(), BodyLimit::middleware);
 
         Router::new()
             .route("/api/config", get(get_config_route))
             .route(
                 "/api/secret",
-                post(store_secret_route).layer(DefaultBodyLimit::max(body_limit)),
+                post(store_secret_route)
+                    .layer(DefaultBodyLimit::disable())
+                    .layer(body_limit_layer),
             )
             .layer(middleware::from_fn(ClientIpExtractor::middleware))
             .with_state(app_state)
@@ -194,9 +197,13 @@ mod tests {
                 r#type: "text/plain".to_string(),
                 size: 100_000_000,
             },
-            payload: "A".repeat(100_000_000), // Much larger than default limit
-            ttl: SecretTTL::OneHour,
+            encrypted_metadata: None,
+            payload: "A".repeat(100_000_000).into(), // Much larger than default limit
+            ttl: Some(SecretTTL::OneHour),
             download_policy: SecretDownloadPolicy::OneTime,
+            remaining_views: None,
+            revocation_token_hash: None,
+            chunks: None,
         };
 
         let request = Request::builder()
@@ -210,7 +217,7 @@ mod tests {
         let response = app.oneshot(request).await.unwrap();
 
         // Should fail due to size limits enforcement
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
     }
 
     #[tokio::test]
@@ -389,43 +396,35 @@ mod tests {
     async fn test_security_limits_service_isolation() {
         // Test that LimitsService properly isolates different configurations
         let config1 = AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
-            message_max_length: 1024,
-            file_upload_enabled: true,
-            file_max_size: 10485760,
             encrypted_message_max_length: Some(15485760),
-            redis_url: "redis://localhost".to_string(),
             ip_limits: Some(IpLimitsConfig {
                 enabled: true,
                 whitelist: vec![IpLimitEntry {
                     ip: "192.168.1.1".to_string(),
                     message_max_length: Some(8192),
                     file_max_size: Some(104857600),
+                    rate_limits: None,
                 }],
                 trusted_proxies: vec![],
             }),
+            ..get_test_app_config()
         };
 
         let config2 = AppConfig {
-            listen: "0.0.0.0:8080".to_string(),
-            log_level: "info".to_string(),
-            log_target: "stdout".to_string(),
             message_max_length: 2048,
-            file_upload_enabled: true,
             file_max_size: 20971520,
             encrypted_message_max_length: Some(31457280),
-            redis_url: "redis://localhost".to_string(),
             ip_limits: Some(IpLimitsConfig {
                 enabled: true,
                 whitelist: vec![IpLimitEntry {
                     ip: "192.168.1.1".to_string(),
                     message_max_length: Some(4096),
                     file_max_size: Some(52428800),
+                    rate_limits: None,
                 }],
                 trusted_proxies: vec![],
             }),
+            ..get_test_app_config()
         };
 
         let service1 = LimitsService::new(&config1);
@@ -0,0 +1,189 @@
+use log::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Interval;
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum ShutdownPhase {
+    Running,
+    /// Readiness reports not-ready, listeners still accept connections
+    Draining,
+    /// Listeners stop accepting connections
+    Stopping,
+}
+
+/// Shared shutdown state. Once triggered readiness reports not-ready at once,
+/// listeners stop accepting connections after pre-stop delay, so load balancers
+/// have time to remove the instance before connections are refused.
+#[derive(Clone)]
+pub struct ShutdownSignal {
+    sender: Arc<watch::Sender<ShutdownPhase>>,
+    pre_stop_delay: Duration,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownSignal {
+    pub fn new() -> ShutdownSignal {
+        let (sender, _) = watch::channel(ShutdownPhase::Running);
+        ShutdownSignal {
+            sender: Arc::new(sender),
+            pre_stop_delay: Duration::ZERO,
+        }
+    }
+
+    pub fn with_pre_stop_delay(mut self, pre_stop_delay: Duration) -> ShutdownSignal {
+        self.pre_stop_delay = pre_stop_delay;
+        self
+    }
+
+    pub fn trigger(&self) {
+        self.advance(ShutdownPhase::Draining);
+
+        if self.pre_stop_delay.is_zero() {
+            self.advance(ShutdownPhase::Stopping);
+            return;
+        }
+
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(shutdown.pre_stop_delay).await;
+            shutdown.advance(ShutdownPhase::Stopping);
+        });
+    }
+
+    fn advance(&self, phase: ShutdownPhase) {
+        self.sender.send_if_modified(|current| {
+            let modified = *current < phase;
+            if modified {
+                *current = phase;
+            }
+            modified
+        });
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.sender.borrow() >= ShutdownPhase::Draining
+    }
+
+    /// Resolves once listeners should stop accepting connections, pre-stop delay after trigger
+    pub async fn wait(&self) {
+        let mut receiver = self.sender.subscribe();
+        let _ = receiver
+            .wait_for(|phase| *phase == ShutdownPhase::Stopping)
+            .await;
+    }
+}
+
+/// Resolves on SIGTERM or SIGINT
+pub async fn wait_for_os_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("unable to listen for SIGINT");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("unable to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("SIGINT received"),
+        _ = terminate => info!("SIGTERM received"),
+    }
+}
+
+/// SIGHUP listener for configuration reloads, never fires on platforms without SIGHUP
+pub struct HangupSignal {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+}
+
+impl HangupSignal {
+    pub fn new() -> anyhow::Result<HangupSignal> {
+        Ok(HangupSignal {
+            #[cfg(unix)]
+            signal: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?,
+        })
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.signal.recv().await;
+
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Waits for the next tick of periodic check, never resolves when checks are disabled
+pub async fn tick(ticker: &mut Option<Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending::<()>().await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn waiters_should_be_released_on_trigger() {
+        let shutdown = ShutdownSignal::new();
+        assert!(!shutdown.is_draining());
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        });
+
+        shutdown.trigger();
+
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(shutdown.is_draining());
+
+        // Late waiters are released immediately
+        tokio::time::timeout(Duration::from_secs(1), shutdown.wait())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn waiters_should_be_released_after_pre_stop_delay() {
+        let shutdown = ShutdownSignal::new().with_pre_stop_delay(Duration::from_millis(300));
+
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.wait().await }
+        });
+
+        shutdown.trigger();
+        assert!(shutdown.is_draining());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!waiter.is_finished());
+
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -0,0 +1,18 @@
+use std::sync::Mutex;
+
+use crate::audit::AuditEvent;
+use crate::audit::sink::AuditSink;
+
+/// Keeps written audit events in memory
+#[derive(Default)]
+pub struct RecordingAuditSink {
+    pub events: Mutex<Vec<AuditEvent>>,
+}
+
+#[async_trait::async_trait]
+impl AuditSink for RecordingAuditSink {
+    async fn write(&self, event: &AuditEvent) -> anyhow::Result<()> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crate::config::model::{
+    AppConfig, AuditConfig, DEFAULT_CONFIG_RELOAD_INTERVAL_SECONDS, DEFAULT_FILE_CHUNK_MAX_SIZE,
+    DEFAULT_LOG_FORMAT, DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_SECONDS, DEFAULT_TTL_DEFAULT,
+    DEFAULT_TTL_MAX, DEFAULT_TTL_MIN, LogFileConfig, LookupGuardConfig, MetricsAccessConfig,
+    RateLimitsConfig, RedisPoolConfig, StorageConfig,
+};
+
+/// Valid config with default limits, tests override what they need with struct update syntax
+pub fn get_test_app_config() -> AppConfig {
+    AppConfig {
+        listen: "0.0.0.0:8080".to_string(),
+        log_level: "info".to_string(),
+        log_target: "stdout".to_string(),
+        log_format: DEFAULT_LOG_FORMAT.to_string(),
+        log_file: LogFileConfig::default(),
+        message_max_length: 1024,
+        file_upload_enabled: true,
+        file_max_size: 10485760,
+        file_chunk_max_size: DEFAULT_FILE_CHUNK_MAX_SIZE,
+        encrypted_message_max_length: None,
+        redis_url: "redis://localhost".to_string(),
+        redis_pool: RedisPoolConfig::default(),
+        storage: StorageConfig::default(),
+        ttl_min: DEFAULT_TTL_MIN,
+        ttl_max: DEFAULT_TTL_MAX,
+        ttl_default: DEFAULT_TTL_DEFAULT,
+        rate_limits: RateLimitsConfig::default(),
+        lookup_guard: LookupGuardConfig::default(),
+        audit: AuditConfig::default(),
+        ip_limits: None,
+        metrics_listen: None,
+        metrics_access: MetricsAccessConfig::default(),
+        shutdown_drain_timeout_seconds: DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_SECONDS,
+        shutdown_pre_stop_delay_seconds: 0,
+        tls: None,
+        config_reload_interval_seconds: DEFAULT_CONFIG_RELOAD_INTERVAL_SECONDS,
+    }
+}
@@ -1,3 +1,6 @@
+pub mod audit;
+pub mod config;
 pub mod logging;
 pub mod secret;
 pub mod string;
+pub mod tls;
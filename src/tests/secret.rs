@@ -1,4 +1,10 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use fake::{Fake, Faker};
+use sha2::{Digest, Sha256};
+
+use crate::config::model::{StorageEncryptionConfig, StorageKeyConfig};
+use crate::secret::encryption::StorageKeys;
 
 use crate::secret::model::{
     Secret, SecretContentType, SecretDownloadPolicy, SecretFileMetadata, SecretTTL,
@@ -9,14 +15,40 @@ use super::string::get_random_string;
 pub fn get_sample_secret() -> Secret {
     Secret {
         id: get_random_string(),
-        payload: Faker.fake::<String>(),
-        ttl: SecretTTL::OneHour,
+        payload: (Faker, 16..64).fake::<Vec<u8>>().into(),
+        ttl: Some(SecretTTL::OneHour),
         download_policy: SecretDownloadPolicy::Unlimited,
+        remaining_views: None,
+        revocation_token_hash: None,
+        chunks: None,
         content_type: SecretContentType::Text,
         metadata: SecretFileMetadata {
             name: get_random_string(),
             r#type: "text".to_string(),
             size: 0,
         },
+        encrypted_metadata: None,
+    }
+}
+
+/// Key bytes are derived from id, so keys with the same id match across calls
+pub fn get_sample_storage_key(id: &str) -> StorageKeyConfig {
+    StorageKeyConfig {
+        id: id.to_string(),
+        key: Some(STANDARD.encode(Sha256::digest(id.as_bytes()))),
+        key_file: None,
     }
 }
+
+pub fn get_sample_storage_keys(key_id: &str, key_ids: &[&str]) -> StorageKeys {
+    let config = StorageEncryptionConfig {
+        enabled: true,
+        key_id: key_id.to_string(),
+        keys: key_ids
+            .iter()
+            .map(|id| get_sample_storage_key(id))
+            .collect(),
+    };
+
+    StorageKeys::from_config(&config).unwrap().unwrap()
+}
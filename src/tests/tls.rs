@@ -0,0 +1,4 @@
+/// Self-signed certificate for `localhost` and `127.0.0.1`, valid for 100 years
+pub const TEST_CERT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/tls/cert.pem");
+
+pub const TEST_KEY_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/tls/key.pem");
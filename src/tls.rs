@@ -0,0 +1,155 @@
+use anyhow::Context;
+use axum_server::tls_rustls::RustlsConfig;
+use log::{error, info, warn};
+use rustls::ServerConfig;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+use crate::config::model::{TlsConfig, TlsVersion};
+use crate::shutdown::{HangupSignal, tick};
+
+/// Builds rustls server config from PEM certificate chain and private key
+pub fn load_server_config(config: &TlsConfig) -> anyhow::Result<Arc<ServerConfig>> {
+    let certs = load_certs(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+
+    let versions: &[&rustls::SupportedProtocolVersion] = match config.min_version {
+        TlsVersion::Tls12 => &[&rustls::version::TLS13, &rustls::version::TLS12],
+        TlsVersion::Tls13 => &[&rustls::version::TLS13],
+    };
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+    let mut server_config = ServerConfig::builder_with_provider(provider)
+        .with_protocol_versions(versions)?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("certificate doesn't match private key")?;
+
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(Arc::new(server_config))
+}
+
+/// Reloads certificates on SIGHUP and when files are modified, current certificates are kept on failure
+pub fn spawn_reloader(
+    config: TlsConfig,
+    rustls_config: RustlsConfig,
+) -> anyhow::Result<JoinHandle<()>> {
+    let mut hangup = HangupSignal::new()?;
+
+    let mut ticker = match config.reload_interval_seconds {
+        0 => None,
+        seconds => Some(tokio::time::interval(Duration::from_secs(seconds))),
+    };
+
+    Ok(tokio::spawn(async move {
+        let mut last_modified = get_modified_time(&config);
+
+        loop {
+            tokio::select! {
+                _ = hangup.recv() => info!("SIGHUP received, reloading TLS certificates"),
+                _ = tick(&mut ticker) => {
+                    let modified = get_modified_time(&config);
+                    if modified == last_modified {
+                        continue;
+                    }
+                    info!("TLS certificate files have changed, reloading");
+                }
+            }
+
+            last_modified = get_modified_time(&config);
+
+            match load_server_config(&config) {
+                Ok(server_config) => {
+                    rustls_config.reload_from_config(server_config);
+                    info!("TLS certificates have been reloaded");
+                }
+                Err(e) => error!(
+                    "unable to reload TLS certificates, current ones are kept: {:#}",
+                    e
+                ),
+            }
+        }
+    }))
+}
+
+fn load_certs(path: &str) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let file = File::open(path).with_context(|| format!("unable to open certificate '{path}'"))?;
+
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("unable to parse certificate '{path}'"))?;
+
+    if certs.is_empty() {
+        return Err(anyhow::anyhow!("no certificates found in '{path}'"));
+    }
+
+    Ok(certs)
+}
+
+fn load_private_key(path: &str) -> anyhow::Result<PrivateKeyDer<'static>> {
+    let file = File::open(path).with_context(|| format!("unable to open private key '{path}'"))?;
+
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .with_context(|| format!("unable to parse private key '{path}'"))?
+        .ok_or_else(|| anyhow::anyhow!("no private key found in '{path}'"))
+}
+
+/// Modification times of certificate and key files, `None` if any of them is unavailable
+fn get_modified_time(config: &TlsConfig) -> Option<(SystemTime, SystemTime)> {
+    let modified = |path: &str| match std::fs::metadata(path).and_then(|meta| meta.modified()) {
+        Ok(modified) => Some(modified),
+        Err(e) => {
+            warn!("unable to check TLS file '{}': {}", path, e);
+            None
+        }
+    };
+
+    Some((modified(&config.cert_path)?, modified(&config.key_path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::model::DEFAULT_TLS_RELOAD_INTERVAL_SECONDS;
+    use crate::tests::tls::{TEST_CERT_PATH, TEST_KEY_PATH};
+
+    fn get_config(min_version: TlsVersion) -> TlsConfig {
+        TlsConfig {
+            cert_path: TEST_CERT_PATH.to_string(),
+            key_path: TEST_KEY_PATH.to_string(),
+            min_version,
+            reload_interval_seconds: DEFAULT_TLS_RELOAD_INTERVAL_SECONDS,
+        }
+    }
+
+    #[test]
+    fn server_config_should_be_loaded_from_pem_files() {
+        assert!(load_server_config(&get_config(TlsVersion::Tls12)).is_ok());
+        assert!(load_server_config(&get_config(TlsVersion::Tls13)).is_ok());
+    }
+
+    #[test]
+    fn missing_files_should_be_reported() {
+        let mut config = get_config(TlsVersion::Tls12);
+        config.key_path = "/nonexistent/tls.key".to_string();
+
+        let error = load_server_config(&config).unwrap_err();
+        assert!(error.to_string().contains("/nonexistent/tls.key"));
+        assert_eq!(get_modified_time(&config), None);
+    }
+
+    #[test]
+    fn key_file_without_certificates_should_be_rejected() {
+        let mut config = get_config(TlsVersion::Tls12);
+        config.cert_path = TEST_KEY_PATH.to_string();
+
+        let error = load_server_config(&config).unwrap_err();
+        assert!(error.to_string().contains("no certificates found"));
+    }
+}